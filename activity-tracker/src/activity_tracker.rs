@@ -12,7 +12,7 @@ use log::{trace, warn};
 
 use common::database::Database;
 use common::display::Display;
-use common::platform::{DefaultPlatform, Platform};
+use common::platform::{DefaultPlatform, InputProcessor, Platform};
 use common::stylesheet::Stylesheet;
 use type_map::TypeMap;
 
@@ -21,6 +21,7 @@ use crate::view::App;
 #[derive(Debug)]
 pub struct ActivityTracker<P: Platform> {
     platform: P,
+    input: InputProcessor,
     display: P::Display,
     res: Resources,
     view: App<P::Battery>,
@@ -40,8 +41,12 @@ impl ActivityTracker<DefaultPlatform> {
 
         let view = App::new(display.bounding_box().into(), res.clone(), battery)?;
 
+        let mut input = InputProcessor::load();
+        input.set_swap_buttons(res.get::<Stylesheet>().swap_buttons);
+
         Ok(ActivityTracker {
             platform,
+            input,
             display,
             res,
             view,
@@ -73,7 +78,7 @@ impl ActivityTracker<DefaultPlatform> {
                 _ = sigterm.recv() => {
                     self.handle_command(Command::Exit).await?;
                 }
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
@@ -82,7 +87,7 @@ impl ActivityTracker<DefaultPlatform> {
 
             #[cfg(not(unix))]
             tokio::select! {
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
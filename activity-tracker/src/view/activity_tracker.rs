@@ -32,12 +32,13 @@ impl ActivityTracker {
 
         let styles = res.get::<Stylesheet>();
 
-        let list = SettingsList::new(
+        let mut list = SettingsList::new(
             Rect::new(x + 12, y, w - 24, h - 8 - ButtonIcon::diameter(&styles)),
             Vec::new(),
             Vec::new(),
             res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
         );
+        list.set_letter_jump(true);
 
         let button_hints = Row::new(
             Point::new(
@@ -1,31 +1,44 @@
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::process;
 
 use anyhow::Result;
+use common::alliumd::AlliumDRequest;
 use common::command::Command;
+use common::constants::TOAST_DURATION;
+use common::datetime::DateTimeSettings;
 use common::display::color::Color;
-use common::geom;
+use common::game_info::GameInfo;
+use common::geom::{self, Point, Rect};
+use common::kid_mode::KidModeSettings;
 use common::locale::{Locale, LocaleSettings};
+use common::profile::Profiles;
 use common::resources::Resources;
-use common::view::View;
+use common::save_sync::{Conflict, SaveSync};
+use common::view::{ButtonIcon, Toast, View};
 use embedded_graphics::prelude::*;
 use log::{info, trace, warn};
+use tokio::time::Instant;
 
 use common::database::Database;
 use common::display::Display;
-use common::platform::{DefaultPlatform, Platform};
+use common::platform::{DefaultPlatform, InputProcessor, Platform};
 use common::stylesheet::Stylesheet;
 use type_map::TypeMap;
 
 use crate::consoles::ConsoleMapper;
-use crate::view::App;
+use crate::entry::game::Game;
+use crate::view::{App, SaveConflicts};
 
 #[derive(Debug)]
 pub struct AlliumLauncher<P: Platform> {
     platform: P,
+    input: InputProcessor,
     display: P::Display,
     res: Resources,
     view: App<P::Battery>,
+    /// The toast being shown and when it should be hidden.
+    toast: Option<(Toast, Instant)>,
 }
 
 impl AlliumLauncher<DefaultPlatform> {
@@ -42,15 +55,22 @@ impl AlliumLauncher<DefaultPlatform> {
         res.insert(Stylesheet::load()?);
         res.insert(Locale::new(&LocaleSettings::load()?.lang));
         res.insert(Into::<geom::Size>::into(display.size()));
+        res.insert(KidModeSettings::load()?);
+        res.insert(DateTimeSettings::load()?);
         let res = Resources::new(res);
 
         let view = App::load_or_new(display.bounding_box().into(), res.clone(), battery)?;
 
+        let mut input = InputProcessor::load();
+        input.set_swap_buttons(res.get::<Stylesheet>().swap_buttons);
+
         Ok(AlliumLauncher {
             platform,
+            input,
             display,
             res,
             view,
+            toast: None,
         })
     }
 
@@ -65,33 +85,61 @@ impl AlliumLauncher<DefaultPlatform> {
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(100);
 
+        let mut started = false;
         loop {
-            if self.view.should_draw()
+            let mut drawn = self.view.should_draw()
                 && self
                     .view
-                    .draw(&mut self.display, &self.res.get::<Stylesheet>())?
-            {
+                    .draw(&mut self.display, &self.res.get::<Stylesheet>())?;
+            if let Some((toast, _)) = &mut self.toast {
+                if drawn {
+                    toast.set_should_draw();
+                }
+                if toast.should_draw() {
+                    drawn |= toast.draw(&mut self.display, &self.res.get::<Stylesheet>())?;
+                }
+            }
+            if drawn {
                 self.display.flush()?;
             }
+            if !started {
+                started = true;
+                // A pending update is only confirmed once the launcher is known to work
+                let result = AlliumDRequest::LauncherStarted
+                    .send()
+                    .await
+                    .and_then(|response| response.into_result());
+                if let Err(e) = result {
+                    warn!("failed to notify alliumd of launcher start: {}", e);
+                }
+            }
+
+            let toast_expires = self.toast.as_ref().map(|(_, expires)| *expires);
 
             #[cfg(unix)]
             tokio::select! {
                 _ = sigterm.recv() => {
                     self.handle_command(Command::Exit).await?;
                 }
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
+                _ = tokio::time::sleep_until(toast_expires.unwrap_or_else(Instant::now)), if toast_expires.is_some() => {
+                    self.hide_toast()?;
+                }
                 else => {}
             }
 
             #[cfg(not(unix))]
             tokio::select! {
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
+                _ = tokio::time::sleep_until(toast_expires.unwrap_or_else(Instant::now)), if toast_expires.is_some() => {
+                    self.hide_toast()?;
+                }
                 else => {}
             }
 
@@ -101,6 +149,23 @@ impl AlliumLauncher<DefaultPlatform> {
         }
     }
 
+    /// Launches the game at `path` the same way as selecting it in the library.
+    pub async fn launch(&mut self, path: PathBuf) -> Result<()> {
+        let mut game = Game::new(path);
+        let command = self.res.get::<ConsoleMapper>().launch_game(
+            &self.res.get(),
+            &self.res.get(),
+            &mut game,
+        )?;
+        match command {
+            Some(command) => self.handle_command(command).await,
+            None => {
+                warn!("can't launch {}", game.path.display());
+                Ok(())
+            }
+        }
+    }
+
     async fn handle_command(&mut self, command: Command) -> Result<()> {
         match command {
             Command::Exit => {
@@ -112,7 +177,24 @@ impl AlliumLauncher<DefaultPlatform> {
             }
             #[allow(unused_mut)]
             Command::Exec(mut cmd) => {
+                if let Some(conflicts) = self.sync_saves().await {
+                    info!("save sync has conflicts, asking before launching");
+                    self.view.show_save_conflicts(SaveConflicts::new(
+                        self.display.bounding_box().into(),
+                        self.res.clone(),
+                        conflicts,
+                    ));
+                    return Ok(());
+                }
                 info!("executing command: {:?}", cmd);
+                // alliumd can't tell when the launcher turns into the game, so it's told here
+                let result = AlliumDRequest::GameStarted
+                    .send()
+                    .await
+                    .and_then(|response| response.into_result());
+                if let Err(e) = result {
+                    warn!("failed to notify alliumd of game start: {}", e);
+                }
                 self.view.save()?;
                 self.display.clear(Color::new(0, 0, 0))?;
                 self.display.flush()?;
@@ -140,6 +222,7 @@ impl AlliumLauncher<DefaultPlatform> {
                 styles.save()?;
                 self.display.clear(styles.background_color)?;
                 self.display.save()?;
+                self.input.set_swap_buttons(styles.swap_buttons);
                 self.res.insert(*styles);
                 self.view.save()?;
                 self.view = App::load_or_new(
@@ -165,6 +248,49 @@ impl AlliumLauncher<DefaultPlatform> {
                     self.platform.battery()?,
                 )?;
             }
+            Command::SaveKidModeSettings(settings) => {
+                trace!("saving kid mode settings");
+                settings.save()?;
+                self.res.insert(*settings);
+                self.view.save()?;
+                self.view = App::load_or_new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    self.platform.battery()?,
+                )?;
+            }
+            Command::SwitchProfile(name) => {
+                trace!("switching profile");
+                self.view.save()?;
+                // Close the database so that the profile directory can be moved
+                self.res.insert(Database::default());
+                if let Err(e) = Profiles::load()?.switch(&name) {
+                    warn!("failed to switch profile: {}", e);
+                }
+                self.res.insert(Database::new()?);
+                let styles = Stylesheet::load()?;
+                self.display.clear(styles.background_color)?;
+                self.display.save()?;
+                self.input.set_swap_buttons(styles.swap_buttons);
+                self.res.insert(styles);
+                self.res.insert(Locale::new(&LocaleSettings::load()?.lang));
+                self.view = App::load_or_new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    self.platform.battery()?,
+                )?;
+            }
+            Command::Toast(text) => {
+                trace!("showing toast: {}", text);
+                self.hide_toast()?;
+                let Rect { x, y, w, h } = self.display.bounding_box().into();
+                let diameter = ButtonIcon::diameter(&self.res.get::<Stylesheet>());
+                let toast = Toast::new(
+                    Point::new(x + w as i32 / 2, y + h as i32 - diameter as i32 - 24),
+                    text,
+                );
+                self.toast = Some((toast, Instant::now() + TOAST_DURATION));
+            }
             Command::Redraw => {
                 trace!("redrawing");
                 self.display.load(self.display.bounding_box().into())?;
@@ -176,4 +302,30 @@ impl AlliumLauncher<DefaultPlatform> {
         }
         Ok(())
     }
+
+    /// Clears the toast, if any, and redraws what was under it.
+    fn hide_toast(&mut self) -> Result<()> {
+        if let Some((mut toast, _)) = self.toast.take() {
+            let rect = toast.bounding_box(&self.res.get::<Stylesheet>());
+            self.display.load(rect)?;
+            self.view.set_should_draw();
+        }
+        Ok(())
+    }
+
+    /// Syncs the saves of the game that is about to be launched. Returns the conflicts that
+    /// have to be resolved first, if any. Sync errors don't stop the game from launching.
+    async fn sync_saves(&self) -> Option<Vec<Conflict>> {
+        let result = async {
+            let (Some(save_sync), Some(game_info)) = (SaveSync::load()?, GameInfo::load()?) else {
+                return Ok(None);
+            };
+            let report = save_sync.sync_game(&game_info.path).await?;
+            anyhow::Ok((!report.conflicts.is_empty()).then_some(report.conflicts))
+        };
+        result.await.unwrap_or_else(|e| {
+            warn!("failed to sync saves: {}", e);
+            None
+        })
+    }
 }
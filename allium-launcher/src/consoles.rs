@@ -2,13 +2,16 @@ use std::path::PathBuf;
 use std::{collections::HashMap, path::Path};
 
 use anyhow::{anyhow, bail, Context, Result};
+use chrono::Duration;
 use common::command::Command;
 use common::database::Database;
 use common::game_info::GameInfo;
+use common::kid_mode::KidModeSettings;
+use common::locale::Locale;
 use serde::Deserialize;
 
 use common::constants::{ALLIUM_CONFIG_CONSOLES, ALLIUM_RETROARCH};
-use log::{debug, trace};
+use log::{debug, info, trace};
 
 use crate::entry::game::Game;
 
@@ -140,12 +143,37 @@ impl ConsoleMapper {
         None
     }
 
-    pub fn launch_game(&self, database: &Database, game: &mut Game) -> Result<Option<Command>> {
+    pub fn launch_game(
+        &self,
+        database: &Database,
+        locale: &Locale,
+        game: &mut Game,
+    ) -> Result<Option<Command>> {
+        self.launch_game_with_core(database, locale, game, None)
+    }
+
+    /// Launches a game with the given RetroArch core instead of the console's default core. If
+    /// kid mode's play time limit has been reached, returns a toast saying so instead.
+    pub fn launch_game_with_core(
+        &self,
+        database: &Database,
+        locale: &Locale,
+        game: &mut Game,
+        core: Option<&str>,
+    ) -> Result<Option<Command>> {
+        let remaining = KidModeSettings::load()?.play_time_remaining(database, Duration::zero())?;
+        if remaining.is_some_and(|r| r <= Duration::zero()) {
+            info!("Kid mode play time limit reached, not launching game");
+            return Ok(Some(Command::Toast(
+                locale.t("kid-mode-time-limit-reached"),
+            )));
+        }
+
         game.image();
         database.increment_play_count(&game.name, game.path.as_path(), game.image_ref())?;
 
-        let core = self.get_console(game.path.as_path());
-        Ok(if let Some(console) = core {
+        let console = self.get_console(game.path.as_path());
+        Ok(if let Some(console) = console {
             let game_info = if let Some(ref path) = console.path {
                 GameInfo::new(
                     game.name.clone(),
@@ -155,13 +183,15 @@ impl ConsoleMapper {
                     vec![game.path.display().to_string()],
                     false,
                 )
-            } else if let Some(retroarch_core) = console.cores.first() {
+            } else if let Some(retroarch_core) =
+                core.or_else(|| console.cores.first().map(String::as_str))
+            {
                 GameInfo::new(
                     game.name.clone(),
                     game.path.clone(),
                     game.image.clone().flatten(),
                     ALLIUM_RETROARCH.display().to_string(),
-                    vec![retroarch_core.clone(), game.path.display().to_string()],
+                    vec![retroarch_core.to_owned(), game.path.display().to_string()],
                     true,
                 )
             } else {
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     ffi::OsStr,
     fs::File,
     path::{Path, PathBuf},
@@ -6,6 +7,7 @@ use std::{
 
 use anyhow::{anyhow, Result};
 use common::constants::ALLIUM_GAMES_DIR;
+use log::warn;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -70,6 +72,11 @@ impl Directory {
         }
     }
 
+    /// Returns true if the entries of this directory are listed in a gamelist.
+    pub fn has_game_list(&self) -> bool {
+        self.path.join("gamelist.xml").exists() || self.path.join("miyoogamelist.xml").exists()
+    }
+
     pub fn entries(&self, console_mapper: &ConsoleMapper) -> Result<Vec<Entry>> {
         let gamelist = self.path.join("gamelist.xml");
         if gamelist.exists() {
@@ -93,6 +100,34 @@ impl Directory {
         Ok(entries)
     }
 
+    /// Returns the playable games in this directory and its subdirectories, skipping hidden paths
+    /// and directories that can't be read.
+    pub fn games(&self, console_mapper: &ConsoleMapper, hidden: &HashSet<PathBuf>) -> Vec<Game> {
+        let entries = match self.entries(console_mapper) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("failed to list games in {}: {}", self.path.display(), e);
+                return Vec::new();
+            }
+        };
+        let mut games = Vec::new();
+        for entry in entries {
+            if hidden.contains(entry.path()) {
+                continue;
+            }
+            match entry {
+                Entry::Directory(dir) => games.extend(dir.games(console_mapper, hidden)),
+                Entry::Game(game) => {
+                    if console_mapper.get_console(&game.path).is_some() {
+                        games.push(game);
+                    }
+                }
+                Entry::App(_) => {}
+            }
+        }
+        games
+    }
+
     pub fn parse_game_list(&self, game_list: &Path) -> Result<Vec<Entry>> {
         let file = File::open(game_list)?;
         let gamelist: GameList = serde_xml_rs::from_reader(file)?;
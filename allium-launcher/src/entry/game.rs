@@ -4,9 +4,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::Result;
-use common::constants::ALLIUM_GAMES_DIR;
-use log::info;
+use anyhow::{bail, Result};
+use common::constants::{ALLIUM_GAMES_DIR, ALLIUM_SAVE_STATES_DIR};
+use common::database::Database;
+use common::game_info::find_guide;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::entry::short_name;
@@ -80,6 +82,49 @@ impl Game {
         self.image_ref()
     }
 
+    /// Returns the size of the game on disk, in bytes. Directories are summed recursively.
+    pub fn size(&self) -> u64 {
+        fn size(path: &Path) -> u64 {
+            if path.is_dir() {
+                fs::read_dir(path)
+                    .map(|entries| entries.flatten().map(|e| size(&e.path())).sum())
+                    .unwrap_or(0)
+            } else {
+                fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+            }
+        }
+        size(&self.path)
+    }
+
+    /// Returns the RetroArch save states for this game. States are sorted into a folder per core,
+    /// so each core folder is searched.
+    pub fn save_states(&self) -> Vec<PathBuf> {
+        let Some(stem) = self.path.file_stem().and_then(OsStr::to_str) else {
+            return Vec::new();
+        };
+        let prefix = format!("{}.state", stem);
+
+        let mut dirs = vec![ALLIUM_SAVE_STATES_DIR.to_path_buf()];
+        if let Ok(entries) = fs::read_dir(ALLIUM_SAVE_STATES_DIR.as_path()) {
+            dirs.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+        }
+
+        let mut states = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            states.extend(entries.flatten().map(|e| e.path()).filter(|path| {
+                path.file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| name.starts_with(&prefix))
+                    && path.extension() != Some(OsStr::new("png"))
+            }));
+        }
+        states.sort();
+        states
+    }
+
     /// Attempts to resync the game path with the games directory. Returns the old path if it changed.
     pub fn resync(&mut self) -> Result<Option<PathBuf>> {
         Ok(if self.path.exists() {
@@ -95,6 +140,117 @@ impl Game {
             None
         })
     }
+
+    /// Renames the game file, along with its box art and guide, and updates the database.
+    pub fn rename(&mut self, database: &Database, name: &str) -> Result<()> {
+        if name.is_empty() || name.contains(std::path::MAIN_SEPARATOR) {
+            bail!("Invalid file name: {:?}", name);
+        }
+
+        let path = self.path.with_file_name(if self.extension.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", name, self.extension)
+        });
+        if path.exists() {
+            bail!("File already exists: {:?}", path);
+        }
+
+        // Files renamed so far, to be moved back if a later step fails
+        let mut renamed = Vec::new();
+        match self.rename_to(database, name, path, &mut renamed) {
+            Ok(game) => {
+                *self = game;
+                Ok(())
+            }
+            Err(e) => {
+                for (from, to) in renamed.into_iter().rev() {
+                    if let Err(e) = fs::rename(&to, &from) {
+                        warn!("failed to move {:?} back to {:?}: {}", to, from, e);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn rename_to(
+        &mut self,
+        database: &Database,
+        name: &str,
+        path: PathBuf,
+        renamed: &mut Vec<(PathBuf, PathBuf)>,
+    ) -> Result<Game> {
+        let image = self.image().map(Path::to_path_buf);
+        let guide = find_guide(&self.path);
+
+        rename_file(&self.path, &path, renamed)?;
+        let image = image
+            .map(|image| rename_sibling(&image, name, renamed))
+            .transpose()?;
+        if let Some(guide) = guide {
+            rename_sibling(&guide, name, renamed)?;
+        }
+
+        let mut game = Game::new(path);
+        database.rename_game(&self.path, &game.path, &game.name, image.as_deref())?;
+        game.image = Some(image);
+        Ok(game)
+    }
+
+    /// Deletes the game file, along with its box art and guide, and removes it from the database.
+    /// Once the game file is gone, failing to remove the box art or guide is only logged, so that
+    /// the database never keeps a deleted game.
+    pub fn delete(&mut self, database: &Database) -> Result<()> {
+        let image = match &self.image {
+            Some(image) => image.clone(),
+            None => self.image().map(Path::to_path_buf),
+        };
+        let guide = find_guide(&self.path);
+
+        if self.path.is_dir() {
+            fs::remove_dir_all(&self.path)?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        database.delete_game(&self.path)?;
+        database.unhide(&self.path)?;
+
+        if let Some(image) = image {
+            if let Err(e) = fs::remove_file(&image) {
+                warn!("failed to remove box art {:?}: {}", image, e);
+            }
+        }
+        if let Some(guide) = guide {
+            if let Err(e) = fs::remove_file(&guide) {
+                warn!("failed to remove guide {:?}: {}", guide, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Renames a file to the given stem, keeping its extension. Returns the new path.
+fn rename_sibling(
+    path: &Path,
+    stem: &str,
+    renamed: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<PathBuf> {
+    let new_path = match path.extension().and_then(OsStr::to_str) {
+        Some(extension) => path.with_file_name(format!("{}.{}", stem, extension)),
+        None => path.with_file_name(stem),
+    };
+    rename_file(path, &new_path, renamed)?;
+    Ok(new_path)
+}
+
+/// Renames a file, recording it in `renamed` so that it can be undone.
+fn rename_file(from: &Path, to: &Path, renamed: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
+    fs::rename(from, to)?;
+    renamed.push((from.to_path_buf(), to.to_path_buf()));
+    Ok(())
 }
 
 impl Ord for Game {
@@ -126,3 +282,85 @@ fn find(path: &Path, name: &OsStr) -> Result<Option<PathBuf>> {
     }
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use common::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_rename_and_delete() {
+        let dir = temp_dir("launcher-game-rename-and-delete");
+        fs::create_dir_all(dir.join("GBA/Imgs")).unwrap();
+        fs::create_dir_all(dir.join("GBA/Guides")).unwrap();
+        fs::write(dir.join("GBA/Game One.gba"), "").unwrap();
+        fs::write(dir.join("GBA/Imgs/Game One.png"), "").unwrap();
+        fs::write(dir.join("GBA/Guides/Game One.txt"), "").unwrap();
+
+        let database = Database::in_memory().unwrap();
+        let mut game = Game::new(dir.join("GBA/Game One.gba"));
+        game.image();
+        database
+            .increment_play_count(&game.name, &game.path, game.image_ref())
+            .unwrap();
+
+        game.rename(&database, "Game Two").unwrap();
+        assert_eq!(game.name, "Game Two");
+        assert_eq!(game.path, dir.join("GBA/Game Two.gba"));
+        assert_eq!(
+            game.image_ref(),
+            Some(dir.join("GBA/Imgs/Game Two.png").as_path())
+        );
+        assert!(!dir.join("GBA/Game One.gba").exists());
+        assert!(dir.join("GBA/Guides/Game Two.txt").exists());
+        let row = database
+            .select_game(&game.path.display().to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.name, "Game Two");
+        assert_eq!(row.image.as_deref(), game.image_ref());
+
+        // Renaming the guide fails, so the game and its box art are moved back
+        fs::create_dir_all(dir.join("GBA/Guides/Game Three.txt/notes")).unwrap();
+        assert!(game.rename(&database, "Game Three").is_err());
+        assert_eq!(game.name, "Game Two");
+        assert!(dir.join("GBA/Game Two.gba").exists());
+        assert!(dir.join("GBA/Imgs/Game Two.png").exists());
+        assert!(dir.join("GBA/Guides/Game Two.txt").exists());
+        assert!(!dir.join("GBA/Game Three.gba").exists());
+        assert!(database
+            .select_game(&game.path.display().to_string())
+            .unwrap()
+            .is_some());
+
+        game.delete(&database).unwrap();
+        assert!(!dir.join("GBA/Game Two.gba").exists());
+        assert!(!dir.join("GBA/Imgs/Game Two.png").exists());
+        assert!(!dir.join("GBA/Guides/Game Two.txt").exists());
+        assert!(database
+            .select_game(&game.path.display().to_string())
+            .unwrap()
+            .is_none());
+
+        // Box art that can't be removed doesn't keep the game in the database
+        fs::write(dir.join("GBA/Game Four.gba"), "").unwrap();
+        fs::write(dir.join("GBA/Imgs/Game Four.png"), "").unwrap();
+        let mut game = Game::new(dir.join("GBA/Game Four.gba"));
+        game.image();
+        database
+            .increment_play_count(&game.name, &game.path, game.image_ref())
+            .unwrap();
+        fs::remove_file(dir.join("GBA/Imgs/Game Four.png")).unwrap();
+        fs::create_dir_all(dir.join("GBA/Imgs/Game Four.png/nested")).unwrap();
+        game.delete(&database).unwrap();
+        assert!(!dir.join("GBA/Game Four.gba").exists());
+        assert!(dir.join("GBA/Imgs/Game Four.png").exists());
+        assert!(database
+            .select_game(&game.path.display().to_string())
+            .unwrap()
+            .is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
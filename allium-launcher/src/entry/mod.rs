@@ -80,6 +80,14 @@ impl Entry {
         }
     }
 
+    pub fn path(&self) -> &Path {
+        match self {
+            Entry::Game(game) => &game.path,
+            Entry::Directory(directory) => &directory.path,
+            Entry::App(app) => &app.directory,
+        }
+    }
+
     pub fn image(&mut self) -> Option<&Path> {
         match self {
             Entry::Game(game) => game.image(),
@@ -7,6 +7,8 @@ mod consoles;
 mod entry;
 mod view;
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 
 use allium_launcher::AlliumLauncher;
@@ -19,6 +21,15 @@ async fn main() -> Result<()> {
 
     let platform = DefaultPlatform::new()?;
     let mut app = AlliumLauncher::new(platform)?;
+
+    // alliumd restarts the launcher with `--launch <path>` to launch a game
+    let mut args = std::env::args().skip(1);
+    if let (Some(flag), Some(path)) = (args.next(), args.next()) {
+        if flag == "--launch" {
+            app.launch(PathBuf::from(path)).await?;
+        }
+    }
+
     app.run_event_loop().await?;
     Ok(())
 }
@@ -8,6 +8,7 @@ use common::command::Command;
 use common::constants::{ALLIUM_LAUNCHER_STATE, ALLIUM_SD_ROOT};
 use common::display::Display;
 use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
@@ -20,7 +21,7 @@ use tokio::sync::mpsc::Sender;
 use crate::view::browser::BrowserState;
 use crate::view::settings::SettingsState;
 use crate::view::Recents;
-use crate::view::{Browser, Settings};
+use crate::view::{Browser, SaveConflicts, Settings};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AppState {
@@ -40,6 +41,10 @@ where
     views: (Recents, Browser, Browser, Settings),
     selected: usize,
     tabs: Row<Label<String>>,
+    /// Number of tabs shown. The settings tab is hidden in kid mode.
+    tab_count: usize,
+    /// Shown over everything else until the save conflicts of a game are resolved.
+    save_conflicts: Option<SaveConflicts>,
     dirty: bool,
 }
 
@@ -56,13 +61,20 @@ where
     ) -> Result<Self> {
         let Rect { x, y, w, h: _h } = rect;
 
+        let tab_count = if res.get::<KidModeSettings>().enabled {
+            3
+        } else {
+            4
+        };
+        let selected = if selected < tab_count { selected } else { 1 };
+
         let battery_indicator = BatteryIndicator::new(Point::new(w as i32 - 12, y + 8), battery);
 
         let mut tabs = Row::new(
             Point::new(x + 12, y + 8),
             {
                 let locale = res.get::<Locale>();
-                vec![
+                let mut tabs = vec![
                     Label::new(
                         Point::zero(),
                         locale.t("tab-recents"),
@@ -71,13 +83,16 @@ where
                     ),
                     Label::new(Point::zero(), locale.t("tab-games"), Alignment::Left, None),
                     Label::new(Point::zero(), locale.t("tab-apps"), Alignment::Left, None),
-                    Label::new(
+                ];
+                if tab_count > 3 {
+                    tabs.push(Label::new(
                         Point::zero(),
                         locale.t("tab-settings"),
                         Alignment::Left,
                         None,
-                    ),
-                ]
+                    ));
+                }
+                tabs
             },
             Alignment::Left,
             12,
@@ -92,6 +107,8 @@ where
             selected,
             battery_indicator,
             tabs,
+            tab_count,
+            save_conflicts: None,
             dirty: true,
         })
     }
@@ -162,6 +179,11 @@ where
         Ok(())
     }
 
+    /// Shows the save conflicts that have to be resolved before a game is launched.
+    pub fn show_save_conflicts(&mut self, save_conflicts: SaveConflicts) {
+        self.save_conflicts = Some(save_conflicts);
+    }
+
     fn view(&self) -> &dyn View {
         match self.selected {
             0 => &self.views.0,
@@ -197,12 +219,12 @@ where
     }
 
     fn next(&mut self) {
-        let selected = (self.selected + 1).rem_euclid(4);
+        let selected = (self.selected + 1).rem_euclid(self.tab_count);
         self.tab_change(selected)
     }
 
     fn prev(&mut self) {
-        let selected = (self.selected as isize - 1).rem_euclid(4);
+        let selected = (self.selected as isize - 1).rem_euclid(self.tab_count as isize);
         self.tab_change(selected as usize)
     }
 }
@@ -217,6 +239,10 @@ where
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
+        if let Some(save_conflicts) = &mut self.save_conflicts {
+            return Ok(save_conflicts.should_draw() && save_conflicts.draw(display, styles)?);
+        }
+
         if self.dirty {
             display.load(self.bounding_box(styles))?;
             self.dirty = false;
@@ -238,10 +264,18 @@ where
     }
 
     fn should_draw(&self) -> bool {
-        self.battery_indicator.should_draw() || self.view().should_draw() || self.tabs.should_draw()
+        self.save_conflicts
+            .as_ref()
+            .is_some_and(|s| s.should_draw())
+            || self.battery_indicator.should_draw()
+            || self.view().should_draw()
+            || self.tabs.should_draw()
     }
 
     fn set_should_draw(&mut self) {
+        if let Some(save_conflicts) = self.save_conflicts.as_mut() {
+            save_conflicts.set_should_draw();
+        }
         self.dirty = true;
         self.battery_indicator.set_should_draw();
         self.view_mut().set_should_draw();
@@ -254,6 +288,25 @@ where
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        if let Some(save_conflicts) = self.save_conflicts.as_mut() {
+            save_conflicts
+                .handle_key_event(event, commands, bubble)
+                .await?;
+            let mut close = false;
+            bubble.retain(|cmd| match cmd {
+                Command::CloseView => {
+                    close = true;
+                    false
+                }
+                _ => true,
+            });
+            if close {
+                self.save_conflicts = None;
+                self.set_should_draw();
+            }
+            return Ok(true);
+        }
+
         if self
             .view_mut()
             .handle_key_event(event, commands, bubble)
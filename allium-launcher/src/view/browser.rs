@@ -1,21 +1,31 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use common::command::Command;
-use common::constants::{IMAGE_WIDTH, SELECTION_MARGIN};
+use common::constants::{ALLIUM_GAMES_DIR, IMAGE_WIDTH, SELECTION_MARGIN};
+use common::database::Database;
 use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::{Stylesheet, StylesheetColor};
-use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Row, ScrollList, View};
+use common::view::{
+    ButtonHint, ButtonIcon, Image, ImageMode, Keyboard, PinInput, Row, ScrollList, View,
+};
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
+use embedded_graphics::Drawable;
+use log::warn;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 
 use crate::consoles::ConsoleMapper;
 use crate::entry::directory::Directory;
 use crate::entry::Entry;
+use crate::view::{GameDetails, RandomGame};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserState {
@@ -33,6 +43,15 @@ pub struct Browser {
     list: ScrollList,
     image: Image,
     button_hints: Row<ButtonHint<String>>,
+    /// Paths hidden from the launcher.
+    hidden: HashSet<PathBuf>,
+    show_hidden: bool,
+    menu: Option<ScrollList>,
+    menu_actions: Vec<MenuAction>,
+    keyboard: Option<Keyboard>,
+    pin_input: Option<PinInput>,
+    /// Full screen view shown on top of the listing, e.g. game details.
+    overlay: Option<Box<dyn View>>,
     pub child: Option<Box<Browser>>,
 }
 
@@ -42,7 +61,8 @@ impl Browser {
 
         let styles = res.get::<Stylesheet>();
 
-        let entries = directory.entries(&res.get())?;
+        let hidden = res.get::<Database>().select_hidden()?;
+        let entries = Self::load_entries(&res, &directory, &hidden, false)?;
         let mut list = ScrollList::new(
             Rect::new(
                 x + 12,
@@ -54,6 +74,7 @@ impl Browser {
             Alignment::Left,
             res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
         );
+        list.set_letter_jump(true);
         list.select(selected);
 
         let mut image = Image::empty(
@@ -105,10 +126,43 @@ impl Browser {
             list,
             image,
             button_hints,
+            hidden,
+            show_hidden: false,
+            menu: None,
+            menu_actions: Vec::new(),
+            keyboard: None,
+            pin_input: None,
+            overlay: None,
             child: None,
         })
     }
 
+    fn load_entries(
+        res: &Resources,
+        directory: &Directory,
+        hidden: &HashSet<PathBuf>,
+        show_hidden: bool,
+    ) -> Result<Vec<Entry>> {
+        let mut entries = directory.entries(&res.get())?;
+        if !show_hidden {
+            entries.retain(|entry| !hidden.contains(entry.path()));
+        }
+        let kid_mode = res.get::<KidModeSettings>();
+        entries.retain(|entry| kid_mode.is_visible(entry.path()));
+        Ok(entries)
+    }
+
+    fn reload_entries(&mut self) -> Result<()> {
+        self.hidden = self.res.get::<Database>().select_hidden()?;
+        self.entries =
+            Self::load_entries(&self.res, &self.directory, &self.hidden, self.show_hidden)?;
+        self.list.set_items(
+            self.entries.iter().map(|e| e.name().to_string()).collect(),
+            !self.entries.is_empty(),
+        );
+        Ok(())
+    }
+
     pub fn load(rect: Rect, res: Resources, state: BrowserState) -> Result<Self> {
         let mut browser = Self::new(rect, res.clone(), state.directory, state.selected)?;
         if let Some(child) = state.child {
@@ -133,10 +187,11 @@ impl Browser {
                     self.child = Some(Box::new(child));
                 }
                 Entry::Game(game) => {
-                    let command = self
-                        .res
-                        .get::<ConsoleMapper>()
-                        .launch_game(&self.res.get(), game)?;
+                    let command = self.res.get::<ConsoleMapper>().launch_game(
+                        &self.res.get(),
+                        &self.res.get(),
+                        game,
+                    )?;
                     if let Some(cmd) = command {
                         commands.send(cmd).await?;
                     }
@@ -148,6 +203,173 @@ impl Browser {
         }
         Ok(())
     }
+
+    fn open_menu(&mut self, actions: Vec<MenuAction>) {
+        let Rect { x, y, w, h } = self.rect;
+        let styles = self.res.get::<Stylesheet>();
+        let locale = self.res.get::<Locale>();
+
+        let labels: Vec<String> = actions.iter().map(|a| a.label(&locale)).collect();
+
+        let height = labels.len() as u32 * (styles.ui_font.size + SELECTION_MARGIN);
+
+        let mut menu = ScrollList::new(
+            Rect::new(
+                x + 12 + (w as i32 - 24) / 6,
+                (y + h as i32 - height as i32) / 2,
+                (w - 24) * 2 / 3,
+                height,
+            ),
+            labels,
+            Alignment::Center,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        menu.set_background_color(StylesheetColor::BackgroundHighlightBlend);
+        self.menu = Some(menu);
+        self.menu_actions = actions;
+    }
+
+    fn entry_actions(&self) -> Vec<MenuAction> {
+        let mut actions = Vec::new();
+        if self.res.get::<KidModeSettings>().enabled {
+            if let Some(Entry::Game(_)) = self.entries.get(self.list.selected()) {
+                actions.push(MenuAction::Details);
+            }
+            actions.push(MenuAction::RandomGame);
+            if self.directory.path != *ALLIUM_GAMES_DIR {
+                actions.push(MenuAction::RandomGameHere);
+            }
+            actions.push(MenuAction::ExitKidMode);
+            return actions;
+        }
+        if let Some(entry) = self.entries.get(self.list.selected()) {
+            if let Entry::Game(_) = entry {
+                actions.push(MenuAction::Details);
+                if !self.directory.has_game_list() {
+                    actions.push(MenuAction::Rename);
+                }
+                actions.push(MenuAction::Delete);
+            }
+            if self.hidden.contains(entry.path()) {
+                actions.push(MenuAction::Unhide);
+            } else {
+                actions.push(MenuAction::Hide);
+            }
+        }
+        actions.push(MenuAction::RandomGame);
+        if self.directory.path != *ALLIUM_GAMES_DIR {
+            actions.push(MenuAction::RandomGameHere);
+        }
+        if self.show_hidden {
+            actions.push(MenuAction::HideHidden);
+        } else {
+            actions.push(MenuAction::ShowHidden);
+        }
+        actions
+    }
+
+    async fn run_action(&mut self, action: MenuAction, commands: Sender<Command>) -> Result<()> {
+        match action {
+            MenuAction::Details => {
+                if let Some(Entry::Game(game)) = self.entries.get(self.list.selected()) {
+                    self.overlay = Some(Box::new(GameDetails::new(
+                        self.rect,
+                        self.res.clone(),
+                        game.clone(),
+                    )?));
+                }
+            }
+            MenuAction::RandomGame => {
+                let mut random_game = RandomGame::new(self.rect, self.res.clone())?;
+                random_game.load(Directory::default(), commands.clone())?;
+                self.overlay = Some(Box::new(random_game));
+            }
+            MenuAction::RandomGameHere => {
+                let mut random_game = RandomGame::new(self.rect, self.res.clone())?;
+                random_game.load(self.directory.clone(), commands.clone())?;
+                self.overlay = Some(Box::new(random_game));
+            }
+            MenuAction::Rename => {
+                if let Some(Entry::Game(game)) = self.entries.get(self.list.selected()) {
+                    let name = game
+                        .path
+                        .file_stem()
+                        .and_then(std::ffi::OsStr::to_str)
+                        .unwrap_or_default()
+                        .to_string();
+                    self.keyboard = Some(Keyboard::new(self.res.clone(), name, false));
+                }
+            }
+            MenuAction::Delete => {
+                self.open_menu(vec![MenuAction::ConfirmDelete, MenuAction::Cancel]);
+            }
+            MenuAction::ConfirmDelete => {
+                if let Some(Entry::Game(game)) = self.entries.get_mut(self.list.selected()) {
+                    if let Err(e) = game.delete(&self.res.get::<Database>()) {
+                        warn!("failed to delete game {:?}: {}", game.path, e);
+                    }
+                }
+                self.reload_entries()?;
+            }
+            MenuAction::Hide => {
+                if let Some(entry) = self.entries.get(self.list.selected()) {
+                    self.res.get::<Database>().hide(entry.path())?;
+                }
+                self.reload_entries()?;
+            }
+            MenuAction::Unhide => {
+                if let Some(entry) = self.entries.get(self.list.selected()) {
+                    self.res.get::<Database>().unhide(entry.path())?;
+                }
+                self.reload_entries()?;
+            }
+            MenuAction::ShowHidden | MenuAction::HideHidden => {
+                self.show_hidden = action == MenuAction::ShowHidden;
+                self.reload_entries()?;
+            }
+            MenuAction::ExitKidMode => {
+                let title = if self.res.get::<KidModeSettings>().is_recovery {
+                    "kid-mode-enter-recovery-pin"
+                } else {
+                    "kid-mode-enter-pin"
+                };
+                let title = self.res.get::<Locale>().t(title);
+                self.pin_input = Some(PinInput::new(self.res.clone(), title));
+            }
+            MenuAction::Cancel => {}
+        }
+        commands.send(Command::Redraw).await?;
+        Ok(())
+    }
+
+    fn rename_entry(&mut self, name: &str) -> Result<()> {
+        if let Some(Entry::Game(game)) = self.entries.get_mut(self.list.selected()) {
+            if let Err(e) = game.rename(&self.res.get::<Database>(), name) {
+                warn!("failed to rename game {:?}: {}", game.path, e);
+            }
+        }
+        self.reload_entries()
+    }
+
+    async fn exit_kid_mode(&mut self, pin: &[Key], commands: Sender<Command>) -> Result<()> {
+        let mut settings = self.res.get::<KidModeSettings>().clone();
+        if settings.pin.is_empty() || settings.pin != pin {
+            warn!("wrong kid mode PIN entered");
+            let text = self.res.get::<Locale>().t("kid-mode-wrong-pin");
+            commands.send(Command::Toast(text)).await?;
+            return Ok(());
+        }
+        settings.enabled = false;
+        if settings.is_recovery {
+            // A new PIN is asked for the next time kid mode is enabled
+            settings.pin.clear();
+            settings.is_recovery = false;
+        }
+        commands
+            .send(Command::SaveKidModeSettings(Box::new(settings)))
+            .await?;
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
@@ -161,6 +383,44 @@ impl View for Browser {
             return Ok(child.should_draw() && child.draw(display, styles)?);
         }
 
+        if let Some(overlay) = &mut self.overlay {
+            return Ok(overlay.should_draw() && overlay.draw(display, styles)?);
+        }
+
+        if let Some(keyboard) = &mut self.keyboard {
+            return Ok(keyboard.should_draw() && keyboard.draw(display, styles)?);
+        }
+
+        if let Some(pin_input) = &mut self.pin_input {
+            return Ok(pin_input.should_draw() && pin_input.draw(display, styles)?);
+        }
+
+        if let Some(menu) = &mut self.menu {
+            if menu.should_draw() {
+                let mut rect = menu
+                    .children_mut()
+                    .iter_mut()
+                    .map(|v| v.bounding_box(styles))
+                    .reduce(|acc, r| acc.union(&r))
+                    .unwrap_or_default();
+                rect.y -= 12;
+                rect.h += 24;
+                rect.x -= 24;
+                rect.w += 48;
+                RoundedRectangle::new(
+                    rect.into(),
+                    CornerRadii::new(Size::new_equal((styles.ui_font.size + 8) / 2)),
+                )
+                .into_styled(PrimitiveStyle::with_fill(
+                    StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+                ))
+                .draw(display)?;
+                menu.draw(display, styles)?;
+                return Ok(true);
+            }
+            return Ok(false);
+        }
+
         let mut drawn = false;
 
         if self.list.should_draw() && self.list.draw(display, styles)? {
@@ -190,6 +450,10 @@ impl View for Browser {
 
     fn should_draw(&self) -> bool {
         self.child.as_ref().map_or(false, |c| c.should_draw())
+            || self.overlay.as_ref().map_or(false, |o| o.should_draw())
+            || self.keyboard.as_ref().map_or(false, |k| k.should_draw())
+            || self.pin_input.as_ref().is_some_and(|p| p.should_draw())
+            || self.menu.as_ref().map_or(false, |m| m.should_draw())
             || self.list.should_draw()
             || self.button_hints.should_draw()
     }
@@ -198,6 +462,18 @@ impl View for Browser {
         if let Some(c) = self.child.as_mut() {
             c.set_should_draw()
         }
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.set_should_draw();
+        }
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+        if let Some(pin_input) = self.pin_input.as_mut() {
+            pin_input.set_should_draw();
+        }
+        if let Some(menu) = self.menu.as_mut() {
+            menu.set_should_draw();
+        }
         self.list.set_should_draw();
         self.image.set_should_draw();
         self.button_hints.set_should_draw();
@@ -224,11 +500,113 @@ impl View for Browser {
             return Ok(false);
         }
 
+        if let Some(overlay) = self.overlay.as_mut() {
+            if overlay
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.overlay = None;
+                    commands.send(Command::Redraw).await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut name = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        name = value.clone().as_string();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.keyboard = None;
+                    self.set_should_draw();
+                }
+                if let Some(name) = name {
+                    self.rename_entry(&name)?;
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(pin_input) = self.pin_input.as_mut() {
+            if pin_input
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut pin = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        pin = value.clone().as_keys();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.pin_input = None;
+                    self.set_should_draw();
+                }
+                if let Some(pin) = pin {
+                    self.exit_kid_mode(&pin, commands).await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(menu) = self.menu.as_mut() {
+            match event {
+                KeyEvent::Pressed(Key::Select | Key::B) => {
+                    self.menu = None;
+                    commands.send(Command::Redraw).await?;
+                }
+                KeyEvent::Pressed(Key::A) => {
+                    let action = self.menu_actions[menu.selected()];
+                    self.menu = None;
+                    self.run_action(action, commands).await?;
+                }
+                _ => {
+                    menu.handle_key_event(event, commands, bubble).await?;
+                }
+            }
+            return Ok(true);
+        }
+
         match event {
             KeyEvent::Pressed(Key::A) => {
                 self.select_entry(commands).await?;
                 Ok(true)
             }
+            KeyEvent::Pressed(Key::Select) => {
+                let actions = self.entry_actions();
+                self.open_menu(actions);
+                Ok(true)
+            }
             KeyEvent::Pressed(Key::B) => {
                 bubble.push_back(Command::CloseView);
                 Ok(true)
@@ -261,3 +639,38 @@ impl View for Browser {
         unimplemented!()
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuAction {
+    Details,
+    RandomGame,
+    RandomGameHere,
+    Rename,
+    Delete,
+    ConfirmDelete,
+    Cancel,
+    Hide,
+    Unhide,
+    ShowHidden,
+    HideHidden,
+    ExitKidMode,
+}
+
+impl MenuAction {
+    fn label(&self, locale: &Locale) -> String {
+        match self {
+            MenuAction::Details => locale.t("game-details"),
+            MenuAction::RandomGame => locale.t("browser-random-game"),
+            MenuAction::RandomGameHere => locale.t("browser-random-game-here"),
+            MenuAction::Rename => locale.t("browser-rename"),
+            MenuAction::Delete => locale.t("browser-delete"),
+            MenuAction::ConfirmDelete => locale.t("browser-delete-confirm"),
+            MenuAction::Cancel => locale.t("button-cancel"),
+            MenuAction::Hide => locale.t("browser-hide"),
+            MenuAction::Unhide => locale.t("browser-unhide"),
+            MenuAction::ShowHidden => locale.t("browser-show-hidden"),
+            MenuAction::HideHidden => locale.t("browser-hide-hidden"),
+            MenuAction::ExitKidMode => locale.t("browser-exit-kid-mode"),
+        }
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, IMAGE_WIDTH, SELECTION_MARGIN};
+use common::database::Database;
+use common::datetime::DateTimeSettings;
+use common::display::Display;
+use common::game_info::find_guide;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::save_backup::SaveBackup;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{
+    ButtonHint, ButtonIcon, CoreOptionsEditor, Image, ImageMode, Label, Row, ScrollList,
+    SettingsList, VideoSettingsEditor, View,
+};
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
+use embedded_graphics::Drawable;
+use tokio::sync::mpsc::Sender;
+
+use crate::consoles::ConsoleMapper;
+use crate::entry::game::Game;
+use crate::view::SaveBackups;
+
+/// Shows information about a single game, and lets it be launched or favorited.
+#[derive(Debug)]
+pub struct GameDetails {
+    rect: Rect,
+    res: Resources,
+    game: Game,
+    /// RetroArch cores the game can be launched with. First is default.
+    cores: Vec<String>,
+    favorite: bool,
+    title: Label<String>,
+    list: SettingsList,
+    image: Image,
+    menu: Option<ScrollList>,
+    backups: Option<SaveBackups>,
+    core_options: Option<CoreOptionsEditor>,
+    video_settings: Option<VideoSettingsEditor>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl GameDetails {
+    pub fn new(rect: Rect, res: Resources, mut game: Game) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+        let datetime = res.get::<DateTimeSettings>();
+        let database = res.get::<Database>();
+        let console_mapper = res.get::<ConsoleMapper>();
+
+        let console = console_mapper.get_console(&game.path);
+        let cores = console
+            .filter(|c| c.path.is_none())
+            .map(|c| c.cores.clone())
+            .unwrap_or_default();
+        let core = console.and_then(|c| {
+            c.path
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|p| p.to_string_lossy().to_string())
+                .or_else(|| c.cores.first().cloned())
+        });
+
+        let row = database.select_game(&game.path.display().to_string())?;
+        let favorite = database.is_favorite(&game.path)?;
+        let last_played = database.select_last_played_at(&game.path)?;
+
+        let play_time = row.as_ref().map(|r| r.play_time).unwrap_or_default();
+        let mut map = HashMap::new();
+        map.insert("hours".to_string(), play_time.num_hours().into());
+        map.insert("minutes".to_string(), (play_time.num_minutes() % 60).into());
+
+        let yes_no = |b: bool| {
+            if b {
+                locale.t("game-details-yes")
+            } else {
+                locale.t("game-details-no")
+            }
+        };
+
+        let list_width = w - IMAGE_WIDTH - 12 - 12 - 24;
+        let entry_height = styles.ui_font.size + SELECTION_MARGIN;
+
+        let title = {
+            let mut title = Label::new(
+                Point::new(x + 24, y + 8),
+                game.full_name.clone(),
+                Alignment::Left,
+                Some(list_width - 24),
+            );
+            title.color(StylesheetColor::Highlight);
+            title
+        };
+
+        let values = vec![
+            console.map_or_else(|| locale.t("game-details-unknown"), |c| c.name.clone()),
+            core.unwrap_or_else(|| locale.t("game-details-unknown")),
+            game.path
+                .strip_prefix(ALLIUM_GAMES_DIR.as_path())
+                .unwrap_or(&game.path)
+                .display()
+                .to_string(),
+            format_size(game.size()),
+            row.as_ref().map_or(0, |r| r.play_count).to_string(),
+            locale.ta("game-details-play-time-value", &map),
+            last_played.map_or_else(
+                || locale.t("game-details-never"),
+                |t| datetime.format_timestamp(&t, false),
+            ),
+            yes_no(find_guide(&game.path).is_some()),
+            game.save_states().len().to_string(),
+            SaveBackup::list(&game.path)?.len().to_string(),
+        ];
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8 + entry_height as i32,
+                list_width,
+                h - 8 - entry_height - ButtonIcon::diameter(&styles) - 8,
+            ),
+            vec![
+                locale.t("game-details-console"),
+                locale.t("game-details-core"),
+                locale.t("game-details-path"),
+                locale.t("game-details-size"),
+                locale.t("game-details-play-count"),
+                locale.t("game-details-play-time"),
+                locale.t("game-details-last-played"),
+                locale.t("game-details-guide"),
+                locale.t("game-details-save-states"),
+                locale.t("game-details-save-backups"),
+            ],
+            values
+                .into_iter()
+                .map(|s| {
+                    Box::new(Label::new(
+                        Point::zero(),
+                        s,
+                        Alignment::Right,
+                        Some(list_width / 2),
+                    )) as Box<dyn View>
+                })
+                .collect(),
+            entry_height,
+        );
+
+        let mut image = Image::empty(
+            Rect::new(
+                x + w as i32 - IMAGE_WIDTH as i32 - 24,
+                y + 8,
+                IMAGE_WIDTH,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            ImageMode::Contain,
+        );
+        image
+            .set_background_color(StylesheetColor::Background)
+            .set_border_radius(12)
+            .set_path(game.image().map(|p| p.to_path_buf()));
+
+        let mut hints = vec![
+            ButtonHint::new(
+                Point::zero(),
+                Key::A,
+                locale.t("game-details-launch"),
+                Alignment::Right,
+            ),
+            ButtonHint::new(
+                Point::zero(),
+                Key::Y,
+                favorite_hint(&locale, favorite),
+                Alignment::Right,
+            ),
+        ];
+        if cores.len() > 1 {
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::X,
+                locale.t("game-details-launch-with"),
+                Alignment::Right,
+            ));
+        }
+        if !cores.is_empty() {
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::Start,
+                locale.t("game-details-core-options"),
+                Alignment::Right,
+            ));
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::Menu,
+                locale.t("game-details-video-settings"),
+                Alignment::Right,
+            ));
+        }
+        hints.push(ButtonHint::new(
+            Point::zero(),
+            Key::Select,
+            locale.t("game-details-save-backups"),
+            Alignment::Right,
+        ));
+        hints.push(ButtonHint::new(
+            Point::zero(),
+            Key::B,
+            locale.t("button-back"),
+            Alignment::Right,
+        ));
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            hints,
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+        drop(datetime);
+        drop(database);
+        drop(console_mapper);
+
+        Ok(Self {
+            rect,
+            res,
+            game,
+            cores,
+            favorite,
+            title,
+            list,
+            image,
+            menu: None,
+            backups: None,
+            core_options: None,
+            video_settings: None,
+            button_hints,
+            dirty: true,
+        })
+    }
+
+    async fn launch(&mut self, core: Option<&str>, commands: Sender<Command>) -> Result<()> {
+        let command = self.res.get::<ConsoleMapper>().launch_game_with_core(
+            &self.res.get(),
+            &self.res.get(),
+            &mut self.game,
+            core,
+        )?;
+        if let Some(command) = command {
+            commands.send(command).await?;
+        }
+        Ok(())
+    }
+
+    fn toggle_favorite(&mut self) -> Result<()> {
+        self.favorite = !self.favorite;
+        self.res.get::<Database>().set_favorite(
+            &self.game.name,
+            &self.game.path,
+            self.game.image_ref(),
+            self.favorite,
+        )?;
+        self.button_hints
+            .get_mut(1)
+            .unwrap()
+            .set_text(favorite_hint(&self.res.get::<Locale>(), self.favorite));
+        Ok(())
+    }
+
+    fn open_menu(&mut self) {
+        let Rect { x, y, w, h } = self.rect;
+        let styles = self.res.get::<Stylesheet>();
+
+        let height = self.cores.len() as u32 * (styles.ui_font.size + SELECTION_MARGIN);
+
+        let mut menu = ScrollList::new(
+            Rect::new(
+                x + 12 + (w as i32 - 24) / 6,
+                (y + h as i32 - height as i32) / 2,
+                (w - 24) * 2 / 3,
+                height,
+            ),
+            self.cores.clone(),
+            Alignment::Center,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        menu.set_background_color(StylesheetColor::BackgroundHighlightBlend);
+        self.menu = Some(menu);
+    }
+}
+
+#[async_trait(?Send)]
+impl View for GameDetails {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(backups) = &mut self.backups {
+            return Ok(backups.should_draw() && backups.draw(display, styles)?);
+        }
+
+        if let Some(core_options) = &mut self.core_options {
+            return Ok(core_options.should_draw() && core_options.draw(display, styles)?);
+        }
+
+        if let Some(video_settings) = &mut self.video_settings {
+            return Ok(video_settings.should_draw() && video_settings.draw(display, styles)?);
+        }
+
+        if let Some(menu) = &mut self.menu {
+            if menu.should_draw() {
+                let mut rect = menu
+                    .children_mut()
+                    .iter_mut()
+                    .map(|v| v.bounding_box(styles))
+                    .reduce(|acc, r| acc.union(&r))
+                    .unwrap_or_default();
+                rect.y -= 12;
+                rect.h += 24;
+                rect.x -= 24;
+                rect.w += 48;
+                RoundedRectangle::new(
+                    rect.into(),
+                    CornerRadii::new(Size::new_equal((styles.ui_font.size + 8) / 2)),
+                )
+                .into_styled(PrimitiveStyle::with_fill(
+                    StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+                ))
+                .draw(display)?;
+                menu.draw(display, styles)?;
+                return Ok(true);
+            }
+            return Ok(false);
+        }
+
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if styles.enable_box_art {
+            drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.backups.as_ref().is_some_and(|b| b.should_draw())
+            || self.core_options.as_ref().is_some_and(|o| o.should_draw())
+            || self
+                .video_settings
+                .as_ref()
+                .is_some_and(|v| v.should_draw())
+            || self.menu.as_ref().is_some_and(|m| m.should_draw())
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.image.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(backups) = self.backups.as_mut() {
+            backups.set_should_draw();
+        }
+        if let Some(core_options) = self.core_options.as_mut() {
+            core_options.set_should_draw();
+        }
+        if let Some(video_settings) = self.video_settings.as_mut() {
+            video_settings.set_should_draw();
+        }
+        if let Some(menu) = self.menu.as_mut() {
+            menu.set_should_draw();
+        }
+        self.title.set_should_draw();
+        self.list.set_should_draw();
+        self.image.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(backups) = self.backups.as_mut() {
+            if backups
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.backups = None;
+                    self.set_should_draw();
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(core_options) = self.core_options.as_mut() {
+            if core_options
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.core_options = None;
+                    self.set_should_draw();
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(video_settings) = self.video_settings.as_mut() {
+            if video_settings
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.video_settings = None;
+                    self.set_should_draw();
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(menu) = self.menu.as_mut() {
+            match event {
+                KeyEvent::Pressed(Key::Select | Key::B) => {
+                    self.menu = None;
+                    self.set_should_draw();
+                }
+                KeyEvent::Pressed(Key::A) => {
+                    let core = self.cores[menu.selected()].clone();
+                    self.menu = None;
+                    self.set_should_draw();
+                    self.launch(Some(&core), commands).await?;
+                }
+                _ => {
+                    menu.handle_key_event(event, commands, bubble).await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.launch(None, commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) if self.cores.len() > 1 => {
+                self.open_menu();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.toggle_favorite()?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Start) if !self.cores.is_empty() => {
+                self.core_options = Some(CoreOptionsEditor::new(
+                    self.rect,
+                    self.res.clone(),
+                    self.cores[0].clone(),
+                    self.game.path.clone(),
+                    false,
+                )?);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Menu) if !self.cores.is_empty() => {
+                self.video_settings = Some(VideoSettingsEditor::new(
+                    self.rect,
+                    self.res.clone(),
+                    &self.cores[0],
+                    &self.game.path,
+                    false,
+                )?);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Select) => {
+                self.backups = Some(SaveBackups::new(
+                    self.rect,
+                    self.res.clone(),
+                    self.game.clone(),
+                )?);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.image, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.image,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn favorite_hint(locale: &Locale, favorite: bool) -> String {
+    if favorite {
+        locale.t("game-details-unfavorite")
+    } else {
+        locale.t("game-details-favorite")
+    }
+}
+
+/// Formats a size in bytes using the largest unit that keeps it above 1.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -1,9 +1,17 @@
 mod app;
 mod browser;
+mod game_details;
+mod random_game;
 mod recents;
+mod save_backups;
+mod save_conflicts;
 mod settings;
 
 pub use app::App;
 pub use browser::Browser;
+pub use game_details::GameDetails;
+pub use random_game::RandomGame;
 pub use recents::Recents;
+pub use save_backups::SaveBackups;
+pub use save_conflicts::SaveConflicts;
 pub use settings::Settings;
@@ -0,0 +1,369 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::database::Database;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Label, Row, View};
+use log::warn;
+use rand::distributions::{Distribution, WeightedIndex};
+use tokio::sync::mpsc::Sender;
+
+use crate::consoles::ConsoleMapper;
+use crate::entry::directory::Directory;
+use crate::entry::game::Game;
+
+/// How many times more likely an unplayed game is to be picked when preferring unplayed games.
+const UNPLAYED_WEIGHT: u32 = 4;
+
+/// Picks a random game from a directory and its subdirectories, with options to re-roll or launch.
+/// The games are found in the background with [`RandomGame::load`], since walking a large
+/// library takes a while.
+#[derive(Debug)]
+pub struct RandomGame {
+    rect: Rect,
+    res: Resources,
+    games: Vec<Game>,
+    /// Games found by the background walk, taken when drawing.
+    loaded: Arc<Mutex<Option<Vec<Game>>>>,
+    /// Paths of games that have been played before.
+    played: HashSet<PathBuf>,
+    prefer_unplayed: bool,
+    selected: Option<usize>,
+    image: Image,
+    name: Label<String>,
+    console: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl RandomGame {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let played = res.get::<Database>().select_played()?;
+
+        let entry_height = styles.ui_font.size + SELECTION_MARGIN;
+        let bottom = y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8;
+
+        let mut image = Image::empty(
+            Rect::new(
+                x + 24,
+                y + 8,
+                w - 48,
+                (bottom - y) as u32 - 8 - entry_height * 2 - 8,
+            ),
+            ImageMode::Contain,
+        );
+        image
+            .set_background_color(StylesheetColor::Background)
+            .set_border_radius(12);
+
+        let mut name = Label::new(
+            Point::new(x + w as i32 / 2, bottom - 8 - entry_height as i32 * 2),
+            locale.t("random-game-loading"),
+            Alignment::Center,
+            Some(w - 48),
+        );
+        name.color(StylesheetColor::Highlight);
+
+        let console = Label::new(
+            Point::new(x + w as i32 / 2, bottom - 8 - entry_height as i32),
+            String::new(),
+            Alignment::Center,
+            Some(w - 48),
+        );
+
+        let button_hints = Row::new(
+            Point::new(x + w as i32 - 12, bottom),
+            vec![ButtonHint::new(
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        Ok(Self {
+            rect,
+            res,
+            games: Vec::new(),
+            loaded: Arc::default(),
+            played,
+            prefer_unplayed: false,
+            selected: None,
+            image,
+            name,
+            console,
+            button_hints,
+            dirty: true,
+        })
+    }
+
+    /// Finds the games in a directory and its subdirectories in the background, then picks one.
+    pub fn load(&mut self, directory: Directory, commands: Sender<Command>) -> Result<()> {
+        let hidden = self.res.get::<Database>().select_hidden()?;
+        let console_mapper = self.res.get::<ConsoleMapper>().clone();
+        let loaded = Arc::clone(&self.loaded);
+        tokio::spawn(async move {
+            let games =
+                tokio::task::spawn_blocking(move || directory.games(&console_mapper, &hidden))
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("failed to find games: {}", e);
+                        Vec::new()
+                    });
+            *loaded.lock().unwrap() = Some(games);
+            commands.send(Command::Redraw).await.ok();
+        });
+        Ok(())
+    }
+
+    fn set_games(&mut self, mut games: Vec<Game>) {
+        let kid_mode = self.res.get::<KidModeSettings>();
+        games.retain(|game| kid_mode.is_visible(&game.path));
+        drop(kid_mode);
+        self.games = games;
+
+        let locale = self.res.get::<Locale>();
+        if self.games.is_empty() {
+            self.name.set_text(locale.t("random-game-empty"));
+        } else {
+            self.button_hints.insert(
+                0,
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("game-details-launch"),
+                    Alignment::Right,
+                ),
+            );
+            self.button_hints.insert(
+                1,
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::X,
+                    locale.t("random-game-reroll"),
+                    Alignment::Right,
+                ),
+            );
+            self.button_hints.insert(
+                2,
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    prefer_unplayed_hint(&locale, self.prefer_unplayed),
+                    Alignment::Right,
+                ),
+            );
+        }
+        drop(locale);
+
+        self.reroll();
+        self.set_should_draw();
+    }
+
+    fn reroll(&mut self) {
+        self.selected = pick(
+            &self.games,
+            &self.played,
+            self.prefer_unplayed,
+            self.selected,
+        );
+
+        if let Some(game) = self.selected.and_then(|i| self.games.get_mut(i)) {
+            self.image.set_path(game.image().map(|p| p.to_path_buf()));
+            self.name.set_text(game.full_name.clone());
+            self.console.set_text(
+                self.res
+                    .get::<ConsoleMapper>()
+                    .get_console(&game.path)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default(),
+            );
+            self.set_should_draw();
+        }
+    }
+
+    async fn launch(&mut self, commands: Sender<Command>) -> Result<()> {
+        if let Some(game) = self.selected.and_then(|i| self.games.get_mut(i)) {
+            let command = self.res.get::<ConsoleMapper>().launch_game(
+                &self.res.get(),
+                &self.res.get(),
+                game,
+            )?;
+            if let Some(command) = command {
+                commands.send(command).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Picks a random game, avoiding the previous pick when there is a choice. Unplayed games are
+/// weighted higher if `prefer_unplayed` is set.
+fn pick(
+    games: &[Game],
+    played: &HashSet<PathBuf>,
+    prefer_unplayed: bool,
+    previous: Option<usize>,
+) -> Option<usize> {
+    let weights = games.iter().enumerate().map(|(i, game)| {
+        if games.len() > 1 && Some(i) == previous {
+            0
+        } else if prefer_unplayed && !played.contains(&game.path) {
+            UNPLAYED_WEIGHT
+        } else {
+            1
+        }
+    });
+    let dist = WeightedIndex::new(weights).ok()?;
+    Some(dist.sample(&mut rand::thread_rng()))
+}
+
+fn prefer_unplayed_hint(locale: &Locale, prefer_unplayed: bool) -> String {
+    if prefer_unplayed {
+        locale.t("random-game-prefer-unplayed-on")
+    } else {
+        locale.t("random-game-prefer-unplayed-off")
+    }
+}
+
+#[async_trait(?Send)]
+impl View for RandomGame {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let loaded = self.loaded.lock().unwrap().take();
+        if let Some(games) = loaded {
+            self.set_games(games);
+        }
+
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.dirty = false;
+            drawn = true;
+        }
+
+        if styles.enable_box_art {
+            drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
+        }
+        drawn |= self.name.should_draw() && self.name.draw(display, styles)?;
+        drawn |= self.console.should_draw() && self.console.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.image.should_draw()
+            || self.name.should_draw()
+            || self.console.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.image.set_should_draw();
+        self.name.set_should_draw();
+        self.console.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.launch(commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.reroll();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) if !self.games.is_empty() => {
+                self.prefer_unplayed = !self.prefer_unplayed;
+                self.button_hints
+                    .get_mut(2)
+                    .unwrap()
+                    .set_text(prefer_unplayed_hint(
+                        &self.res.get::<Locale>(),
+                        self.prefer_unplayed,
+                    ));
+                self.reroll();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.image, &self.name, &self.console, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.image,
+            &mut self.name,
+            &mut self.console,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick() {
+        assert_eq!(pick(&[], &HashSet::new(), false, None), None);
+
+        let games = vec![Game::new("GB/One.gb".into())];
+        assert_eq!(pick(&games, &HashSet::new(), true, Some(0)), Some(0));
+
+        let games = vec![Game::new("GB/One.gb".into()), Game::new("GB/Two.gb".into())];
+        for _ in 0..10 {
+            assert_eq!(pick(&games, &HashSet::new(), false, Some(0)), Some(1));
+        }
+    }
+}
@@ -7,6 +7,7 @@ use common::constants::{IMAGE_WIDTH, RECENT_GAMES_LIMIT, SELECTION_MARGIN};
 use common::database::Database;
 use common::display::Display;
 use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
@@ -20,6 +21,7 @@ use tokio::sync::mpsc::Sender;
 
 use crate::consoles::ConsoleMapper;
 use crate::entry::game::Game;
+use crate::view::GameDetails;
 
 #[derive(Debug)]
 pub struct Recents {
@@ -30,6 +32,7 @@ pub struct Recents {
     list: ScrollList,
     image: Image,
     menu: Option<ScrollList>,
+    details: Option<GameDetails>,
     button_hints: Row<ButtonHint<String>>,
 }
 
@@ -39,7 +42,7 @@ impl Recents {
 
         let styles = res.get::<Stylesheet>();
 
-        let list = ScrollList::new(
+        let mut list = ScrollList::new(
             Rect::new(
                 x + 12,
                 y + 8,
@@ -50,6 +53,7 @@ impl Recents {
             Alignment::Left,
             res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
         );
+        list.set_letter_jump(true);
 
         let mut image = Image::empty(
             Rect::new(
@@ -100,6 +104,7 @@ impl Recents {
             list,
             image,
             menu: None,
+            details: None,
             button_hints,
         };
 
@@ -118,10 +123,11 @@ impl Recents {
                 }
             }
 
-            let command = self
-                .res
-                .get::<ConsoleMapper>()
-                .launch_game(&self.res.get(), entry)?;
+            let command = self.res.get::<ConsoleMapper>().launch_game(
+                &self.res.get(),
+                &self.res.get(),
+                entry,
+            )?;
 
             if let Some(command) = command {
                 commands.send(command).await?;
@@ -140,10 +146,16 @@ impl Recents {
                 .res
                 .get::<Database>()
                 .select_most_played(RECENT_GAMES_LIMIT)?,
+            Sort::Favorites => self
+                .res
+                .get::<Database>()
+                .select_favorites(RECENT_GAMES_LIMIT)?,
         };
 
+        let kid_mode = self.res.get::<KidModeSettings>();
         self.entries = games
             .into_iter()
+            .filter(|game| kid_mode.is_visible(&game.path))
             .map(|game| {
                 let extension = game
                     .path
@@ -163,6 +175,7 @@ impl Recents {
                 }
             })
             .collect();
+        drop(kid_mode);
 
         self.list.set_items(
             self.entries.iter().map(|e| e.name.to_string()).collect(),
@@ -177,7 +190,11 @@ impl Recents {
         let styles = self.res.get::<Stylesheet>();
         let locale = self.res.get::<Locale>();
 
-        let labels = vec![locale.t("recents-launch"), locale.t("recents-remove")];
+        let labels = vec![
+            locale.t("recents-launch"),
+            locale.t("game-details"),
+            locale.t("recents-remove"),
+        ];
 
         let height = labels.len() as u32 * (styles.ui_font.size + SELECTION_MARGIN);
 
@@ -204,6 +221,10 @@ impl View for Recents {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
+        if let Some(details) = &mut self.details {
+            return Ok(details.should_draw() && details.draw(display, styles)?);
+        }
+
         let mut drawn = false;
 
         if let Some(menu) = &mut self.menu {
@@ -267,15 +288,22 @@ impl View for Recents {
     }
 
     fn should_draw(&self) -> bool {
-        self.menu
+        self.details
             .as_ref()
             .map_or(false, common::view::View::should_draw)
+            || self
+                .menu
+                .as_ref()
+                .map_or(false, common::view::View::should_draw)
             || self.list.should_draw()
             || self.image.should_draw()
             || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
+        if let Some(details) = self.details.as_mut() {
+            details.set_should_draw();
+        }
         if let Some(menu) = self.menu.as_mut() {
             menu.set_should_draw();
         }
@@ -290,6 +318,28 @@ impl View for Recents {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        if let Some(details) = self.details.as_mut() {
+            if details
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.details = None;
+                    self.load_entries()?;
+                    commands.send(Command::Redraw).await?;
+                }
+            }
+            return Ok(true);
+        }
+
         if let Some(ref mut menu) = self.menu {
             match event {
                 KeyEvent::Pressed(Key::Select | Key::B) => {
@@ -304,6 +354,18 @@ impl View for Recents {
                         Ok(true)
                     }
                     1 => {
+                        if let Some(entry) = self.entries.get(self.list.selected()) {
+                            self.details = Some(GameDetails::new(
+                                self.rect,
+                                self.res.clone(),
+                                entry.clone(),
+                            )?);
+                            commands.send(Command::Redraw).await?;
+                        }
+                        self.menu = None;
+                        Ok(true)
+                    }
+                    2 => {
                         if let Some(entry) = self.entries.get(self.list.selected()) {
                             self.res.get::<Database>().delete_game(&entry.path)?;
                             self.load_entries()?;
@@ -360,6 +422,7 @@ impl View for Recents {
 enum Sort {
     LastPlayed,
     MostPlayed,
+    Favorites,
 }
 
 impl Sort {
@@ -367,13 +430,15 @@ impl Sort {
         match self {
             Sort::LastPlayed => locale.t("recents-sort-currently-last-played"),
             Sort::MostPlayed => locale.t("recents-sort-currently-most-played"),
+            Sort::Favorites => locale.t("recents-sort-currently-favorites"),
         }
     }
 
     fn next(self) -> Self {
         match self {
             Sort::LastPlayed => Sort::MostPlayed,
-            Sort::MostPlayed => Sort::LastPlayed,
+            Sort::MostPlayed => Sort::Favorites,
+            Sort::Favorites => Sort::LastPlayed,
         }
     }
 }
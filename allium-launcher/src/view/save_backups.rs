@@ -0,0 +1,307 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_SD_ROOT, SELECTION_MARGIN};
+use common::datetime::DateTimeSettings;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::save_backup::{self, SaveBackup};
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, ScrollList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::entry::game::Game;
+
+/// Lists the save backups of a game, and lets one be restored or everything exported.
+#[derive(Debug)]
+pub struct SaveBackups {
+    rect: Rect,
+    res: Resources,
+    game: Game,
+    backups: Vec<SaveBackup>,
+    /// Whether restore has been pressed once and needs to be confirmed.
+    confirm_restore: bool,
+    title: Label<String>,
+    list: ScrollList,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl SaveBackups {
+    pub fn new(rect: Rect, res: Resources, game: Game) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let entry_height = styles.ui_font.size + SELECTION_MARGIN;
+
+        let mut title = Label::new(
+            Point::new(x + 24, y + 8),
+            game.full_name.clone(),
+            Alignment::Left,
+            Some(w - 48),
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8 + entry_height as i32,
+                w - 24,
+                h - 8 - entry_height * 2 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Alignment::Left,
+            entry_height,
+        );
+
+        let status = Label::new(
+            Point::new(
+                x + 24,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8 - entry_height as i32,
+            ),
+            String::new(),
+            Alignment::Left,
+            Some(w - 48),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("save-backups-restore"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("save-backups-export"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            game,
+            backups: Vec::new(),
+            confirm_restore: false,
+            title,
+            list,
+            status,
+            button_hints,
+            dirty: true,
+        };
+        this.reload()?;
+
+        Ok(this)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        self.backups = SaveBackup::list(&self.game.path)?;
+        let locale = self.res.get::<Locale>();
+        let datetime = self.res.get::<DateTimeSettings>();
+        let items = if self.backups.is_empty() {
+            vec![locale.t("save-backups-empty")]
+        } else {
+            self.backups
+                .iter()
+                .map(|backup| {
+                    let mut map = HashMap::new();
+                    map.insert(
+                        "time".to_string(),
+                        datetime.format_timestamp(&backup.time, true).into(),
+                    );
+                    map.insert(
+                        "files".to_string(),
+                        backup.files().map_or(0, |f| f.len()).into(),
+                    );
+                    locale.ta("save-backups-entry", &map)
+                })
+                .collect()
+        };
+        drop(locale);
+        drop(datetime);
+        self.list.set_items(items, false);
+        Ok(())
+    }
+
+    fn set_confirm_restore(&mut self, confirm_restore: bool) {
+        self.confirm_restore = confirm_restore;
+        let locale = self.res.get::<Locale>();
+        self.button_hints
+            .get_mut(0)
+            .unwrap()
+            .set_text(if confirm_restore {
+                locale.t("save-backups-restore-confirm")
+            } else {
+                locale.t("save-backups-restore")
+            });
+    }
+
+    fn restore(&mut self) -> Result<()> {
+        let Some(backup) = self.backups.get(self.list.selected()) else {
+            return Ok(());
+        };
+        let status = match backup.restore(&self.game.path) {
+            Ok(()) => self.res.get::<Locale>().t("save-backups-restored"),
+            Err(e) => {
+                warn!("failed to restore save backup: {}", e);
+                self.res.get::<Locale>().t("save-backups-restore-failed")
+            }
+        };
+        self.status.set_text(status);
+        self.reload()?;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn export(&mut self) {
+        let locale = self.res.get::<Locale>();
+        let status = match save_backup::export() {
+            Ok(path) => {
+                let mut map = HashMap::new();
+                map.insert(
+                    "path".to_string(),
+                    path.strip_prefix(ALLIUM_SD_ROOT.as_path())
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string()
+                        .into(),
+                );
+                locale.ta("save-backups-exported", &map)
+            }
+            Err(e) => {
+                warn!("failed to export saves: {}", e);
+                locale.t("save-backups-export-failed")
+            }
+        };
+        drop(locale);
+        self.status.set_text(status);
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for SaveBackups {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.status.should_draw() && self.status.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.title.set_should_draw();
+        self.list.set_should_draw();
+        self.status.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let KeyEvent::Pressed(key) = event {
+            if key != Key::A && self.confirm_restore {
+                self.set_confirm_restore(false);
+            }
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if self.confirm_restore {
+                    self.set_confirm_restore(false);
+                    self.restore()?;
+                } else if !self.backups.is_empty() {
+                    self.set_confirm_restore(true);
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.export();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.status,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::datetime::DateTimeSettings;
+use common::display::Display;
+use common::game_info::GameInfo;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::save_sync::{Conflict, FileVersion, Resolution, SaveSync};
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, ScrollList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+/// Asks which side to keep when save files changed both on the device and on the sync remote,
+/// before launching the game.
+#[derive(Debug)]
+pub struct SaveConflicts {
+    rect: Rect,
+    res: Resources,
+    conflicts: Vec<Conflict>,
+    title: Label<String>,
+    list: ScrollList,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl SaveConflicts {
+    pub fn new(rect: Rect, res: Resources, conflicts: Vec<Conflict>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+        let datetime = res.get::<DateTimeSettings>();
+
+        let entry_height = styles.ui_font.size + SELECTION_MARGIN;
+
+        let mut title = Label::new(
+            Point::new(x + 24, y + 8),
+            locale.t("save-conflicts-title"),
+            Alignment::Left,
+            Some(w - 48),
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let format_time =
+            |version: &FileVersion| datetime.format_timestamp(&version.modified, false);
+        let items = conflicts
+            .iter()
+            .map(|conflict| {
+                let mut map = HashMap::new();
+                map.insert(
+                    "file".to_string(),
+                    conflict
+                        .path
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string()
+                        .into(),
+                );
+                let (mut local, mut remote) =
+                    (format_time(&conflict.local), format_time(&conflict.remote));
+                let newer = if conflict.is_remote_newer() {
+                    &mut remote
+                } else {
+                    &mut local
+                };
+                newer.push(' ');
+                newer.push_str(&locale.t("save-conflicts-newer"));
+                map.insert("local".to_string(), local.into());
+                map.insert("remote".to_string(), remote.into());
+                locale.ta("save-conflicts-entry", &map)
+            })
+            .collect();
+        let list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8 + entry_height as i32,
+                w - 24,
+                h - 8 - entry_height * 2 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            items,
+            Alignment::Left,
+            entry_height,
+        );
+
+        let status = Label::new(
+            Point::new(
+                x + 24,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8 - entry_height as i32,
+            ),
+            String::new(),
+            Alignment::Left,
+            Some(w - 48),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("save-conflicts-keep-local"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("save-conflicts-keep-remote"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::X,
+                    locale.t("save-conflicts-keep-newer"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-cancel"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+        drop(datetime);
+
+        Self {
+            rect,
+            res,
+            conflicts,
+            title,
+            list,
+            status,
+            button_hints,
+            dirty: true,
+        }
+    }
+
+    /// Resolves the conflicts and launches the game.
+    async fn resolve(
+        &mut self,
+        resolution: Resolution,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<()> {
+        let result = match SaveSync::load()? {
+            Some(save_sync) => save_sync.resolve(&self.conflicts, resolution).await,
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("failed to resolve save conflicts: {}", e);
+            self.status
+                .set_text(self.res.get::<Locale>().t("save-conflicts-failed"));
+            return Ok(());
+        }
+
+        if let Some(game_info) = GameInfo::load()? {
+            commands.send(Command::Exec(game_info.command())).await?;
+        }
+        bubble.push_back(Command::CloseView);
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for SaveConflicts {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.status.should_draw() && self.status.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.title.set_should_draw();
+        self.list.set_should_draw();
+        self.status.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.resolve(Resolution::KeepLocal, commands, bubble)
+                    .await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.resolve(Resolution::KeepRemote, commands, bubble)
+                    .await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.resolve(Resolution::KeepNewer, commands, bubble)
+                    .await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                // The game was about to be launched, so it has to be forgotten
+                GameInfo::delete()?;
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.status,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -9,16 +10,42 @@ use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
+use common::update::{self, Release};
 use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, View};
+use log::warn;
 use sysinfo::{DiskExt, SystemExt};
 use tokio::sync::mpsc::Sender;
 
+use crate::view::settings::changelog::Changelog;
 use crate::view::settings::{ChildState, SettingsChild};
 
+const AVAILABLE_VERSION_ROW: usize = 1;
+
+/// Only shown if the previous version was kept by an update.
+const ROLLBACK_ROW: usize = 7;
+
+#[derive(Debug, Clone)]
+enum UpdateStatus {
+    Checking,
+    UpToDate,
+    Available(Box<Release>),
+    Installing,
+    Staged,
+    Failed,
+}
+
 pub struct About {
     rect: Rect,
+    res: Resources,
     list: SettingsList,
     button_hints: Row<ButtonHint<String>>,
+    status: UpdateStatus,
+    /// Set by the check and install tasks, and taken when drawing.
+    next_status: Arc<Mutex<Option<UpdateStatus>>>,
+    tasks_started: bool,
+    changelog: Option<Changelog>,
+    has_rollback: bool,
+    pending_rollback: bool,
 }
 
 impl About {
@@ -34,6 +61,74 @@ impl About {
         let locale = res.get::<Locale>();
         let styles = res.get::<Stylesheet>();
 
+        let has_rollback = update::can_rollback();
+
+        let mut left = vec![
+            locale.t("settings-about-allium-version"),
+            locale.t("settings-about-available-version"),
+            locale.t("settings-about-model-name"),
+            locale.t("settings-about-firmware-version"),
+            locale.t("settings-about-operating-system-version"),
+            locale.t("settings-about-kernel-version"),
+            locale.t("settings-about-storage-used"),
+        ];
+        let mut right: Vec<Box<dyn View>> = vec![
+            Box::new(Label::new(
+                Point::zero(),
+                format!("v{ALLIUM_VERSION}"),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                String::new(),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                DefaultPlatform::device_model(),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Label::new(Point::zero(), firmware, Alignment::Right, None)),
+            Box::new(Label::new(
+                Point::zero(),
+                sys.long_os_version().map_or_else(
+                    || locale.t("settings-about-unknown-value"),
+                    |s| s.trim().to_owned(),
+                ),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                sys.kernel_version()
+                    .unwrap_or_else(|| locale.t("settings-about-unknown-value")),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                format!(
+                    "{}GB / {}GB",
+                    (disk.total_space() - disk.available_space()) / (1024 * 1024 * 1024),
+                    disk.total_space() / (1024 * 1024 * 1024)
+                ),
+                Alignment::Right,
+                None,
+            )),
+        ];
+        if has_rollback {
+            left.push(locale.t("settings-about-rollback"));
+            right.push(Box::new(Label::new(
+                Point::zero(),
+                String::new(),
+                Alignment::Right,
+                None,
+            )));
+        }
+
         let mut list = SettingsList::new(
             Rect::new(
                 x + 12,
@@ -41,55 +136,8 @@ impl About {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-about-allium-version"),
-                locale.t("settings-about-model-name"),
-                locale.t("settings-about-firmware-version"),
-                locale.t("settings-about-operating-system-version"),
-                locale.t("settings-about-kernel-version"),
-                locale.t("settings-about-storage-used"),
-            ],
-            vec![
-                Box::new(Label::new(
-                    Point::zero(),
-                    format!("v{ALLIUM_VERSION}"),
-                    Alignment::Right,
-                    None,
-                )),
-                Box::new(Label::new(
-                    Point::zero(),
-                    DefaultPlatform::device_model(),
-                    Alignment::Right,
-                    None,
-                )),
-                Box::new(Label::new(Point::zero(), firmware, Alignment::Right, None)),
-                Box::new(Label::new(
-                    Point::zero(),
-                    sys.long_os_version().map_or_else(
-                        || locale.t("settings-about-unknown-value"),
-                        |s| s.trim().to_owned(),
-                    ),
-                    Alignment::Right,
-                    None,
-                )),
-                Box::new(Label::new(
-                    Point::zero(),
-                    sys.kernel_version()
-                        .unwrap_or_else(|| locale.t("settings-about-unknown-value")),
-                    Alignment::Right,
-                    None,
-                )),
-                Box::new(Label::new(
-                    Point::zero(),
-                    format!(
-                        "{}GB / {}GB",
-                        (disk.total_space() - disk.available_space()) / (1024 * 1024 * 1024),
-                        disk.total_space() / (1024 * 1024 * 1024)
-                    ),
-                    Alignment::Right,
-                    None,
-                )),
-            ],
+            left,
+            right,
             styles.ui_font.size + SELECTION_MARGIN,
         );
         if let Some(state) = state {
@@ -101,21 +149,117 @@ impl About {
                 rect.x + rect.w as i32 - 12,
                 rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
             ),
-            vec![ButtonHint::new(
-                Point::zero(),
-                Key::B,
-                locale.t("button-back"),
-                Alignment::Right,
-            )],
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-about-check-for-updates"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
             Alignment::Right,
             12,
         );
 
-        Self {
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
             rect,
+            res,
             list,
             button_hints,
+            status: UpdateStatus::Checking,
+            next_status: Arc::new(Mutex::new(None)),
+            tasks_started: false,
+            changelog: None,
+            has_rollback,
+            pending_rollback: false,
+        };
+        if update::is_staged() {
+            this.set_status(UpdateStatus::Staged);
         }
+        this
+    }
+
+    /// Checks for an update, unless one is already staged.
+    pub fn spawn_tasks(&mut self, commands: Sender<Command>) {
+        self.tasks_started = true;
+        if !matches!(self.status, UpdateStatus::Staged) {
+            self.check(commands);
+        }
+    }
+
+    fn check(&mut self, commands: Sender<Command>) {
+        self.set_status(UpdateStatus::Checking);
+        let next_status = Arc::clone(&self.next_status);
+        tokio::spawn(async move {
+            let status = match Release::check().await {
+                Ok(Some(release)) => UpdateStatus::Available(Box::new(release)),
+                Ok(None) => UpdateStatus::UpToDate,
+                Err(e) => {
+                    warn!("failed to check for updates: {}", e);
+                    UpdateStatus::Failed
+                }
+            };
+            *next_status.lock().unwrap() = Some(status);
+            commands.send(Command::Redraw).await.ok();
+        });
+    }
+
+    fn install(&mut self, release: Box<Release>, commands: Sender<Command>) {
+        self.set_status(UpdateStatus::Installing);
+        let next_status = Arc::clone(&self.next_status);
+        tokio::spawn(async move {
+            let status = match release.stage().await {
+                Ok(()) => UpdateStatus::Staged,
+                Err(e) => {
+                    warn!("failed to install update: {}", e);
+                    UpdateStatus::Failed
+                }
+            };
+            *next_status.lock().unwrap() = Some(status);
+            commands.send(Command::Redraw).await.ok();
+        });
+    }
+
+    fn set_status(&mut self, status: UpdateStatus) {
+        let locale = self.res.get::<Locale>();
+        let text = match &status {
+            UpdateStatus::Checking => locale.t("settings-about-update-checking"),
+            UpdateStatus::UpToDate => locale.t("settings-about-update-up-to-date"),
+            UpdateStatus::Available(release) => release.manifest.version.clone(),
+            UpdateStatus::Installing => locale.t("settings-about-update-installing"),
+            UpdateStatus::Staged => locale.t("settings-about-update-staged"),
+            UpdateStatus::Failed => locale.t("settings-about-update-failed"),
+        };
+        drop(locale);
+        self.list.set_right(
+            AVAILABLE_VERSION_ROW,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
+        self.status = status;
+    }
+
+    fn set_pending_rollback(&mut self, pending: bool) {
+        self.pending_rollback = pending;
+        let text = if pending {
+            self.res
+                .get::<Locale>()
+                .t("settings-about-rollback-confirm")
+        } else {
+            String::new()
+        };
+        self.list.set_right(
+            ROLLBACK_ROW,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
     }
 }
 
@@ -126,6 +270,15 @@ impl View for About {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
+        if let Some(changelog) = &mut self.changelog {
+            return Ok(changelog.should_draw() && changelog.draw(display, styles)?);
+        }
+
+        let status = self.next_status.lock().unwrap().take();
+        if let Some(status) = status {
+            self.set_status(status);
+        }
+
         let mut drawn = false;
 
         if self.list.should_draw() && self.list.draw(display, styles)? {
@@ -140,10 +293,15 @@ impl View for About {
     }
 
     fn should_draw(&self) -> bool {
-        self.list.should_draw() || self.button_hints.should_draw()
+        self.changelog.as_ref().is_some_and(|c| c.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
+        if let Some(changelog) = &mut self.changelog {
+            changelog.set_should_draw();
+        }
         self.list.set_should_draw();
         self.button_hints.set_should_draw();
     }
@@ -154,7 +312,85 @@ impl View for About {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        if !self.tasks_started {
+            // Restored from saved state without a command sender, so start the tasks now
+            self.spawn_tasks(commands.clone());
+        }
+
+        if let Some(changelog) = self.changelog.as_mut() {
+            if changelog
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut install = false;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, _) => {
+                        install = true;
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.changelog = None;
+                    commands.send(Command::Redraw).await?;
+                }
+                if let (true, UpdateStatus::Available(release)) = (install, &self.status) {
+                    self.install(release.clone(), commands);
+                }
+            }
+            return Ok(true);
+        }
+
+        if let KeyEvent::Pressed(key) = event {
+            if key != Key::A && self.pending_rollback {
+                self.set_pending_rollback(false);
+            }
+        }
+
         match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() == AVAILABLE_VERSION_ROW => {
+                match &self.status {
+                    UpdateStatus::Available(release) => {
+                        self.changelog = Some(Changelog::new(
+                            self.rect,
+                            self.res.clone(),
+                            &release.manifest,
+                        ));
+                    }
+                    UpdateStatus::UpToDate | UpdateStatus::Failed => self.check(commands),
+                    UpdateStatus::Checking | UpdateStatus::Installing | UpdateStatus::Staged => {}
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::A)
+                if self.has_rollback && self.list.selected() == ROLLBACK_ROW =>
+            {
+                if matches!(self.status, UpdateStatus::Installing) {
+                    return Ok(true);
+                }
+                if self.pending_rollback {
+                    update::stage_rollback()?;
+                    self.set_pending_rollback(false);
+                    self.set_status(UpdateStatus::Staged);
+                } else {
+                    self.set_pending_rollback(true);
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                if !matches!(
+                    self.status,
+                    UpdateStatus::Checking | UpdateStatus::Installing | UpdateStatus::Staged
+                ) {
+                    self.check(commands);
+                }
+                Ok(true)
+            }
             KeyEvent::Pressed(Key::B) => {
                 bubble.push_back(Command::CloseView);
                 Ok(true)
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::update::Manifest;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, ScrollList, View};
+use tokio::sync::mpsc::Sender;
+
+/// Shows the changelog of an available update. Pressing A bubbles `ValueChanged` to install it.
+#[derive(Debug)]
+pub struct Changelog {
+    rect: Rect,
+    title: Label<String>,
+    list: ScrollList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Changelog {
+    pub fn new(rect: Rect, res: Resources, manifest: &Manifest) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let entry_height = styles.ui_font.size + SELECTION_MARGIN;
+
+        let mut title = Label::new(
+            Point::new(x + 24, y + 8),
+            format!("Allium {}", manifest.version),
+            Alignment::Left,
+            Some(w - 48),
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8 + entry_height as i32,
+                w - 24,
+                h - 8 - entry_height - ButtonIcon::diameter(&styles) - 8,
+            ),
+            manifest
+                .changelog
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect(),
+            Alignment::Left,
+            entry_height,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("settings-about-install-update"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        Self {
+            rect,
+            title,
+            list,
+            button_hints,
+            dirty: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Changelog {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.title.set_should_draw();
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                bubble.push_back(Command::ValueChanged(0, Value::Bool(true)));
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.title, &mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
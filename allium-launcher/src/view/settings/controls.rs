@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::hotkeys::{self, Hotkeys};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, ChordInput, Label, Row, SettingsList, Toggle, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+const SWAP_BUTTONS_ROW: usize = 0;
+/// Row of the first hotkey, which are listed after the other settings.
+const HOTKEYS_ROW: usize = 1;
+
+/// Lists the hotkeys handled by alliumd. Changes are saved right away, and alliumd picks them up
+/// the next time a key is pressed. Hotkeys are saved with the physical buttons, since alliumd
+/// reads them before A/B and X/Y are swapped.
+pub struct Controls {
+    rect: Rect,
+    res: Resources,
+    hotkeys: Hotkeys,
+    list: SettingsList,
+    chord_input: Option<ChordInput>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Controls {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let hotkeys = Hotkeys::load().unwrap_or_else(|e| {
+            warn!("failed to load hotkeys: {}", e);
+            Hotkeys::default()
+        });
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let (left, right) = Self::items(&locale, &styles, &hotkeys);
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-controls-reset"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            hotkeys,
+            list,
+            chord_input: None,
+            button_hints,
+        }
+    }
+
+    fn items(
+        locale: &Locale,
+        styles: &Stylesheet,
+        hotkeys: &Hotkeys,
+    ) -> (Vec<String>, Vec<Box<dyn View>>) {
+        let mut left = vec![locale.t("settings-controls-swap-buttons")];
+        let mut right: Vec<Box<dyn View>> = vec![Box::new(Toggle::new(
+            Point::zero(),
+            styles.swap_buttons,
+            Alignment::Right,
+        ))];
+        for hotkey in &hotkeys.hotkeys {
+            left.push(locale.t(hotkey.action.key()));
+            right.push(Box::new(Label::new(
+                Point::zero(),
+                hotkeys::chord_name(&hotkey.keys),
+                Alignment::Right,
+                None,
+            )));
+        }
+        (left, right)
+    }
+
+    /// Sets the chord of the i-th hotkey, as recorded by [`ChordInput`].
+    fn set_chord(&mut self, i: usize, keys: Vec<Key>) -> Result<()> {
+        self.list.set_right(
+            HOTKEYS_ROW + i,
+            Box::new(Label::new(
+                Point::zero(),
+                hotkeys::chord_name(&keys),
+                Alignment::Right,
+                None,
+            )),
+        );
+        self.hotkeys.hotkeys[i].keys = keys;
+        self.hotkeys.save()
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.hotkeys = Hotkeys::default();
+        let (left, right) = Self::items(
+            &self.res.get::<Locale>(),
+            &self.res.get::<Stylesheet>(),
+            &self.hotkeys,
+        );
+        let selected = self.list.selected();
+        self.list.set_items(left, right);
+        self.list.select(selected);
+        self.hotkeys.save()
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Controls {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        if let Some(chord_input) = &mut self.chord_input {
+            drawn |= chord_input.should_draw() && chord_input.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.chord_input.as_ref().is_some_and(|c| c.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(chord_input) = self.chord_input.as_mut() {
+            chord_input.set_should_draw();
+        }
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(chord_input) = self.chord_input.as_mut() {
+            chord_input
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            let mut chord = None;
+            let mut close = false;
+            bubble.retain(|cmd| match cmd {
+                Command::ValueChanged(_, value) => {
+                    chord = value.clone().as_keys();
+                    false
+                }
+                Command::CloseView => {
+                    close = true;
+                    false
+                }
+                _ => true,
+            });
+            if close {
+                self.chord_input = None;
+                commands.send(Command::Redraw).await?;
+            }
+            if let Some(chord) = chord {
+                self.set_chord(self.list.selected() - HOTKEYS_ROW, chord)?;
+            }
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(SWAP_BUTTONS_ROW, val) = command {
+                    let mut styles = self.res.get::<Stylesheet>().clone();
+                    styles.swap_buttons = val.as_bool().unwrap();
+                    commands
+                        .send(Command::SaveStylesheet(Box::new(styles)))
+                        .await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() >= HOTKEYS_ROW => {
+                self.chord_input = Some(ChordInput::new(
+                    self.res.get::<Locale>().t("settings-controls-record"),
+                    self.res.get::<Stylesheet>().swap_buttons,
+                ));
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.reset()?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Controls {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
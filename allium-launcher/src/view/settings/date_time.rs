@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Timelike};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::datetime::DateTimeSettings;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{
+    ButtonHint, ButtonIcon, DateTimePicker, DateTimePickerMode, Label, Row, SettingsList, Toggle,
+    View,
+};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::timezone::TimezonePicker;
+use crate::view::settings::{ChildState, SettingsChild};
+
+const DATE_ROW: usize = 0;
+const TIME_ROW: usize = 1;
+const TIMEZONE_ROW: usize = 2;
+const USE_24_HOUR_ROW: usize = 3;
+
+pub struct DateTime {
+    rect: Rect,
+    res: Resources,
+    settings: DateTimeSettings,
+    list: SettingsList,
+    timezone_picker: Option<TimezonePicker>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl DateTime {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = res.get::<DateTimeSettings>().clone();
+        let now = settings.now().naive_local();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            vec![
+                locale.t("settings-date-time-date"),
+                locale.t("settings-date-time-time"),
+                locale.t("settings-date-time-timezone"),
+                locale.t("settings-date-time-24-hour"),
+            ],
+            vec![
+                Box::new(DateTimePicker::new(
+                    Point::zero(),
+                    now,
+                    DateTimePickerMode::Date,
+                    Alignment::Right,
+                )),
+                Box::new(DateTimePicker::new(
+                    Point::zero(),
+                    now,
+                    DateTimePickerMode::Time {
+                        use_24_hour: settings.use_24_hour,
+                    },
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    settings.timezone.clone(),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.use_24_hour,
+                    Alignment::Right,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            settings,
+            list,
+            timezone_picker: None,
+            button_hints,
+        }
+    }
+
+    /// Shows the current local time in the date and time pickers.
+    fn refresh_pickers(&mut self) {
+        let now = self.settings.now().naive_local();
+        self.list.set_right(
+            DATE_ROW,
+            Box::new(DateTimePicker::new(
+                Point::zero(),
+                now,
+                DateTimePickerMode::Date,
+                Alignment::Right,
+            )),
+        );
+        self.list.set_right(
+            TIME_ROW,
+            Box::new(DateTimePicker::new(
+                Point::zero(),
+                now,
+                DateTimePickerMode::Time {
+                    use_24_hour: self.settings.use_24_hour,
+                },
+                Alignment::Right,
+            )),
+        );
+    }
+
+    fn set_system_time(&mut self, time: NaiveDateTime) {
+        if let Err(e) = self.settings.set_system_time(time) {
+            warn!("failed to set system time: {}", e);
+        }
+        self.refresh_pickers();
+    }
+
+    fn save_settings(&mut self) -> Result<()> {
+        self.settings.save()?;
+        self.res.insert(self.settings.clone());
+        self.refresh_pickers();
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for DateTime {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(timezone_picker) = &mut self.timezone_picker {
+            return Ok(timezone_picker.should_draw() && timezone_picker.draw(display, styles)?);
+        }
+
+        let mut drawn = false;
+
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.timezone_picker
+            .as_ref()
+            .is_some_and(|p| p.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(timezone_picker) = self.timezone_picker.as_mut() {
+            timezone_picker.set_should_draw();
+        }
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(timezone_picker) = self.timezone_picker.as_mut() {
+            if timezone_picker
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut timezone = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        timezone = value.clone().as_string();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.timezone_picker = None;
+                    commands.send(Command::Redraw).await?;
+                }
+                if let Some(timezone) = timezone {
+                    self.settings.timezone = timezone;
+                    self.list.set_right(
+                        TIMEZONE_ROW,
+                        Box::new(Label::new(
+                            Point::zero(),
+                            self.settings.timezone.clone(),
+                            Alignment::Right,
+                            None,
+                        )),
+                    );
+                    self.save_settings()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        DATE_ROW => {
+                            // Keep the time as it is now, rather than when the page was opened
+                            let date = val.as_date_time().unwrap().date();
+                            let time = self.settings.now().naive_local().time();
+                            self.set_system_time(date.and_time(time));
+                        }
+                        TIME_ROW => {
+                            let date = self.settings.now().naive_local().date();
+                            let time = val.as_date_time().unwrap().time();
+                            self.set_system_time(
+                                date.and_time(time.with_second(0).unwrap_or(time)),
+                            );
+                        }
+                        TIMEZONE_ROW => {}
+                        USE_24_HOUR_ROW => {
+                            self.settings.use_24_hour = val.as_bool().unwrap();
+                            self.save_settings()?;
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() == TIMEZONE_ROW => {
+                self.timezone_picker = Some(TimezonePicker::new(
+                    self.rect,
+                    self.res.clone(),
+                    &self.settings.timezone,
+                ));
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for DateTime {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, ALLIUM_SD_ROOT, SELECTION_MARGIN};
+use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{
+    ButtonHint, ButtonIcon, Label, Number, PinInput, Row, SettingsList, Toggle, View,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Number of rows before the allow-listed directories.
+const FIXED_ROWS: usize = 3;
+
+pub struct KidMode {
+    rect: Rect,
+    res: Resources,
+    settings: KidModeSettings,
+    /// Directories that can be allow-listed, in the same order as their rows.
+    directories: Vec<PathBuf>,
+    list: SettingsList,
+    pin_input: Option<PinInput>,
+    /// Whether kid mode should be enabled once a PIN has been set.
+    enable_after_pin: bool,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl KidMode {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = res.get::<KidModeSettings>().clone();
+        let directories = directories();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut left = vec![
+            locale.t("settings-kid-mode-enabled"),
+            locale.t("settings-kid-mode-pin"),
+            locale.t("settings-kid-mode-daily-limit"),
+        ];
+        let mut right: Vec<Box<dyn View>> = vec![
+            Box::new(Toggle::new(
+                Point::zero(),
+                settings.enabled,
+                Alignment::Right,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                pin_label(&locale, &settings),
+                Alignment::Right,
+                None,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                settings.daily_limit as i32,
+                0,
+                600,
+                Alignment::Right,
+            )),
+        ];
+        for directory in &directories {
+            left.push(
+                directory
+                    .strip_prefix(ALLIUM_SD_ROOT.as_path())
+                    .unwrap_or(directory)
+                    .display()
+                    .to_string(),
+            );
+            right.push(Box::new(Toggle::new(
+                Point::zero(),
+                settings.allowed.contains(directory),
+                Alignment::Right,
+            )));
+        }
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            settings,
+            directories,
+            list,
+            pin_input: None,
+            enable_after_pin: false,
+            button_hints,
+        }
+    }
+
+    fn open_pin_input(&mut self) {
+        let title = self.res.get::<Locale>().t("kid-mode-set-pin");
+        self.pin_input = Some(PinInput::new(self.res.clone(), title));
+    }
+
+    async fn set_enabled(&mut self, enabled: bool, commands: Sender<Command>) -> Result<()> {
+        self.settings.enabled = enabled;
+        commands
+            .send(Command::SaveKidModeSettings(Box::new(
+                self.settings.clone(),
+            )))
+            .await?;
+        Ok(())
+    }
+
+    fn save_settings(&self) -> Result<()> {
+        self.settings.save()?;
+        self.res.insert(self.settings.clone());
+        Ok(())
+    }
+}
+
+/// Returns the top level directories of the games and apps directories.
+fn directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    for root in [ALLIUM_GAMES_DIR.clone(), ALLIUM_SD_ROOT.join("Apps")] {
+        let Ok(entries) = fs::read_dir(&root) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with('.'))
+            })
+            .collect();
+        paths.sort_unstable();
+        directories.extend(paths);
+    }
+    directories
+}
+
+fn pin_label(locale: &Locale, settings: &KidModeSettings) -> String {
+    if settings.pin.is_empty() {
+        locale.t("settings-kid-mode-pin-not-set")
+    } else {
+        locale.t("settings-kid-mode-pin-set")
+    }
+}
+
+#[async_trait(?Send)]
+impl View for KidMode {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(pin_input) = &mut self.pin_input {
+            return Ok(pin_input.should_draw() && pin_input.draw(display, styles)?);
+        }
+
+        let mut drawn = false;
+
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.pin_input.as_ref().is_some_and(|p| p.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(pin_input) = self.pin_input.as_mut() {
+            pin_input.set_should_draw();
+        }
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(pin_input) = self.pin_input.as_mut() {
+            if pin_input
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut pin = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        pin = value.clone().as_keys();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.pin_input = None;
+                    self.set_should_draw();
+                }
+                match pin {
+                    Some(pin) if !pin.is_empty() => {
+                        self.settings.pin = pin;
+                        self.list.set_right(
+                            1,
+                            Box::new(Label::new(
+                                Point::zero(),
+                                pin_label(&self.res.get::<Locale>(), &self.settings),
+                                Alignment::Right,
+                                None,
+                            )),
+                        );
+                        if self.enable_after_pin {
+                            self.set_enabled(true, commands).await?;
+                        } else {
+                            self.save_settings()?;
+                        }
+                    }
+                    _ => {
+                        if self.enable_after_pin {
+                            self.list.set_right(
+                                0,
+                                Box::new(Toggle::new(Point::zero(), false, Alignment::Right)),
+                            );
+                        }
+                    }
+                }
+                if close {
+                    self.enable_after_pin = false;
+                }
+            }
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => {
+                            let enabled = val.as_bool().unwrap();
+                            if enabled && self.settings.pin.is_empty() {
+                                // A PIN is needed to leave kid mode, so set one first
+                                self.enable_after_pin = true;
+                                self.open_pin_input();
+                            } else {
+                                self.set_enabled(enabled, commands.clone()).await?;
+                            }
+                        }
+                        1 => {} // pin
+                        2 => {
+                            self.settings.daily_limit = val.as_int().unwrap() as u32;
+                            self.save_settings()?;
+                        }
+                        i => {
+                            let directory = &self.directories[i - FIXED_ROWS];
+                            if val.as_bool().unwrap() {
+                                self.settings.allowed.push(directory.clone());
+                            } else {
+                                self.settings.allowed.retain(|d| d != directory);
+                            }
+                            self.save_settings()?;
+                        }
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() == 1 => {
+                self.open_pin_input();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for KidMode {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
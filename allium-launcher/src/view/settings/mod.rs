@@ -1,12 +1,22 @@
 mod about;
+mod changelog;
+mod controls;
+mod date_time;
 mod display;
+mod kid_mode;
 mod language;
+mod profiles;
 mod theme;
+mod timezone;
 mod wifi;
 
 use self::about::About;
+use self::controls::Controls;
+use self::date_time::DateTime;
 use self::display::Display;
+use self::kid_mode::KidMode;
 use self::language::Language;
+use self::profiles::Profiles;
 use self::theme::Theme;
 use self::wifi::Wifi;
 
@@ -67,13 +77,17 @@ impl Settings {
         let styles = res.get::<Stylesheet>();
 
         let has_wifi = DefaultPlatform::has_wifi();
-        let mut labels = Vec::with_capacity(7);
+        let mut labels = Vec::with_capacity(9);
         if has_wifi {
             labels.push(locale.t("settings-wifi"));
         }
         labels.push(locale.t("settings-display"));
         labels.push(locale.t("settings-theme"));
         labels.push(locale.t("settings-language"));
+        labels.push(locale.t("settings-profiles"));
+        labels.push(locale.t("settings-kid-mode"));
+        labels.push(locale.t("settings-date-time"));
+        labels.push(locale.t("settings-controls"));
         labels.push(locale.t("settings-about"));
 
         let mut list = ScrollList::new(
@@ -94,7 +108,11 @@ impl Settings {
                 1 => Some(Box::new(Display::new(rect, res.clone(), Some(child)))),
                 2 => Some(Box::new(Theme::new(rect, res.clone(), Some(child)))),
                 3 => Some(Box::new(Language::new(rect, res.clone(), Some(child)))),
-                4 => Some(Box::new(About::new(rect, res.clone(), Some(child)))),
+                4 => Some(Box::new(Profiles::new(rect, res.clone(), Some(child)))),
+                5 => Some(Box::new(KidMode::new(rect, res.clone(), Some(child)))),
+                6 => Some(Box::new(DateTime::new(rect, res.clone(), Some(child)))),
+                7 => Some(Box::new(Controls::new(rect, res.clone(), Some(child)))),
+                8 => Some(Box::new(About::new(rect, res.clone(), Some(child)))),
                 _ => None,
             }
         } else {
@@ -144,17 +162,29 @@ impl Settings {
         }
     }
 
-    async fn select_entry(&mut self, _commands: Sender<Command>) -> Result<()> {
+    async fn select_entry(&mut self, commands: Sender<Command>) -> Result<()> {
         let mut selected = self.list.selected();
         if !self.has_wifi {
             selected += 1
         };
         match selected {
-            0 => self.child = Some(Box::new(Wifi::new(self.rect, self.res.clone(), None))),
+            0 => {
+                let mut wifi = Wifi::new(self.rect, self.res.clone(), None);
+                wifi.spawn_tasks(commands);
+                self.child = Some(Box::new(wifi));
+            }
             1 => self.child = Some(Box::new(Display::new(self.rect, self.res.clone(), None))),
             2 => self.child = Some(Box::new(Theme::new(self.rect, self.res.clone(), None))),
             3 => self.child = Some(Box::new(Language::new(self.rect, self.res.clone(), None))),
-            4 => self.child = Some(Box::new(About::new(self.rect, self.res.clone(), None))),
+            4 => self.child = Some(Box::new(Profiles::new(self.rect, self.res.clone(), None))),
+            5 => self.child = Some(Box::new(KidMode::new(self.rect, self.res.clone(), None))),
+            6 => self.child = Some(Box::new(DateTime::new(self.rect, self.res.clone(), None))),
+            7 => self.child = Some(Box::new(Controls::new(self.rect, self.res.clone(), None))),
+            8 => {
+                let mut about = About::new(self.rect, self.res.clone(), None);
+                about.spawn_tasks(commands);
+                self.child = Some(Box::new(about));
+            }
             _ => unreachable!("Invalid index"),
         }
         self.dirty = true;
@@ -0,0 +1,336 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::profile::Profiles as ProfilesSettings;
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Label, Row, SettingsList, Toggle, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Number of rows before the list of profiles.
+const FIXED_ROWS: usize = 2;
+
+pub struct Profiles {
+    rect: Rect,
+    res: Resources,
+    profiles: ProfilesSettings,
+    list: SettingsList,
+    keyboard: Option<Keyboard>,
+    /// Index of the profile that will be deleted if delete is pressed again.
+    pending_delete: Option<usize>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Profiles {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let profiles = ProfilesSettings::load().unwrap();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let (left, right) = items(&locale, &profiles);
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-profiles-delete"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-profiles-new"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            profiles,
+            list,
+            keyboard: None,
+            pending_delete: None,
+            button_hints,
+        }
+    }
+
+    /// Returns the profile on the selected row, if any.
+    fn selected_profile(&self) -> Option<&str> {
+        self.list
+            .selected()
+            .checked_sub(FIXED_ROWS)
+            .and_then(|i| self.profiles.profiles.get(i))
+            .map(|p| p.name.as_str())
+    }
+
+    fn reload_items(&mut self) {
+        let selected = self.list.selected();
+        let (left, right) = items(&self.res.get::<Locale>(), &self.profiles);
+        let len = left.len();
+        self.list.select(0);
+        self.list.set_items(left, right);
+        self.list.select(selected.min(len - 1));
+    }
+
+    fn set_pending_delete(&mut self, pending_delete: Option<usize>) {
+        self.pending_delete = pending_delete;
+        let locale = self.res.get::<Locale>();
+        self.button_hints
+            .get_mut(1)
+            .unwrap()
+            .set_text(if pending_delete.is_some() {
+                locale.t("settings-profiles-delete-confirm")
+            } else {
+                locale.t("settings-profiles-delete")
+            });
+    }
+}
+
+fn items(locale: &Locale, profiles: &ProfilesSettings) -> (Vec<String>, Vec<Box<dyn View>>) {
+    let active = profiles.active();
+    let mut left = vec![
+        locale.t("settings-profiles-separate-theme"),
+        locale.t("settings-profiles-separate-language"),
+    ];
+    let mut right: Vec<Box<dyn View>> = vec![
+        Box::new(Toggle::new(
+            Point::zero(),
+            active.is_some_and(|p| p.separate_theme),
+            Alignment::Right,
+        )),
+        Box::new(Toggle::new(
+            Point::zero(),
+            active.is_some_and(|p| p.separate_locale),
+            Alignment::Right,
+        )),
+    ];
+    for profile in &profiles.profiles {
+        left.push(profile.name.clone());
+        right.push(Box::new(Label::new(
+            Point::zero(),
+            if profile.name == profiles.active {
+                locale.t("settings-profiles-active")
+            } else {
+                String::new()
+            },
+            Alignment::Right,
+            None,
+        )));
+    }
+    (left, right)
+}
+
+#[async_trait(?Send)]
+impl View for Profiles {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(keyboard) = &mut self.keyboard {
+            return Ok(keyboard.should_draw() && keyboard.draw(display, styles)?);
+        }
+
+        let mut drawn = false;
+
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.keyboard.as_ref().is_some_and(|k| k.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut name = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        name = value.clone().as_string();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.keyboard = None;
+                    self.set_should_draw();
+                }
+                if let Some(name) = name {
+                    if let Err(e) = self.profiles.create(&name) {
+                        warn!("failed to create profile: {}", e);
+                    }
+                    self.reload_items();
+                }
+            }
+            return Ok(true);
+        }
+
+        if let KeyEvent::Pressed(key) = event {
+            if key != Key::X && self.pending_delete.is_some() {
+                self.set_pending_delete(None);
+            }
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    let active = self.profiles.active.clone();
+                    if let Some(profile) = self.profiles.get_mut(&active) {
+                        match i {
+                            0 => profile.separate_theme = val.as_bool().unwrap(),
+                            1 => profile.separate_locale = val.as_bool().unwrap(),
+                            _ => {}
+                        }
+                    }
+                    self.profiles.save()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(name) = self.selected_profile() {
+                    if name != self.profiles.active {
+                        commands
+                            .send(Command::SwitchProfile(name.to_string()))
+                            .await?;
+                    }
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                let selected = self.list.selected();
+                match self.selected_profile() {
+                    Some(name) if name != self.profiles.active => {
+                        if self.pending_delete == Some(selected) {
+                            let name = name.to_string();
+                            if let Err(e) = self.profiles.delete(&name) {
+                                warn!("failed to delete profile: {}", e);
+                            }
+                            self.set_pending_delete(None);
+                            self.reload_items();
+                        } else {
+                            self.set_pending_delete(Some(selected));
+                        }
+                    }
+                    _ => {}
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.keyboard = Some(Keyboard::new(self.res.clone(), String::new(), false));
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Profiles {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::datetime;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Row, ScrollList, View};
+use tokio::sync::mpsc::Sender;
+
+/// Lists the timezones of the bundled tz database. Pressing A bubbles `ValueChanged` with the
+/// name of the selected timezone.
+#[derive(Debug)]
+pub struct TimezonePicker {
+    rect: Rect,
+    timezones: Vec<String>,
+    list: ScrollList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl TimezonePicker {
+    pub fn new(rect: Rect, res: Resources, selected: &str) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let timezones = datetime::timezones();
+
+        let mut list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            timezones.clone(),
+            Alignment::Left,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        list.set_letter_jump(true);
+        if let Some(i) = timezones.iter().position(|tz| tz == selected) {
+            list.select(i);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        Self {
+            rect,
+            timezones,
+            list,
+            button_hints,
+            dirty: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for TimezonePicker {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                let timezone = self.timezones[self.list.selected()].clone();
+                bubble.push_back(Command::ValueChanged(0, Value::String(timezone)));
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
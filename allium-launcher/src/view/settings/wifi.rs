@@ -1,26 +1,60 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use common::command::Command;
-use common::constants::SELECTION_MARGIN;
+use common::constants::{FILE_SERVER_PORT, SELECTION_MARGIN, WIFI_STATUS_INTERVAL};
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, TextBox, Toggle, View};
-use common::wifi::{self, WiFiSettings};
+use common::view::{
+    ButtonHint, ButtonIcon, Keyboard, Label, Number, Row, SettingsList, Toggle, View,
+};
+use common::wifi::{self, ScannedNetwork, WiFiSettings, WiFiStatus};
+use log::warn;
 use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Number of rows before the add network row.
+const FIXED_ROWS: usize = 9;
+
+/// Rows of the status and IP address labels.
+const STATUS_ROW: usize = 1;
+const IP_ADDRESS_ROW: usize = 2;
+const FILE_SERVER_ADDRESS_ROW: usize = 7;
+const FILE_SERVER_TOKEN_ROW: usize = 8;
+
+/// What the keyboard is open for.
+enum KeyboardTarget {
+    /// Name of a network to add.
+    Ssid,
+    /// Password of the network with the given SSID.
+    Password(String),
+}
+
 pub struct Wifi {
     rect: Rect,
     res: Resources,
     settings: WiFiSettings,
     list: SettingsList,
-    has_ip_address: bool,
+    /// Visible networks that are not saved, in the same order as their rows.
+    visible: Vec<ScannedNetwork>,
+    /// Results of the last scan, set by the scan task and taken when drawing.
+    scan_results: Arc<Mutex<Option<Vec<ScannedNetwork>>>>,
+    /// Latest connection status, set by the status task and taken when drawing.
+    next_status: Arc<Mutex<Option<WiFiStatus>>>,
+    status: WiFiStatus,
+    /// Cleared when the view is dropped to stop the status task.
+    alive: Arc<AtomicBool>,
+    tasks_started: bool,
+    keyboard: Option<(Keyboard, KeyboardTarget)>,
+    /// Row of the saved network that will be removed if delete is pressed again.
+    pending_delete: Option<usize>,
     button_hints: Row<ButtonHint<String>>,
 }
 
@@ -33,6 +67,7 @@ impl Wifi {
         let locale = res.get::<Locale>();
         let styles = res.get::<Stylesheet>();
 
+        let (left, right) = items(&locale, &settings, &[]);
         let mut list = SettingsList::new(
             Rect::new(
                 x + 12,
@@ -40,46 +75,9 @@ impl Wifi {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-wifi-wifi-enabled"),
-                locale.t("settings-wifi-ip-address"),
-                locale.t("settings-wifi-wifi-network"),
-                locale.t("settings-wifi-wifi-password"),
-                locale.t("settings-wifi-ntp-enabled"),
-                locale.t("settings-wifi-telnet-enabled"),
-                locale.t("settings-wifi-ftp-enabled"),
-            ],
-            vec![
-                Box::new(Toggle::new(Point::zero(), settings.wifi, Alignment::Right)),
-                Box::new(Label::new(
-                    Point::zero(),
-                    String::new(),
-                    Alignment::Right,
-                    None,
-                )),
-                Box::new(TextBox::new(
-                    Point::zero(),
-                    res.clone(),
-                    settings.ssid.clone(),
-                    Alignment::Right,
-                    false,
-                )),
-                Box::new(TextBox::new(
-                    Point::zero(),
-                    res.clone(),
-                    settings.password.clone(),
-                    Alignment::Right,
-                    true,
-                )),
-                Box::new(Toggle::new(Point::zero(), settings.ntp, Alignment::Right)),
-                Box::new(Toggle::new(
-                    Point::zero(),
-                    settings.telnet,
-                    Alignment::Right,
-                )),
-                Box::new(Toggle::new(Point::zero(), settings.ftp, Alignment::Right)),
-            ],
-            res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
         );
         if let Some(state) = state {
             list.select(state.selected);
@@ -97,6 +95,18 @@ impl Wifi {
                     locale.t("button-edit"),
                     Alignment::Right,
                 ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-wifi-forget"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-wifi-scan"),
+                    Alignment::Right,
+                ),
                 ButtonHint::new(
                     Point::zero(),
                     Key::B,
@@ -111,15 +121,288 @@ impl Wifi {
         drop(locale);
         drop(styles);
 
-        Self {
+        let mut this = Self {
             rect,
             res,
             settings,
             list,
-            has_ip_address: false,
+            visible: Vec::new(),
+            scan_results: Arc::new(Mutex::new(None)),
+            next_status: Arc::new(Mutex::new(None)),
+            status: WiFiStatus::default(),
+            alive: Arc::new(AtomicBool::new(true)),
+            tasks_started: false,
+            keyboard: None,
+            pending_delete: None,
             button_hints,
+        };
+        this.update_status();
+        this
+    }
+
+    /// Starts the tasks that refresh the connection status and scan for networks. Both send a
+    /// redraw once they have new results.
+    pub fn spawn_tasks(&mut self, commands: Sender<Command>) {
+        self.tasks_started = true;
+
+        let next_status = Arc::clone(&self.next_status);
+        let alive = Arc::clone(&self.alive);
+        let status_commands = commands.clone();
+        tokio::spawn(async move {
+            let mut last = None;
+            while alive.load(Ordering::Relaxed) {
+                let next = wifi::status().await.unwrap_or_else(|_| WiFiStatus {
+                    ip_address: wifi::ip_address(),
+                    ..Default::default()
+                });
+                if last.as_ref() != Some(&next) {
+                    last = Some(next.clone());
+                    *next_status.lock().unwrap() = Some(next);
+                    if status_commands.send(Command::Redraw).await.is_err() {
+                        break;
+                    }
+                }
+                tokio::time::sleep(WIFI_STATUS_INTERVAL).await;
+            }
+        });
+
+        if self.settings.wifi {
+            self.scan(commands);
         }
     }
+
+    fn scan(&self, commands: Sender<Command>) {
+        let scan_results = Arc::clone(&self.scan_results);
+        tokio::spawn(async move {
+            match wifi::scan().await {
+                Ok(networks) => {
+                    *scan_results.lock().unwrap() = Some(networks);
+                    commands.send(Command::Redraw).await.ok();
+                }
+                Err(e) => warn!("failed to scan for networks: {}", e),
+            }
+        });
+    }
+
+    /// Returns the index of the saved network on the given row, if any.
+    fn saved_network(&self, row: usize) -> Option<usize> {
+        row.checked_sub(FIXED_ROWS + 1)
+            .filter(|&i| i < self.settings.networks.len())
+    }
+
+    /// Returns the visible network on the given row, if any.
+    fn visible_network(&self, row: usize) -> Option<&ScannedNetwork> {
+        row.checked_sub(FIXED_ROWS + 1 + self.settings.networks.len())
+            .and_then(|i| self.visible.get(i))
+    }
+
+    fn reload_items(&mut self, scanned: Option<Vec<ScannedNetwork>>) {
+        if let Some(mut scanned) = scanned {
+            scanned.retain(|n| !self.settings.networks.iter().any(|s| s.ssid == n.ssid));
+            self.visible = scanned;
+        } else {
+            let networks = &self.settings.networks;
+            self.visible
+                .retain(|n| !networks.iter().any(|s| s.ssid == n.ssid));
+        }
+
+        let selected = self.list.selected();
+        let (left, right) = items(&self.res.get::<Locale>(), &self.settings, &self.visible);
+        let len = left.len();
+        self.list.select(0);
+        self.list.set_items(left, right);
+        self.list.select(selected.min(len - 1));
+        self.update_status();
+    }
+
+    /// Shows the current connection status, IP address and file server address.
+    fn update_status(&mut self) {
+        let status = &self.status;
+        let locale = self.res.get::<Locale>();
+        let (text, ip_address) = if !self.settings.wifi {
+            (String::new(), String::new())
+        } else if let (true, Some(ssid)) = (status.is_connected(), &status.ssid) {
+            let mut map = HashMap::new();
+            map.insert("ssid".to_string(), ssid.clone().into());
+            (
+                locale.ta("settings-wifi-status-connected", &map),
+                status.ip_address.clone().unwrap_or_default(),
+            )
+        } else if let Some(ip_address) = &status.ip_address {
+            (locale.t("settings-wifi-status-online"), ip_address.clone())
+        } else {
+            (locale.t("settings-wifi-connecting"), String::new())
+        };
+        drop(locale);
+
+        let (file_server_address, file_server_token) = if self.settings.file_server {
+            (
+                if ip_address.is_empty() {
+                    String::new()
+                } else {
+                    format!("http://{}:{}", ip_address, FILE_SERVER_PORT)
+                },
+                self.settings.file_server_token.clone(),
+            )
+        } else {
+            (String::new(), String::new())
+        };
+
+        self.list.set_right(
+            STATUS_ROW,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
+        self.list.set_right(
+            IP_ADDRESS_ROW,
+            Box::new(Label::new(
+                Point::zero(),
+                ip_address,
+                Alignment::Right,
+                None,
+            )),
+        );
+        self.list.set_right(
+            FILE_SERVER_ADDRESS_ROW,
+            Box::new(Label::new(
+                Point::zero(),
+                file_server_address,
+                Alignment::Right,
+                None,
+            )),
+        );
+        self.list.set_right(
+            FILE_SERVER_TOKEN_ROW,
+            Box::new(Label::new(
+                Point::zero(),
+                file_server_token,
+                Alignment::Right,
+                None,
+            )),
+        );
+    }
+
+    fn set_pending_delete(&mut self, pending_delete: Option<usize>) {
+        self.pending_delete = pending_delete;
+        let locale = self.res.get::<Locale>();
+        self.button_hints
+            .get_mut(1)
+            .unwrap()
+            .set_text(if pending_delete.is_some() {
+                locale.t("settings-wifi-forget-confirm")
+            } else {
+                locale.t("settings-wifi-forget")
+            });
+    }
+
+    fn open_keyboard(&mut self, target: KeyboardTarget) {
+        let is_password = matches!(target, KeyboardTarget::Password(_));
+        self.keyboard = Some((
+            Keyboard::new(self.res.clone(), String::new(), is_password),
+            target,
+        ));
+    }
+
+    fn add_network(&mut self, ssid: String, password: String) -> Result<()> {
+        self.settings.add_network(ssid, password)?;
+        self.reload_items(None);
+        Ok(())
+    }
+}
+
+impl Drop for Wifi {
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Relaxed);
+    }
+}
+
+fn items(
+    locale: &Locale,
+    settings: &WiFiSettings,
+    visible: &[ScannedNetwork],
+) -> (Vec<String>, Vec<Box<dyn View>>) {
+    let mut left = vec![
+        locale.t("settings-wifi-wifi-enabled"),
+        locale.t("settings-wifi-status"),
+        locale.t("settings-wifi-ip-address"),
+        locale.t("settings-wifi-ntp-enabled"),
+        locale.t("settings-wifi-telnet-enabled"),
+        locale.t("settings-wifi-ftp-enabled"),
+        locale.t("settings-wifi-file-server-enabled"),
+        locale.t("settings-wifi-file-server-address"),
+        locale.t("settings-wifi-file-server-token"),
+        locale.t("settings-wifi-add-network"),
+    ];
+    let mut right: Vec<Box<dyn View>> = vec![
+        Box::new(Toggle::new(Point::zero(), settings.wifi, Alignment::Right)),
+        Box::new(Label::new(
+            Point::zero(),
+            String::new(),
+            Alignment::Right,
+            None,
+        )),
+        Box::new(Label::new(
+            Point::zero(),
+            String::new(),
+            Alignment::Right,
+            None,
+        )),
+        Box::new(Toggle::new(Point::zero(), settings.ntp, Alignment::Right)),
+        Box::new(Toggle::new(
+            Point::zero(),
+            settings.telnet,
+            Alignment::Right,
+        )),
+        Box::new(Toggle::new(Point::zero(), settings.ftp, Alignment::Right)),
+        Box::new(Toggle::new(
+            Point::zero(),
+            settings.file_server,
+            Alignment::Right,
+        )),
+        Box::new(Label::new(
+            Point::zero(),
+            String::new(),
+            Alignment::Right,
+            None,
+        )),
+        Box::new(Label::new(
+            Point::zero(),
+            String::new(),
+            Alignment::Right,
+            None,
+        )),
+        Box::new(Label::new(
+            Point::zero(),
+            String::new(),
+            Alignment::Right,
+            None,
+        )),
+    ];
+    for network in &settings.networks {
+        left.push(network.ssid.clone());
+        right.push(Box::new(Number::new(
+            Point::zero(),
+            network.priority,
+            0,
+            99,
+            Alignment::Right,
+        )));
+    }
+    for network in visible {
+        let mut map = HashMap::new();
+        map.insert("strength".to_string(), network.strength().into());
+        left.push(network.ssid.clone());
+        right.push(Box::new(Label::new(
+            Point::zero(),
+            if network.secured {
+                locale.ta("settings-wifi-signal-secured", &map)
+            } else {
+                locale.ta("settings-wifi-signal", &map)
+            },
+            Alignment::Right,
+            None,
+        )));
+    }
+    (left, right)
 }
 
 #[async_trait(?Send)]
@@ -129,47 +412,21 @@ impl View for Wifi {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
-        let mut drawn = false;
+        if let Some((keyboard, _)) = &mut self.keyboard {
+            return Ok(keyboard.should_draw() && keyboard.draw(display, styles)?);
+        }
 
-        let locale = self.res.get::<Locale>();
-        if self.settings.wifi {
-            if !self.has_ip_address {
-                // Try to get the IP address if we don't have it yet
-                if let Some(ip_address) = wifi::ip_address() {
-                    self.has_ip_address = true;
-                    self.list.set_right(
-                        1,
-                        Box::new(Label::new(
-                            Point::zero(),
-                            ip_address,
-                            Alignment::Right,
-                            None,
-                        )),
-                    );
-                } else {
-                    self.list.set_right(
-                        1,
-                        Box::new(Label::new(
-                            Point::zero(),
-                            locale.t("settings-wifi-connecting"),
-                            Alignment::Right,
-                            None,
-                        )),
-                    );
-                }
-            }
-        } else if self.has_ip_address {
-            self.has_ip_address = false;
-            self.list.set_right(
-                1,
-                Box::new(Label::new(
-                    Point::zero(),
-                    String::new(),
-                    Alignment::Right,
-                    None,
-                )),
-            );
+        let scanned = self.scan_results.lock().unwrap().take();
+        if scanned.is_some() {
+            self.reload_items(scanned);
         }
+        let status = self.next_status.lock().unwrap().take();
+        if let Some(status) = status {
+            self.status = status;
+            self.update_status();
+        }
+
+        let mut drawn = false;
 
         drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
         drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
@@ -178,10 +435,15 @@ impl View for Wifi {
     }
 
     fn should_draw(&self) -> bool {
-        self.list.should_draw() || self.button_hints.should_draw()
+        self.keyboard.as_ref().is_some_and(|(k, _)| k.should_draw())
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
+        if let Some((keyboard, _)) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
         self.list.set_should_draw();
         self.button_hints.set_should_draw();
     }
@@ -192,6 +454,61 @@ impl View for Wifi {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        if !self.tasks_started {
+            // Restored from saved state without a command sender, so start the tasks now
+            self.spawn_tasks(commands.clone());
+        }
+
+        if let Some((keyboard, _)) = self.keyboard.as_mut() {
+            if keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut value = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, val) => {
+                        value = val.clone().as_string();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                let target = if close {
+                    let (_, target) = self.keyboard.take().unwrap();
+                    self.set_should_draw();
+                    Some(target)
+                } else {
+                    None
+                };
+                match (value, target) {
+                    (Some(ssid), Some(KeyboardTarget::Ssid)) if !ssid.is_empty() => {
+                        // Ask for the password next
+                        self.open_keyboard(KeyboardTarget::Password(ssid));
+                    }
+                    (Some(password), Some(KeyboardTarget::Password(ssid))) => {
+                        if wifi::is_valid_password(&password) {
+                            self.add_network(ssid, password)?;
+                        } else {
+                            let text = self.res.get::<Locale>().t("settings-wifi-invalid-password");
+                            commands.send(Command::Toast(text)).await?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        if let KeyEvent::Pressed(key) = event {
+            if key != Key::X && self.pending_delete.is_some() {
+                self.set_pending_delete(None);
+            }
+        }
+
         if self
             .list
             .handle_key_event(event, commands.clone(), bubble)
@@ -202,32 +519,77 @@ impl View for Wifi {
                     match i {
                         0 => {
                             self.settings.set_wifi(val.as_bool().unwrap())?;
-                            let commands = commands.clone();
-                            tokio::spawn(async move {
-                                if wifi::wait_for_wifi().await.is_ok() {
-                                    commands.send(Command::Redraw).await.ok();
-                                }
-                            });
+                            self.update_status();
+                            if self.settings.wifi {
+                                let commands = commands.clone();
+                                let scan_results = Arc::clone(&self.scan_results);
+                                tokio::spawn(async move {
+                                    if wifi::wait_for_wifi().await.is_ok() {
+                                        if let Ok(networks) = wifi::scan().await {
+                                            *scan_results.lock().unwrap() = Some(networks);
+                                        }
+                                        commands.send(Command::Redraw).await.ok();
+                                    }
+                                });
+                            }
+                        }
+                        STATUS_ROW | IP_ADDRESS_ROW => {}
+                        3 => self.settings.toggle_ntp(val.as_bool().unwrap())?,
+                        4 => self.settings.toggle_telnet(val.as_bool().unwrap())?,
+                        5 => self.settings.toggle_ftp(val.as_bool().unwrap())?,
+                        6 => {
+                            self.settings.toggle_file_server(val.as_bool().unwrap())?;
+                            self.update_status();
+                        }
+                        FILE_SERVER_ADDRESS_ROW | FILE_SERVER_TOKEN_ROW => {}
+                        i => {
+                            if let Some(n) = self.saved_network(i) {
+                                let ssid = self.settings.networks[n].ssid.clone();
+                                self.settings.set_priority(&ssid, val.as_int().unwrap())?;
+                            }
                         }
-                        1 => {} // ip address
-                        2 => self
-                            .settings
-                            .set_ssid(val.as_string().unwrap().to_string())?,
-                        3 => self
-                            .settings
-                            .set_password(val.as_string().unwrap().to_string())?,
-                        4 => self.settings.toggle_ntp(val.as_bool().unwrap())?,
-                        5 => self.settings.toggle_telnet(val.as_bool().unwrap())?,
-                        6 => self.settings.toggle_ftp(val.as_bool().unwrap())?,
-                        _ => unreachable!("Invalid index"),
                     }
                 }
                 self.settings.save()?;
+                WiFiSettings::notify_alliumd().await;
             }
             return Ok(true);
         }
 
         match event {
+            KeyEvent::Pressed(Key::A) => {
+                let selected = self.list.selected();
+                if selected == FIXED_ROWS {
+                    self.open_keyboard(KeyboardTarget::Ssid);
+                } else if let Some(network) = self.visible_network(selected).cloned() {
+                    if network.secured {
+                        self.open_keyboard(KeyboardTarget::Password(network.ssid));
+                    } else {
+                        self.add_network(network.ssid, String::new())?;
+                    }
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                let selected = self.list.selected();
+                if let Some(n) = self.saved_network(selected) {
+                    if self.pending_delete == Some(selected) {
+                        let ssid = self.settings.networks[n].ssid.clone();
+                        self.settings.remove_network(&ssid)?;
+                        self.set_pending_delete(None);
+                        self.reload_items(None);
+                    } else {
+                        self.set_pending_delete(Some(selected));
+                    }
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                if self.settings.wifi {
+                    self.scan(commands);
+                }
+                Ok(true)
+            }
             KeyEvent::Pressed(Key::B) => {
                 bubble.push_back(Command::CloseView);
                 Ok(true)
@@ -8,8 +8,9 @@ use common::display::color::Color;
 use common::display::Display;
 use common::game_info::GameInfo;
 use common::geom;
+use common::kid_mode::KidModeSettings;
 use common::locale::{Locale, LocaleSettings};
-use common::platform::{DefaultPlatform, Platform};
+use common::platform::{DefaultPlatform, InputProcessor, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
 use common::view::View;
@@ -27,6 +28,7 @@ where
     P: Platform,
 {
     platform: P,
+    input: InputProcessor,
     display: P::Display,
     res: Resources,
     view: IngameMenu<P::Battery>,
@@ -48,11 +50,16 @@ impl AlliumMenu<DefaultPlatform> {
         res.insert(GameInfo::load()?.unwrap_or_default());
         res.insert(Stylesheet::load()?);
         res.insert(Locale::new(&LocaleSettings::load()?.lang));
+        res.insert(KidModeSettings::load()?);
         res.insert(Into::<geom::Size>::into(display.size()));
         let res = Resources::new(res);
 
+        let mut input = InputProcessor::load();
+        input.set_swap_buttons(res.get::<Stylesheet>().swap_buttons);
+
         Ok(AlliumMenu {
             platform,
+            input,
             display,
             res: res.clone(),
             view: IngameMenu::load_or_new(
@@ -93,7 +100,7 @@ impl AlliumMenu<DefaultPlatform> {
                 Some(command) = rx.recv() => {
                     self.handle_command(command)?;
                 }
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
@@ -105,7 +112,7 @@ impl AlliumMenu<DefaultPlatform> {
                 Some(command) = rx.recv() => {
                     self.handle_command(command)?;
                 }
-                event = self.platform.poll() => {
+                event = self.input.poll(&mut self.platform) => {
                     let mut bubble = VecDeque::new();
                     self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
                 }
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::cheats::Cheats;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::game_info::GameInfo;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, Toggle, View};
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+/// Lists the cheats of the current game, each with a toggle. Cheats that RetroArch didn't load
+/// when the game was launched apply after a restart.
+pub struct CheatsMenu {
+    rect: Rect,
+    cheats: Cheats,
+    title: Label<String>,
+    list: SettingsList,
+    note: Option<Label<String>>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl CheatsMenu {
+    pub fn new(rect: Rect, res: Resources, cheats: Cheats) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("ingame-menu-cheats"),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 24,
+                y + 8 + styles.ui_font.size as i32 + 8,
+                w - 48,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            (0..cheats.len())
+                .map(|i| cheats.desc(i).to_string())
+                .collect(),
+            (0..cheats.len())
+                .map(|i| {
+                    Box::new(Toggle::new(
+                        Point::zero(),
+                        cheats.is_enabled(i),
+                        Alignment::Right,
+                    )) as Box<dyn View>
+                })
+                .collect(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let note = (!cheats.is_loaded()).then(|| {
+            Label::new(
+                Point::new(
+                    x + 12,
+                    y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+                ),
+                locale.t("cheats-restart-note"),
+                Alignment::Left,
+                None,
+            )
+        });
+
+        let mut hints = vec![ButtonHint::new(
+            Point::zero(),
+            Key::A,
+            locale.t("button-edit"),
+            Alignment::Right,
+        )];
+        if !cheats.is_loaded() {
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::X,
+                locale.t("cheats-restart"),
+                Alignment::Right,
+            ));
+        }
+        hints.push(ButtonHint::new(
+            Point::zero(),
+            Key::B,
+            locale.t("button-back"),
+            Alignment::Right,
+        ));
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            hints,
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            cheats,
+            title,
+            list,
+            note,
+            button_hints,
+            dirty: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for CheatsMenu {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            if let Some(note) = self.note.as_mut() {
+                note.set_should_draw();
+            }
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if let Some(note) = self.note.as_mut() {
+            drawn |= note.should_draw() && note.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.note.as_ref().is_some_and(|note| note.should_draw())
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    if let Err(e) = self.cheats.set_enabled(i, val.as_bool().unwrap()).await {
+                        error!("failed to set cheat: {}", e);
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::X) if !self.cheats.is_loaded() => {
+                GameInfo::request_relaunch().await?;
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        let mut children: Vec<&dyn View> = vec![&self.title, &self.list];
+        if let Some(note) = &self.note {
+            children.push(note);
+        }
+        children.push(&self.button_hints);
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        let mut children: Vec<&mut dyn View> = vec![&mut self.title, &mut self.list];
+        if let Some(note) = self.note.as_mut() {
+            children.push(note);
+        }
+        children.push(&mut self.button_hints);
+        children
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -4,26 +4,30 @@ use std::fs::File;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use common::alliumd::AlliumDRequest;
 use common::battery::Battery;
+use common::cheats::Cheats;
 use common::command::Command;
 use common::constants::{ALLIUM_MENU_STATE, SELECTION_MARGIN};
 use common::display::Display;
 use common::game_info::GameInfo;
 use common::geom::{Alignment, Point, Rect};
+use common::kid_mode::KidModeSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::retroarch::RetroArchCommand;
 use common::stylesheet::Stylesheet;
 use common::view::{
-    BatteryIndicator, ButtonHint, ButtonIcon, Label, NullView, Row, SettingsList, View,
+    BatteryIndicator, ButtonHint, ButtonIcon, CoreOptionsEditor, Label, NullView, Row,
+    SettingsList, VideoSettingsEditor, View,
 };
-use log::warn;
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
 use strum::{EnumCount, EnumIter, IntoEnumIterator};
 use tokio::sync::mpsc::Sender;
 
-use crate::view::TextReader;
+use crate::view::{CheatsMenu, NetplayMenu, ShadersMenu, TextReader};
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct IngameMenuState {
@@ -39,7 +43,10 @@ where
     name: Label<String>,
     battery_indicator: BatteryIndicator<B>,
     menu: SettingsList,
+    entries: Vec<MenuEntry>,
     child: Option<TextReader>,
+    /// Page opened from the menu, e.g. netplay or cheats.
+    page: Option<Box<dyn View>>,
     button_hints: Row<ButtonHint<String>>,
     disk_slot: u8,
     max_disk_slots: u8,
@@ -76,6 +83,32 @@ where
 
         let battery_indicator = BatteryIndicator::new(Point::new(w as i32 - 12, y + 8), battery);
 
+        let has_cheats = find_cheats(&game_info).is_some();
+        let kid_mode = res.get::<KidModeSettings>().enabled;
+
+        let entries: Vec<MenuEntry> = MenuEntry::iter()
+            .filter(|e| match e {
+                // Kid mode only allows playing, so anything that changes settings is hidden
+                MenuEntry::Cheats
+                | MenuEntry::CoreOptions
+                | MenuEntry::VideoSettings
+                | MenuEntry::Shaders
+                | MenuEntry::Netplay
+                | MenuEntry::Settings
+                    if kid_mode =>
+                {
+                    false
+                }
+                MenuEntry::Cheats => has_cheats,
+                MenuEntry::CoreOptions | MenuEntry::VideoSettings | MenuEntry::Shaders => {
+                    game_info.core().is_some()
+                }
+                MenuEntry::Netplay => game_info.has_menu && DefaultPlatform::has_wifi(),
+                MenuEntry::Guide => game_info.guide.is_some(),
+                _ => true,
+            })
+            .collect();
+
         let mut menu = SettingsList::new(
             Rect::new(
                 x + 24,
@@ -83,29 +116,10 @@ where
                 w / 2 - 48,
                 h - 8 - styles.ui_font.size - 8,
             ),
-            MenuEntry::iter()
-                .filter_map(|e| match e {
-                    MenuEntry::Guide => {
-                        if game_info.guide.is_some() {
-                            Some(e.as_str(&locale))
-                        } else {
-                            None
-                        }
-                    }
-                    _ => Some(e.as_str(&locale)),
-                })
-                .collect(),
-            MenuEntry::iter()
-                .filter_map(|e| match e {
-                    MenuEntry::Guide => {
-                        if game_info.guide.is_some() {
-                            Some(Box::new(NullView) as Box<dyn View>)
-                        } else {
-                            None
-                        }
-                    }
-                    _ => Some(Box::new(NullView) as Box<dyn View>),
-                })
+            entries.iter().map(|e| e.as_str(&locale)).collect(),
+            entries
+                .iter()
+                .map(|_| Box::new(NullView) as Box<dyn View>)
                 .collect(),
             styles.ui_font.size + SELECTION_MARGIN,
         );
@@ -163,7 +177,9 @@ where
             name,
             battery_indicator,
             menu,
+            entries,
             child,
+            page: None,
             button_hints,
             disk_slot,
             max_disk_slots,
@@ -221,29 +237,7 @@ where
     }
 
     async fn select_entry(&mut self, commands: Sender<Command>) -> Result<bool> {
-        let selected = match self.menu.selected() {
-            0 => MenuEntry::Continue,
-            1 => MenuEntry::Save,
-            2 => MenuEntry::Load,
-            3 => MenuEntry::Reset,
-            4 => {
-                if self.res.get::<GameInfo>().guide.is_some() {
-                    MenuEntry::Guide
-                } else {
-                    MenuEntry::Settings
-                }
-            }
-            5 => {
-                if self.res.get::<GameInfo>().guide.is_some() {
-                    MenuEntry::Settings
-                } else {
-                    MenuEntry::Quit
-                }
-            }
-            6 => MenuEntry::Quit,
-            _ => unreachable!(),
-        };
-        match selected {
+        match self.entries[self.menu.selected()] {
             MenuEntry::Continue => {
                 commands.send(Command::Exit).await?;
             }
@@ -263,6 +257,70 @@ where
                 RetroArchCommand::Reset.send().await?;
                 commands.send(Command::Exit).await?;
             }
+            MenuEntry::Cheats => {
+                let cheats = find_cheats(&self.res.get::<GameInfo>());
+                if let Some(cheats) = cheats {
+                    self.page = Some(Box::new(CheatsMenu::new(
+                        self.rect,
+                        self.res.clone(),
+                        cheats,
+                    )));
+                    self.set_should_draw();
+                }
+            }
+            MenuEntry::CoreOptions => {
+                let game_info = self.res.get::<GameInfo>();
+                let core = game_info.core().map(str::to_string);
+                let rom = game_info.path.clone();
+                drop(game_info);
+                if let Some(core) = core {
+                    self.page = Some(Box::new(CoreOptionsEditor::new(
+                        self.rect,
+                        self.res.clone(),
+                        core,
+                        rom,
+                        true,
+                    )?));
+                    self.set_should_draw();
+                }
+            }
+            MenuEntry::VideoSettings => {
+                let game_info = self.res.get::<GameInfo>();
+                let core = game_info.core().map(str::to_string);
+                let rom = game_info.path.clone();
+                drop(game_info);
+                if let Some(core) = core {
+                    self.page = Some(Box::new(VideoSettingsEditor::new(
+                        self.rect,
+                        self.res.clone(),
+                        &core,
+                        &rom,
+                        true,
+                    )?));
+                    self.set_should_draw();
+                }
+            }
+            MenuEntry::Shaders => {
+                let game_info = self.res.get::<GameInfo>();
+                let core = game_info.core().map(str::to_string);
+                let rom = game_info.path.clone();
+                drop(game_info);
+                if let Some(core) = core {
+                    self.page = Some(Box::new(ShadersMenu::new(
+                        self.rect,
+                        self.res.clone(),
+                        &core,
+                        &rom,
+                    )?));
+                    self.set_should_draw();
+                }
+            }
+            MenuEntry::Netplay => {
+                let mut netplay = NetplayMenu::new(self.rect, self.res.clone());
+                netplay.discover(commands);
+                self.page = Some(Box::new(netplay));
+                self.set_should_draw();
+            }
             MenuEntry::Guide => {
                 if let Some(guide) = self.res.get::<GameInfo>().guide.as_ref() {
                     self.child = Some(TextReader::new(self.rect, self.res.clone(), guide.clone()));
@@ -273,7 +331,8 @@ where
                 commands.send(Command::Exit).await?;
             }
             MenuEntry::Quit => {
-                RetroArchCommand::Quit.send().await?;
+                // alliumd closes the menu and quits RetroArch
+                AlliumDRequest::Quit.send().await?.into_result()?;
                 commands.send(Command::Exit).await?;
             }
         }
@@ -281,6 +340,14 @@ where
     }
 }
 
+fn find_cheats(game_info: &GameInfo) -> Option<Cheats> {
+    let core = game_info.core()?;
+    Cheats::find(core, &game_info.path, game_info.start_time)
+        .map_err(|e| error!("failed to load cheats: {}", e))
+        .ok()
+        .flatten()
+}
+
 #[async_trait(?Send)]
 impl<B> View for IngameMenu<B>
 where
@@ -300,6 +367,8 @@ where
 
         if let Some(child) = self.child.as_mut() {
             drawn |= child.should_draw() && child.draw(display, styles)?;
+        } else if let Some(page) = self.page.as_mut() {
+            drawn |= page.should_draw() && page.draw(display, styles)?;
         } else {
             drawn |= self.name.should_draw() && self.name.draw(display, styles)?;
             drawn |= self.battery_indicator.should_draw()
@@ -314,6 +383,8 @@ where
     fn should_draw(&self) -> bool {
         if let Some(child) = self.child.as_ref() {
             self.dirty || child.should_draw()
+        } else if let Some(page) = self.page.as_ref() {
+            self.dirty || page.should_draw()
         } else {
             self.dirty
                 || self.name.should_draw()
@@ -327,6 +398,8 @@ where
         self.dirty = true;
         if let Some(child) = self.child.as_mut() {
             child.set_should_draw();
+        } else if let Some(page) = self.page.as_mut() {
+            page.set_should_draw();
         } else {
             self.name.set_should_draw();
             self.battery_indicator.set_should_draw();
@@ -358,6 +431,23 @@ where
             }
         }
 
+        if let Some(page) = self.page.as_mut() {
+            if page
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                bubble.retain(|cmd| match cmd {
+                    Command::CloseView => {
+                        self.page = None;
+                        self.set_should_draw();
+                        false
+                    }
+                    _ => true,
+                });
+            }
+            return Ok(true);
+        }
+
         // Handle disk slot selection
         let selected = self.menu.selected();
         if self.max_disk_slots > 1 && selected == MenuEntry::Continue as usize {
@@ -532,6 +622,11 @@ pub enum MenuEntry {
     Save,
     Load,
     Reset,
+    Cheats,
+    CoreOptions,
+    VideoSettings,
+    Shaders,
+    Netplay,
     Guide,
     Settings,
     Quit,
@@ -544,6 +639,11 @@ impl MenuEntry {
             MenuEntry::Save => locale.t("ingame-menu-save"),
             MenuEntry::Load => locale.t("ingame-menu-load"),
             MenuEntry::Reset => locale.t("ingame-menu-reset"),
+            MenuEntry::Cheats => locale.t("ingame-menu-cheats"),
+            MenuEntry::CoreOptions => locale.t("ingame-menu-core-options"),
+            MenuEntry::VideoSettings => locale.t("ingame-menu-video-settings"),
+            MenuEntry::Shaders => locale.t("ingame-menu-shaders"),
+            MenuEntry::Netplay => locale.t("ingame-menu-netplay"),
             MenuEntry::Guide => locale.t("ingame-menu-guide"),
             MenuEntry::Settings => locale.t("ingame-menu-settings"),
             MenuEntry::Quit => locale.t("ingame-menu-quit"),
@@ -1,5 +1,11 @@
+mod cheats;
 mod ingame_menu;
+mod netplay;
+mod shaders;
 mod text_reader;
 
+pub use cheats::CheatsMenu;
 pub use ingame_menu::IngameMenu;
+pub use netplay::NetplayMenu;
+pub use shaders::ShadersMenu;
 pub use text_reader::TextReader;
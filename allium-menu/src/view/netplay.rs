@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::alliumd::AlliumDRequest;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::netplay::{self, Netplay, NetplayHost};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Label, NullView, Row, SettingsList, View};
+use log::{info, warn};
+use tokio::sync::mpsc::Sender;
+
+const HOST_ROW: usize = 0;
+const JOIN_BY_IP_ROW: usize = 1;
+/// Rows before the discovered hosts.
+const FIXED_ROWS: usize = 2;
+
+/// Hosts the current game, or joins a game on the LAN. Starting a session quits RetroArch and
+/// lets alliumd relaunch the game with the netplay arguments.
+pub struct NetplayMenu {
+    rect: Rect,
+    res: Resources,
+    title: Label<String>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+    keyboard: Option<Keyboard>,
+    hosts: Vec<NetplayHost>,
+    /// Set by the discovery task once it finishes.
+    discovered: Arc<Mutex<Option<Vec<NetplayHost>>>>,
+    searching: bool,
+    dirty: bool,
+}
+
+impl NetplayMenu {
+    pub fn new(rect: Rect, res: Resources) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("ingame-menu-netplay"),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let (left, right) = items(&locale, &[], false);
+        let list = SettingsList::new(
+            Rect::new(
+                x + 24,
+                y + 8 + styles.ui_font.size as i32 + 8,
+                w - 48,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("netplay-refresh"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            title,
+            list,
+            button_hints,
+            keyboard: None,
+            hosts: Vec::new(),
+            discovered: Arc::new(Mutex::new(None)),
+            searching: false,
+            dirty: true,
+        }
+    }
+
+    /// Broadcasts a discovery query in the background. The list is updated on the next redraw.
+    pub fn discover(&mut self, commands: Sender<Command>) {
+        if self.searching {
+            return;
+        }
+        self.searching = true;
+        self.hosts.clear();
+        self.reload_items();
+
+        let discovered = Arc::clone(&self.discovered);
+        tokio::spawn(async move {
+            let hosts = netplay::discover().await.unwrap_or_else(|e| {
+                warn!("failed to discover netplay hosts: {}", e);
+                Vec::new()
+            });
+            *discovered.lock().unwrap() = Some(hosts);
+            commands.send(Command::Redraw).await.ok();
+        });
+    }
+
+    fn reload_items(&mut self) {
+        let selected = self.list.selected();
+        let (left, right) = items(&self.res.get::<Locale>(), &self.hosts, self.searching);
+        let len = left.len();
+        self.list.select(0);
+        self.list.set_items(left, right);
+        self.list.select(selected.min(len - 1));
+    }
+
+    /// Asks alliumd to relaunch the game with the session.
+    async fn start(&self, netplay: Netplay, commands: Sender<Command>) -> Result<()> {
+        info!("starting netplay session: {:?}", netplay);
+        AlliumDRequest::Relaunch {
+            netplay: Some(netplay),
+        }
+        .send()
+        .await?
+        .into_result()?;
+        commands.send(Command::Exit).await?;
+        Ok(())
+    }
+}
+
+fn items(
+    locale: &Locale,
+    hosts: &[NetplayHost],
+    searching: bool,
+) -> (Vec<String>, Vec<Box<dyn View>>) {
+    let mut left = vec![locale.t("netplay-host"), locale.t("netplay-join-by-ip")];
+    let mut right: Vec<Box<dyn View>> = vec![Box::new(NullView), Box::new(NullView)];
+
+    if hosts.is_empty() {
+        left.push(if searching {
+            locale.t("netplay-searching")
+        } else {
+            locale.t("netplay-no-hosts")
+        });
+        right.push(Box::new(NullView));
+    }
+    for host in hosts {
+        left.push(host.nick.clone());
+        let mut content = format!("{} ({})", host.content, host.core);
+        if host.has_password {
+            content.push(' ');
+            content.push_str(&locale.t("netplay-password"));
+        }
+        right.push(Box::new(Label::new(
+            Point::zero(),
+            content,
+            Alignment::Right,
+            None,
+        )));
+    }
+
+    (left, right)
+}
+
+#[async_trait(?Send)]
+impl View for NetplayMenu {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            return Ok(keyboard.should_draw() && keyboard.draw(display, styles)?);
+        }
+
+        let discovered = self.discovered.lock().unwrap().take();
+        if let Some(hosts) = discovered {
+            self.hosts = hosts;
+            self.searching = false;
+            self.reload_items();
+        }
+
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        if let Some(keyboard) = self.keyboard.as_ref() {
+            return keyboard.should_draw();
+        }
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let mut value = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, val) => {
+                        value = val.clone().as_string();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.keyboard = None;
+                    self.set_should_draw();
+                }
+                if let Some(value) = value {
+                    match netplay::parse_address(&value) {
+                        Some(address) => self.start(Netplay::Join(address), commands).await?,
+                        None => warn!("invalid netplay address: {}", value),
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                match self.list.selected() {
+                    HOST_ROW => self.start(Netplay::Host, commands).await?,
+                    JOIN_BY_IP_ROW => {
+                        self.keyboard = Some(Keyboard::new(self.res.clone(), String::new(), false));
+                    }
+                    row => {
+                        if let Some(host) = self.hosts.get(row - FIXED_ROWS) {
+                            self.start(Netplay::Join(host.address), commands).await?;
+                        }
+                    }
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.discover(commands);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Left | Key::Right)
+            | KeyEvent::Autorepeat(Key::Left | Key::Right) => {
+                // Don't scroll with left/right
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.title, &mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
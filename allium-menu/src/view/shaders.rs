@@ -0,0 +1,548 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::game_info::GameInfo;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::overrides::{OverrideScope, Overrides};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::shaders::{self, Preset};
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, ScrollList, Select, SettingsList, View};
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+const SCOPE_ROW: usize = 0;
+const SHADER_ROW: usize = 1;
+const OVERLAY_ROW: usize = 2;
+
+/// Picks the video filter and overlay of the current game, saved as RetroArch overrides for the
+/// game, its console or every game run with the core. RetroArch can't switch to a specific filter
+/// or overlay over the network command interface, so changes apply after a restart.
+pub struct ShadersMenu {
+    rect: Rect,
+    res: Resources,
+    /// Overrides of each scope, from most to least specific.
+    overrides: Vec<Overrides>,
+    /// Index of the scope that changes are saved to.
+    scope: usize,
+    filters: Vec<Preset>,
+    overlays: Vec<Preset>,
+    title: Label<String>,
+    list: SettingsList,
+    note: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    picker: Option<PresetPicker>,
+    dirty: bool,
+}
+
+impl ShadersMenu {
+    pub fn new(rect: Rect, res: Resources, core: &str, rom: &Path) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let overrides = OverrideScope::ALL
+            .iter()
+            .map(|scope| Overrides::load(core, rom, *scope))
+            .collect::<Result<Vec<_>>>()?;
+        // Edit the most specific overrides that already set a filter or overlay
+        let scope = overrides
+            .iter()
+            .position(|o| shaders::KEYS.iter().any(|k| o.get(k).is_some()))
+            .unwrap_or_default();
+
+        let filters = shaders::video_filters();
+        let overlays = shaders::overlays();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("ingame-menu-shaders"),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 24,
+                y + 8 + styles.ui_font.size as i32 + 8,
+                w - 48,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            vec![
+                locale.t("shaders-scope"),
+                locale.t("shaders-shader"),
+                locale.t("shaders-overlay"),
+            ],
+            vec![
+                Box::new(Select::new(
+                    Point::zero(),
+                    scope,
+                    vec![
+                        locale.t("shaders-scope-game"),
+                        locale.t("shaders-scope-console"),
+                        locale.t("shaders-scope-core"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    String::new(),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    String::new(),
+                    Alignment::Right,
+                    None,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let note = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            locale.t("shaders-restart-note"),
+            Alignment::Left,
+            None,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::X,
+                    locale.t("shaders-restart"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            overrides,
+            scope,
+            filters,
+            overlays,
+            title,
+            list,
+            note,
+            button_hints,
+            picker: None,
+            dirty: true,
+        };
+        this.update_values();
+        Ok(this)
+    }
+
+    /// The video filter RetroArch would use with the selected scope's overrides.
+    fn video_filter(&self) -> Option<PathBuf> {
+        self.overrides[self.scope..]
+            .iter()
+            .find_map(shaders::video_filter)
+            .flatten()
+    }
+
+    /// The overlay RetroArch would use with the selected scope's overrides.
+    fn overlay(&self) -> Option<PathBuf> {
+        self.overrides[self.scope..]
+            .iter()
+            .find_map(shaders::overlay)
+            .flatten()
+    }
+
+    fn update_values(&mut self) {
+        let filter = preset_name(
+            &self.res.get::<Locale>(),
+            &self.filters,
+            self.video_filter(),
+        );
+        let overlay = preset_name(&self.res.get::<Locale>(), &self.overlays, self.overlay());
+        self.list.set_right(
+            SHADER_ROW,
+            Box::new(Label::new(Point::zero(), filter, Alignment::Right, None)),
+        );
+        self.list.set_right(
+            OVERLAY_ROW,
+            Box::new(Label::new(Point::zero(), overlay, Alignment::Right, None)),
+        );
+    }
+
+    fn open_picker(&mut self, row: usize) {
+        let (presets, selected) = match row {
+            SHADER_ROW => (&self.filters, self.video_filter()),
+            OVERLAY_ROW => (&self.overlays, self.overlay()),
+            _ => return,
+        };
+        self.picker = Some(PresetPicker::new(
+            self.rect,
+            self.res.clone(),
+            row,
+            presets,
+            selected.as_deref(),
+        ));
+    }
+
+    /// Saves the preset picked for a row. Index 0 turns it off.
+    fn set_preset(&mut self, row: usize, index: usize) -> Result<()> {
+        let overrides = &mut self.overrides[self.scope];
+        match row {
+            SHADER_ROW => {
+                let filter = index.checked_sub(1).map(|i| self.filters[i].path.as_path());
+                shaders::set_video_filter(overrides, filter);
+            }
+            OVERLAY_ROW => {
+                let overlay = index
+                    .checked_sub(1)
+                    .map(|i| self.overlays[i].path.as_path());
+                shaders::set_overlay(overrides, overlay);
+            }
+            _ => return Ok(()),
+        }
+        overrides.save()?;
+        self.update_values();
+        Ok(())
+    }
+
+    /// Quits RetroArch and lets alliumd launch the game again with the new overrides.
+    async fn restart(&self, commands: Sender<Command>) -> Result<()> {
+        GameInfo::request_relaunch().await?;
+        commands.send(Command::Exit).await?;
+        Ok(())
+    }
+}
+
+fn preset_name(locale: &Locale, presets: &[Preset], path: Option<PathBuf>) -> String {
+    match path {
+        Some(path) => presets
+            .iter()
+            .find(|p| p.path == path)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| {
+                path.file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            }),
+        None => locale.t("shaders-off"),
+    }
+}
+
+#[async_trait(?Send)]
+impl View for ShadersMenu {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(picker) = &mut self.picker {
+            return Ok(picker.should_draw() && picker.draw(display, styles)?);
+        }
+
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.note.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.note.should_draw() && self.note.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        if let Some(picker) = &self.picker {
+            return picker.should_draw();
+        }
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.note.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(picker) = self.picker.as_mut() {
+            picker.set_should_draw();
+        }
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(picker) = self.picker.as_mut() {
+            if picker
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                let row = picker.row;
+                let mut picked = None;
+                let mut close = false;
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, value) => {
+                        picked = value.clone().as_int();
+                        false
+                    }
+                    Command::CloseView => {
+                        close = true;
+                        false
+                    }
+                    _ => true,
+                });
+                if close {
+                    self.picker = None;
+                    self.set_should_draw();
+                }
+                if let Some(index) = picked {
+                    if let Err(e) = self.set_preset(row, index as usize) {
+                        error!("failed to save overrides: {}", e);
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(SCOPE_ROW, val) = command {
+                    self.scope = val.as_int().unwrap() as usize;
+                    self.update_values();
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.open_picker(self.list.selected());
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.restart(commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.note, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.note,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+/// Lists the presets of a row, with "Off" first. Pressing A bubbles `ValueChanged` with the
+/// index of the selected entry.
+struct PresetPicker {
+    rect: Rect,
+    row: usize,
+    list: ScrollList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl PresetPicker {
+    fn new(
+        rect: Rect,
+        res: Resources,
+        row: usize,
+        presets: &[Preset],
+        selected: Option<&Path>,
+    ) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let mut list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            std::iter::once(locale.t("shaders-off"))
+                .chain(presets.iter().map(|p| p.name.clone()))
+                .collect(),
+            Alignment::Left,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        list.set_letter_jump(true);
+        if let Some(i) = selected.and_then(|s| presets.iter().position(|p| p.path == s)) {
+            list.select(i + 1);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        Self {
+            rect,
+            row,
+            list,
+            button_hints,
+            dirty: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for PresetPicker {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                bubble.push_back(Command::ValueChanged(
+                    self.row,
+                    Value::Int(self.list.selected() as i32),
+                ));
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
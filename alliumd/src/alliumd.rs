@@ -1,26 +1,43 @@
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{DateTime, Duration, Utc};
+use common::alliumd::{AlliumDRequest, AlliumDResponse, AlliumDStatus, BatteryStatus, GameStatus};
 use common::battery::Battery;
 use common::constants::{
-    ALLIUMD_STATE, ALLIUM_GAME_INFO, ALLIUM_MENU, ALLIUM_SD_ROOT, ALLIUM_VERSION,
-    BATTERY_SHUTDOWN_THRESHOLD, BATTERY_UPDATE_INTERVAL,
+    ALLIUMD_STATE, ALLIUM_GAME_INFO, ALLIUM_LAUNCHER, ALLIUM_MENU, ALLIUM_SD_ROOT, ALLIUM_VERSION,
+    BATTERY_LOW_THRESHOLD, BATTERY_SHUTDOWN_THRESHOLD, BATTERY_UPDATE_INTERVAL,
+    KID_MODE_CHECK_INTERVAL, KID_MODE_WARNING_TIME, RETROARCH_QUIT_TIMEOUT,
 };
+use common::core_options::CoreOptions;
 use common::display::settings::DisplaySettings;
+use common::hotkeys::{HotkeyAction, HotkeyMatcher, Hotkeys};
+use common::kid_mode::KidModeSettings;
+use common::locale::{Locale, LocaleSettings};
+use common::netplay::Netplay;
 use common::retroarch::RetroArchCommand;
+use common::save_backup::SaveBackup;
+use common::save_sync::SaveSync;
+use common::update;
 use common::wifi::WiFiSettings;
-use enum_map::EnumMap;
 use futures::future::join3;
 use log::{debug, error, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 
 use common::database::Database;
+use common::datetime::DateTimeSettings;
 use common::game_info::GameInfo;
-use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::platform::{DefaultPlatform, InputProcessor, KeyEvent, Platform};
+
+use crate::api::{self, PendingRequest};
+use crate::file_server::FileServer;
+use crate::hooks::{Hook, HookEvent, Hooks};
 
 #[cfg(unix)]
 use {
@@ -42,11 +59,28 @@ pub struct AlliumDState {
 #[derive(Debug)]
 pub struct AlliumD<P: Platform> {
     platform: P,
+    battery: P::Battery,
     main: Child,
     menu: Option<Child>,
-    keys: EnumMap<Key, bool>,
-    is_menu_pressed_alone: bool,
+    hotkeys: Hotkeys,
+    /// Modification time of the hotkeys config when it was loaded.
+    hotkeys_modified: Option<SystemTime>,
+    /// Processes raw key events for the hotkey matcher. Buttons aren't swapped, since hotkeys
+    /// are physical.
+    input: InputProcessor,
+    hotkey_matcher: HotkeyMatcher,
     is_terminating: bool,
+    /// When the device went to sleep, while it's asleep.
+    suspended_at: Option<DateTime<Utc>>,
+    /// Whether the kid mode play time warning has been shown for the current game.
+    is_kid_mode_warned: bool,
+    file_server: Option<FileServer>,
+    /// Library rescans requested through the file server.
+    rescan_tx: mpsc::Sender<()>,
+    rescan_rx: mpsc::Receiver<()>,
+    hooks: Hooks,
+    /// Whether the low battery hooks have been run since the battery was last charging.
+    is_battery_low: bool,
     state: AlliumDState,
 }
 
@@ -100,15 +134,24 @@ impl AlliumDState {
 fn spawn_main() -> Result<Child> {
     #[cfg(feature = "miyoo")]
     return Ok(match GameInfo::load()? {
+        Some(game_info)
+            if game_info.profile.is_some()
+                && game_info.profile != Some(common::profile::Profiles::load()?.active) =>
+        {
+            warn!("game was launched from another profile, not resuming");
+            GameInfo::delete()?;
+            Command::new(ALLIUM_LAUNCHER.as_path())
+        }
         Some(mut game_info) => {
             debug!("found game info, resuming game");
+            // Netplay sessions don't survive a restart
+            game_info.netplay = None;
             game_info.start_time = Utc::now();
             game_info.save()?;
             game_info.command().into()
         }
         None => {
             debug!("no game info found, launching launcher");
-            use common::constants::ALLIUM_LAUNCHER;
             Command::new(ALLIUM_LAUNCHER.as_path())
         }
     }
@@ -124,16 +167,31 @@ fn spawn_main() -> Result<Child> {
 impl AlliumD<DefaultPlatform> {
     pub fn new() -> Result<AlliumD<DefaultPlatform>> {
         let platform = DefaultPlatform::new()?;
+        let battery = platform.battery()?;
         let state = AlliumDState::load()?;
         let main = spawn_main()?;
+        let (rescan_tx, rescan_rx) = mpsc::channel(1);
 
         Ok(AlliumD {
             platform,
+            battery,
             main,
             menu: None,
-            keys: EnumMap::default(),
-            is_menu_pressed_alone: false,
+            hotkeys_modified: Hotkeys::modified(),
+            hotkeys: Hotkeys::load().unwrap_or_else(|e| {
+                error!("failed to load hotkeys: {}", e);
+                Hotkeys::default()
+            }),
+            input: InputProcessor::load(),
+            hotkey_matcher: HotkeyMatcher::default(),
             is_terminating: false,
+            suspended_at: None,
+            is_kid_mode_warned: false,
+            file_server: None,
+            rescan_tx,
+            rescan_rx,
+            hooks: Hooks::default(),
+            is_battery_low: false,
             state,
         })
     }
@@ -150,13 +208,29 @@ impl AlliumD<DefaultPlatform> {
             WiFiSettings::load()?.init()?;
         }
 
+        self.hooks.run(Hook::new(HookEvent::Boot));
+        // spawn_main resumes the game that was running when the device was turned off
+        self.run_game_start_hooks()?;
+
         #[cfg(unix)]
         {
             let mut sigint = tokio::signal::unix::signal(SignalKind::interrupt())?;
             let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())?;
 
             let mut battery_interval = tokio::time::interval(BATTERY_UPDATE_INTERVAL);
-            let mut battery = self.platform.battery()?;
+
+            let mut kid_mode_interval = tokio::time::interval(KID_MODE_CHECK_INTERVAL);
+
+            if DefaultPlatform::has_wifi() {
+                if let Err(e) = self.update_file_server() {
+                    error!("failed to start file server: {}", e);
+                }
+            }
+
+            let (api_tx, mut api_rx) = mpsc::channel::<PendingRequest>(8);
+            if let Err(e) = api::start(api_tx) {
+                error!("failed to start API: {}", e);
+            }
 
             loop {
                 let menu_terminated = match self.menu.as_mut() {
@@ -165,15 +239,43 @@ impl AlliumD<DefaultPlatform> {
                 };
 
                 tokio::select! {
-                    key_event = self.platform.poll() => {
+                    key_event = self.input.poll(&mut self.platform) => {
                         self.handle_key_event(key_event).await?;
                     }
                     _ = self.main.wait() => {
                         if !self.is_terminating {
                             info!("main process terminated, recording play time");
-                            self.update_play_time()?;
-                            GameInfo::delete()?;
-                            self.main = spawn_main()?;
+                            let saves = self.update_play_time()?;
+                            self.is_kid_mode_warned = false;
+                            let game_info = GameInfo::load()?;
+                            if let Some(game_info) = &game_info {
+                                self.hooks.run(Hook::new(HookEvent::GameEnd).with_game(game_info.into()));
+                            }
+                            if let Some(core) = game_info.as_ref().and_then(|g| g.core()) {
+                                if let Err(e) = CoreOptions::apply_pending(core) {
+                                    warn!("failed to apply pending core options: {}", e);
+                                }
+                            }
+                            self.main = match game_info {
+                                Some(mut game_info) if game_info.relaunch => {
+                                    // The game reads its saves as it starts, so they have to be
+                                    // backed up and synced first
+                                    if let Some(saves) = saves {
+                                        saves.await.ok();
+                                    }
+                                    info!("relaunching game");
+                                    game_info.relaunch = false;
+                                    game_info.start_time = Utc::now();
+                                    game_info.save()?;
+                                    let main = Command::from(game_info.command()).spawn()?;
+                                    self.run_game_start_hooks()?;
+                                    main
+                                }
+                                _ => {
+                                    GameInfo::delete()?;
+                                    spawn_main()?
+                                }
+                            };
                         }
                     }
                     _ = menu_terminated => {
@@ -185,14 +287,27 @@ impl AlliumD<DefaultPlatform> {
                     _ = sigint.recv() => self.handle_quit().await?,
                     _ = sigterm.recv() => self.handle_quit().await?,
                     _ = battery_interval.tick() => {
-                        if let Err(e) = battery.update() {
+                        if let Err(e) = self.battery.update() {
                             error!("failed to update battery: {}", e);
                         }
-                        if battery.percentage() <= BATTERY_SHUTDOWN_THRESHOLD && !battery.charging() {
+                        if self.battery.percentage() <= BATTERY_SHUTDOWN_THRESHOLD && !self.battery.charging() {
                             warn!("battery is low, shutting down");
                             self.handle_quit().await?;
                         }
+                        let is_battery_low = self.battery.percentage() <= BATTERY_LOW_THRESHOLD && !self.battery.charging();
+                        if is_battery_low && !self.is_battery_low {
+                            info!("battery is low, running hooks");
+                            self.hooks.run(Hook::new(HookEvent::LowBattery).with_battery(self.battery_status()));
+                        }
+                        self.is_battery_low = is_battery_low;
                     }
+                    _ = kid_mode_interval.tick() => {
+                        if let Err(e) = self.check_kid_mode().await {
+                            error!("failed to check kid mode play time: {}", e);
+                        }
+                    }
+                    Some(()) = self.rescan_rx.recv() => self.rescan_library().await?,
+                    Some((request, reply)) = api_rx.recv() => self.handle_request(request, reply).await?,
                 }
             }
         }
@@ -200,7 +315,7 @@ impl AlliumD<DefaultPlatform> {
         #[cfg(not(unix))]
         loop {
             tokio::select! {
-                key_event = self.platform.poll() => {
+                key_event = self.input.poll(&mut self.platform) => {
                     self.handle_key_event(key_event).await?;
                 }
             }
@@ -214,131 +329,199 @@ impl AlliumD<DefaultPlatform> {
             self.main.id(),
             self.is_ingame()
         );
-        match key_event {
-            KeyEvent::Pressed(Key::Menu) => {
-                self.is_menu_pressed_alone = true;
-            }
-            KeyEvent::Pressed(_) => {
-                self.is_menu_pressed_alone = false;
-            }
-            KeyEvent::Released(_) | KeyEvent::Autorepeat(_) => {}
+        if matches!(key_event, KeyEvent::Pressed(_))
+            && !self.hotkey_matcher.held().values().any(|held| *held)
+        {
+            self.reload_hotkeys();
         }
-        match key_event {
-            KeyEvent::Pressed(key) => {
-                self.keys[key] = true;
-            }
-            KeyEvent::Released(key) => {
-                self.keys[key] = false;
-            }
-            KeyEvent::Autorepeat(_) => {}
-        }
-        match key_event {
-            KeyEvent::Pressed(Key::L2) | KeyEvent::Autorepeat(Key::L2) => {
-                if self.keys[Key::Start] {
-                    self.add_brightness(-5)?;
-                } else if self.keys[Key::Select] {
-                    self.add_volume(-1)?
+
+        let Some(action) = self.hotkey_matcher.handle(&self.hotkeys, key_event) else {
+            return Ok(());
+        };
+        debug!("hotkey: {:?}", action);
+        // While asleep, only waking up and quitting are allowed, so that buttons pressed in a
+        // pocket do nothing
+        if self.suspended_at.is_some()
+            && !matches!(action, HotkeyAction::Suspend | HotkeyAction::Quit)
+        {
+            return Ok(());
+        }
+        match action {
+            HotkeyAction::VolumeUp => self.add_volume(1)?,
+            HotkeyAction::VolumeDown => self.add_volume(-1)?,
+            HotkeyAction::BrightnessUp => self.add_brightness(5)?,
+            HotkeyAction::BrightnessDown => self.add_brightness(-5)?,
+            HotkeyAction::Screenshot => self.screenshot().await?,
+            HotkeyAction::Menu => self.toggle_menu().await?,
+            HotkeyAction::Suspend => {
+                if self.suspended_at.is_some() {
+                    self.resume()?;
+                } else {
+                    self.suspend()?;
                 }
             }
-            KeyEvent::Pressed(Key::R2) | KeyEvent::Autorepeat(Key::R2) => {
-                if self.keys[Key::Start] {
-                    self.add_brightness(5)?;
-                } else if self.keys[Key::Select] {
-                    self.add_volume(1)?
+            HotkeyAction::Quit => self.handle_quit().await?,
+            HotkeyAction::QuickSave | HotkeyAction::QuickLoad | HotkeyAction::FastForward => {
+                if self.menu.is_none() && self.is_ingame() {
+                    let is_repeat = matches!(key_event, KeyEvent::Autorepeat(_));
+                    self.handle_ingame_hotkey(action, is_repeat).await?;
                 }
             }
-            KeyEvent::Pressed(Key::VolDown) | KeyEvent::Autorepeat(Key::VolDown) => {
-                if self.keys[Key::Menu] {
-                    self.add_brightness(-5)?;
-                } else {
-                    self.add_volume(-1)?
-                }
+        }
+
+        Ok(())
+    }
+
+    /// Reloads the hotkeys if the config changed since they were loaded.
+    fn reload_hotkeys(&mut self) {
+        let modified = Hotkeys::modified();
+        if modified == self.hotkeys_modified {
+            return;
+        }
+        self.hotkeys_modified = modified;
+        match Hotkeys::load() {
+            Ok(hotkeys) => {
+                info!("reloaded hotkeys");
+                self.hotkeys = hotkeys;
             }
-            KeyEvent::Pressed(Key::VolUp) | KeyEvent::Autorepeat(Key::VolUp) => {
-                if self.keys[Key::Menu] {
-                    self.add_brightness(5)?;
-                } else {
-                    self.add_volume(1)?
-                }
+            Err(e) => error!("failed to reload hotkeys: {}", e),
+        }
+    }
+
+    async fn screenshot(&self) -> Result<()> {
+        let game_info = GameInfo::load()?;
+        let name = match game_info.as_ref() {
+            Some(game_info) => game_info.name.as_str(),
+            None => "Allium",
+        };
+        let file_name = format!(
+            "{}-{}.png",
+            DateTimeSettings::load()?.format_file_name(&Utc::now()),
+            name,
+        );
+        Command::new("screenshot")
+            .arg(ALLIUM_SD_ROOT.join("Screenshots").join(file_name))
+            .spawn()?
+            .wait()
+            .await?;
+        Ok(())
+    }
+
+    /// Opens the in-game menu, or closes it if it is open.
+    async fn toggle_menu(&mut self) -> Result<()> {
+        if !self.is_ingame() {
+            return Ok(());
+        }
+        if let Some(game_info) = GameInfo::load()? {
+            if let Some(menu) = &mut self.menu {
+                terminate(menu).await?;
+            } else if game_info.has_menu {
+                // TODO: combine these into one command?
+                let (max_disk_slots, disk_slot, state_slot) = join3(
+                    RetroArchCommand::GetDiskCount.send_recv(),
+                    RetroArchCommand::GetDiskSlot.send_recv(),
+                    RetroArchCommand::GetStateSlot.send_recv(),
+                )
+                .await;
+
+                let max_disk_slots = max_disk_slots?;
+                let max_disk_slots = max_disk_slots
+                    .as_ref()
+                    .and_then(|s| s.split_ascii_whitespace().nth(1))
+                    .unwrap_or("0");
+
+                let disk_slot = disk_slot?;
+                let disk_slot = disk_slot
+                    .as_ref()
+                    .and_then(|s| s.split_ascii_whitespace().nth(1))
+                    .unwrap_or("0");
+
+                let state_slot = state_slot?;
+                let state_slot = state_slot
+                    .as_ref()
+                    .and_then(|s| s.split_ascii_whitespace().nth(1))
+                    .unwrap_or("0");
+
+                #[cfg(unix)]
+                signal(&self.main, Signal::SIGSTOP)?;
+                self.menu = Some(
+                    Command::new(ALLIUM_MENU.as_path())
+                        .args([disk_slot, max_disk_slots, state_slot])
+                        .spawn()?,
+                );
+                self.hooks
+                    .run(Hook::new(HookEvent::MenuOpen).with_game((&game_info).into()));
             }
-            KeyEvent::Released(Key::Power) => {
-                if self.keys[Key::Menu] {
-                    let game_info = GameInfo::load()?;
-                    let name = match game_info.as_ref() {
-                        Some(game_info) => game_info.name.as_str(),
-                        None => "Allium",
-                    };
-                    let file_name = format!(
-                        "{}-{}.png",
-                        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"),
-                        name,
-                    );
-                    Command::new("screenshot")
-                        .arg(ALLIUM_SD_ROOT.join("Screenshots").join(file_name))
-                        .spawn()?.wait().await?;
-                } else {
-                    // TODO: suspend
-                }
+        }
+        Ok(())
+    }
+
+    /// Puts the device to sleep: the running processes are paused and the screen is turned off.
+    fn suspend(&mut self) -> Result<()> {
+        if self.suspended_at.is_some() {
+            return Ok(());
+        }
+        info!("suspending");
+        #[cfg(unix)]
+        {
+            if let Some(menu) = &self.menu {
+                signal(menu, Signal::SIGSTOP)?;
             }
-            KeyEvent::Autorepeat(Key::Power) => {
-                if !self.keys[Key::Menu] {
-                    self.handle_quit().await?;
-                }
+            signal(&self.main, Signal::SIGSTOP)?;
+        }
+        self.platform.suspend()?;
+        self.suspended_at = Some(Utc::now());
+        self.run_game_hooks(HookEvent::Suspend)
+    }
+
+    /// Wakes the device up. Time spent asleep doesn't count as play time.
+    fn resume(&mut self) -> Result<()> {
+        let Some(suspended_at) = self.suspended_at.take() else {
+            return Ok(());
+        };
+        info!("resuming");
+        self.platform.unsuspend()?;
+        if let Some(mut game_info) = GameInfo::load()? {
+            game_info.start_time += Utc::now() - suspended_at;
+            game_info.save()?;
+        }
+        #[cfg(unix)]
+        match &self.menu {
+            // The game stays paused behind the menu
+            Some(menu) => signal(menu, Signal::SIGCONT)?,
+            None => signal(&self.main, Signal::SIGCONT)?,
+        }
+        self.run_game_hooks(HookEvent::Resume)
+    }
+
+    /// Runs a hotkey that controls the running RetroArch game. Repeats keep holding fast-forward
+    /// without showing the message again.
+    async fn handle_ingame_hotkey(&mut self, action: HotkeyAction, is_repeat: bool) -> Result<()> {
+        if !GameInfo::load()?.is_some_and(|game_info| game_info.has_menu) {
+            return Ok(());
+        }
+
+        let message = match action {
+            HotkeyAction::QuickSave => {
+                RetroArchCommand::SaveState.send().await?;
+                "hotkey-quick-save"
             }
-            KeyEvent::Released(Key::Menu) => {
-                if self.is_menu_pressed_alone {
-                    if self.is_ingame()
-                        && self
-                            .keys
-                            .iter()
-                            .all(|(k, pressed)| k == Key::Menu || !pressed)
-                    {
-                        if let Some(game_info) = GameInfo::load()? {
-                            if let Some(menu) = &mut self.menu {
-                                terminate(menu).await?;
-                            } else if game_info.has_menu {
-                                // TODO: combine these into one command?
-                                let (max_disk_slots, disk_slot, state_slot) = join3(
-                                    RetroArchCommand::GetDiskCount.send_recv(),
-                                    RetroArchCommand::GetDiskSlot.send_recv(),
-                                    RetroArchCommand::GetStateSlot.send_recv(),
-                                )
-                                .await;
-
-                                let max_disk_slots = max_disk_slots?;
-                                let max_disk_slots = max_disk_slots
-                                    .as_ref()
-                                    .and_then(|s| s.split_ascii_whitespace().nth(1))
-                                    .unwrap_or("0");
-
-                                let disk_slot = disk_slot?;
-                                let disk_slot = disk_slot
-                                    .as_ref()
-                                    .and_then(|s| s.split_ascii_whitespace().nth(1))
-                                    .unwrap_or("0");
-
-                                let state_slot = state_slot?;
-                                let state_slot = state_slot
-                                    .as_ref()
-                                    .and_then(|s| s.split_ascii_whitespace().nth(1))
-                                    .unwrap_or("0");
-
-                                #[cfg(unix)]
-                                signal(&self.main, Signal::SIGSTOP)?;
-                                self.menu = Some(
-                                    Command::new(ALLIUM_MENU.as_path())
-                                        .args([disk_slot, max_disk_slots, state_slot])
-                                        .spawn()?,
-                                );
-                            }
-                        }
-                    }
-                    self.is_menu_pressed_alone = false;
+            HotkeyAction::QuickLoad => {
+                RetroArchCommand::LoadState.send().await?;
+                "hotkey-quick-load"
+            }
+            HotkeyAction::FastForward => {
+                RetroArchCommand::FastForwardHold.send().await?;
+                if is_repeat {
+                    return Ok(());
                 }
+                "hotkey-fast-forward"
             }
-            _ => {}
-        }
+            _ => return Ok(()),
+        };
 
+        let locale = Locale::new(&LocaleSettings::load()?.lang);
+        RetroArchCommand::ShowMsg(locale.t(message)).send().await?;
         Ok(())
     }
 
@@ -347,14 +530,20 @@ impl AlliumD<DefaultPlatform> {
         if self.is_terminating {
             return Ok(());
         }
+        // Paused processes can't save before they're terminated
+        self.resume()?;
 
         debug!("terminating, saving state");
 
         self.state.time = Utc::now();
         self.state.save()?;
 
+        let mut game_end = None;
+        let mut saves = None;
         if self.is_ingame() {
-            self.update_play_time()?;
+            saves = self.update_play_time()?;
+            game_end = GameInfo::load()?
+                .map(|game_info| Hook::new(HookEvent::GameEnd).with_game((&game_info).into()));
 
             if let Some(menu) = self.menu.as_mut() {
                 #[cfg(unix)]
@@ -365,16 +554,30 @@ impl AlliumD<DefaultPlatform> {
             terminate(&mut self.main).await?;
         }
 
+        // Let the save backup and sync finish so that powering off doesn't cut them short
+        if let Some(saves) = saves {
+            saves.await.ok();
+        }
+
+        // Unlike other hooks, these are waited for so that they finish before powering off. They
+        // are still limited by the hook timeout.
+        if let Some(hook) = game_end {
+            self.hooks.run(hook).await.ok();
+        }
+        self.hooks.run(Hook::new(HookEvent::Shutdown)).await.ok();
+
         self.is_terminating = true;
         self.platform.shutdown()?;
 
         Ok(())
     }
 
+    /// Records the current game's play time, then backs up and syncs its saves in the
+    /// background. Returns the background task, if any.
     #[allow(unused)]
-    fn update_play_time(&self) -> Result<()> {
+    fn update_play_time(&self) -> Result<Option<JoinHandle<()>>> {
         if !self.is_ingame() {
-            return Ok(());
+            return Ok(None);
         }
 
         let file = File::open(ALLIUM_GAME_INFO.as_path())?;
@@ -383,31 +586,293 @@ impl AlliumD<DefaultPlatform> {
         // As a sanity check, don't add play time if the game was played for more than 24 hours
         if game_info.play_time() > Duration::hours(24) {
             warn!("play time is too long, not adding to database");
-            return Ok(());
+            return Ok(None);
         }
 
         let database = Database::new()?;
         database.add_play_time(game_info.path.as_path(), game_info.play_time());
+        database.add_play_session(
+            game_info.path.as_path(),
+            game_info.start_time,
+            game_info.play_time(),
+        )?;
+
+        let save_sync = SaveSync::load()?;
+        let path = game_info.path.clone();
+        Ok(Some(tokio::spawn(async move {
+            // The backup is taken before syncing, which may replace the local saves
+            let backup_path = path.clone();
+            match tokio::task::spawn_blocking(move || SaveBackup::create(&backup_path)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("failed to back up save files: {}", e),
+                Err(e) => error!("save backup task failed: {}", e),
+            }
 
+            if let Some(save_sync) = save_sync {
+                match save_sync.sync_game(&path).await {
+                    Ok(report) if !report.conflicts.is_empty() => {
+                        warn!("save sync has conflicts, will prompt on next launch");
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("failed to sync saves: {}", e),
+                }
+            }
+        })))
+    }
+
+    /// Runs the game start hooks for the current game, if any. Called wherever a game is
+    /// launched: at boot, when relaunching, and when the launcher is about to exec one.
+    fn run_game_start_hooks(&self) -> Result<()> {
+        if let Some(game_info) = GameInfo::load()? {
+            self.hooks
+                .run(Hook::new(HookEvent::GameStart).with_game((&game_info).into()));
+        }
+        Ok(())
+    }
+
+    /// Runs the hooks for an event, with the current game if there is one.
+    fn run_game_hooks(&self, event: HookEvent) -> Result<()> {
+        let mut hook = Hook::new(event);
+        if let Some(game_info) = GameInfo::load()? {
+            hook = hook.with_game((&game_info).into());
+        }
+        self.hooks.run(hook);
+        Ok(())
+    }
+
+    /// Warns when the kid mode daily play time limit is almost reached, and quits the game once
+    /// it has been reached.
+    #[cfg(unix)]
+    async fn check_kid_mode(&mut self) -> Result<()> {
+        if !self.is_ingame() || self.suspended_at.is_some() {
+            return Ok(());
+        }
+
+        let settings = KidModeSettings::load()?;
+        let Some(game_info) = GameInfo::load()? else {
+            return Ok(());
+        };
+        let Some(remaining) =
+            settings.play_time_remaining(&Database::new()?, game_info.play_time())?
+        else {
+            return Ok(());
+        };
+
+        if remaining <= Duration::zero() {
+            info!("kid mode play time limit reached, quitting game");
+            self.quit_game().await?;
+        } else if !self.is_kid_mode_warned
+            && remaining.to_std().is_ok_and(|r| r <= KID_MODE_WARNING_TIME)
+        {
+            info!("kid mode play time limit almost reached, warning");
+            self.is_kid_mode_warned = true;
+            if game_info.has_menu {
+                let locale = Locale::new(&LocaleSettings::load()?.lang);
+                RetroArchCommand::ShowMsg(locale.t("kid-mode-time-warning"))
+                    .send()
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Quits the running game. The event loop then records its play time and returns to the
+    /// launcher. RetroArch is asked to quit first, so that it writes its save file.
+    #[cfg(unix)]
+    async fn quit_game(&mut self) -> Result<()> {
+        self.resume()?;
+        if let Some(menu) = self.menu.as_mut() {
+            signal(&self.main, Signal::SIGCONT)?;
+            terminate(menu).await?;
+        }
+        if GameInfo::load()?.is_some_and(|game_info| game_info.has_menu) {
+            RetroArchCommand::Quit.send().await?;
+            if tokio::time::timeout(RETROARCH_QUIT_TIMEOUT, self.main.wait())
+                .await
+                .is_ok()
+            {
+                return Ok(());
+            }
+            warn!("RetroArch didn't quit, terminating it");
+        }
+        terminate(&mut self.main).await
+    }
+
+    /// Marks the running game to be launched again once it exits, and quits RetroArch. If the
+    /// menu is open, the game quits once the menu closes.
+    #[cfg(unix)]
+    async fn relaunch(&mut self, netplay: Option<Netplay>) -> Result<()> {
+        let Some(mut game_info) = GameInfo::load()? else {
+            bail!("No game is running.");
+        };
+        if !game_info.has_menu {
+            bail!("Only RetroArch games can be relaunched.");
+        }
+        self.resume()?;
+        info!("relaunching game after it quits");
+        game_info.relaunch = true;
+        if netplay.is_some() {
+            game_info.netplay = netplay;
+        }
+        game_info.save()?;
+        RetroArchCommand::Quit.send().await
+    }
+
+    /// Answers an API request. Shutting down doesn't return, so it's answered beforehand.
+    #[cfg(unix)]
+    async fn handle_request(
+        &mut self,
+        request: AlliumDRequest,
+        reply: oneshot::Sender<AlliumDResponse>,
+    ) -> Result<()> {
+        if request == AlliumDRequest::Shutdown {
+            reply.send(AlliumDResponse::Ok).ok();
+            return self.handle_quit().await;
+        }
+
+        let response = match self.run_request(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("API request failed: {}", e);
+                AlliumDResponse::Error {
+                    message: e.to_string(),
+                }
+            }
+        };
+        // The client may have disconnected already
+        reply.send(response).ok();
         Ok(())
     }
 
+    #[cfg(unix)]
+    async fn run_request(&mut self, request: AlliumDRequest) -> Result<AlliumDResponse> {
+        match request {
+            AlliumDRequest::Status => return Ok(AlliumDResponse::Status(self.status()?)),
+            AlliumDRequest::SetVolume { volume } => self.set_volume(volume)?,
+            AlliumDRequest::SetBrightness { brightness } => self.set_brightness(brightness)?,
+            AlliumDRequest::Launch { path } => self.launch(path).await?,
+            AlliumDRequest::Quit => {
+                if !self.is_ingame() {
+                    bail!("No game is running.");
+                }
+                info!("quitting game");
+                self.quit_game().await?;
+            }
+            AlliumDRequest::Relaunch { netplay } => self.relaunch(netplay).await?,
+            AlliumDRequest::Menu => {
+                if !self.is_ingame() {
+                    bail!("No game is running.");
+                }
+                self.resume()?;
+                if self.menu.is_none() {
+                    self.toggle_menu().await?;
+                }
+            }
+            AlliumDRequest::Screenshot => self.screenshot().await?,
+            AlliumDRequest::Suspend => self.suspend()?,
+            AlliumDRequest::Resume => self.resume()?,
+            AlliumDRequest::LauncherStarted => update::confirm()?,
+            AlliumDRequest::GameStarted => self.run_game_start_hooks()?,
+            AlliumDRequest::ReloadWiFiSettings => {
+                if DefaultPlatform::has_wifi() {
+                    self.update_file_server()?;
+                }
+            }
+            AlliumDRequest::Shutdown => self.handle_quit().await?,
+        }
+        Ok(AlliumDResponse::Ok)
+    }
+
+    fn status(&self) -> Result<AlliumDStatus> {
+        Ok(AlliumDStatus {
+            game: GameInfo::load()?.as_ref().map(GameStatus::from),
+            battery: self.battery_status(),
+            volume: self.state.volume,
+            brightness: self.state.brightness,
+        })
+    }
+
+    fn battery_status(&self) -> BatteryStatus {
+        BatteryStatus {
+            percentage: self.battery.percentage(),
+            charging: self.battery.charging(),
+        }
+    }
+
+    /// Launches a game by restarting the launcher with it, so that it's launched the same way as
+    /// from the library.
+    async fn launch(&mut self, path: PathBuf) -> Result<()> {
+        if self.is_ingame() {
+            bail!("A game is already running.");
+        }
+        if !path.exists() {
+            bail!("{} does not exist.", path.display());
+        }
+        info!("launching {}", path.display());
+        self.resume()?;
+        terminate(&mut self.main).await?;
+        self.main = Command::new(ALLIUM_LAUNCHER.as_path())
+            .arg("--launch")
+            .arg(path)
+            .spawn()?;
+        Ok(())
+    }
+
+    /// Starts or stops the file server to match the Wi-Fi settings. A new token restarts it.
+    #[cfg(unix)]
+    fn update_file_server(&mut self) -> Result<()> {
+        let settings = WiFiSettings::load()?;
+        let enabled = settings.wifi && settings.file_server;
+        if self
+            .file_server
+            .as_ref()
+            .is_some_and(|server| !enabled || server.token() != settings.file_server_token)
+        {
+            self.file_server = None;
+        }
+        if enabled && self.file_server.is_none() {
+            self.file_server = Some(FileServer::start(
+                settings.file_server_token,
+                self.rescan_tx.clone(),
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Restarts the launcher so that it reloads the library. Ignored while in game.
+    #[cfg(unix)]
+    async fn rescan_library(&mut self) -> Result<()> {
+        if self.is_ingame() || self.menu.is_some() {
+            info!("in game, not rescanning library");
+            return Ok(());
+        }
+        info!("rescanning library, restarting launcher");
+        terminate(&mut self.main).await
+    }
+
     fn is_ingame(&self) -> bool {
         Path::new(&*ALLIUM_GAME_INFO).exists()
     }
 
     fn add_volume(&mut self, add: i32) -> Result<()> {
         info!("adding volume: {}", add);
-        self.state.volume = (self.state.volume + add).clamp(0, 20);
-        self.platform.set_volume(self.state.volume)?;
-        Ok(())
+        self.set_volume(self.state.volume + add)
+    }
+
+    fn set_volume(&mut self, volume: i32) -> Result<()> {
+        self.state.volume = volume.clamp(0, 20);
+        self.platform.set_volume(self.state.volume)
     }
 
     fn add_brightness(&mut self, add: i8) -> Result<()> {
         info!("adding brightness: {}", add);
-        self.state.brightness = (self.state.brightness as i8 + add).clamp(0, 100) as u8;
-        self.platform.set_brightness(self.state.brightness)?;
-        Ok(())
+        self.set_brightness((self.state.brightness as i8 + add).clamp(0, 100) as u8)
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<()> {
+        self.state.brightness = brightness.min(100);
+        self.platform.set_brightness(self.state.brightness)
     }
 }
 
@@ -0,0 +1,77 @@
+use std::fs;
+
+use anyhow::Result;
+use common::alliumd::{AlliumDRequest, AlliumDResponse};
+use common::constants::ALLIUMD_SOCKET;
+use log::{debug, error, info};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+/// A request waiting for the event loop, which answers it on the sender.
+pub type PendingRequest = (AlliumDRequest, oneshot::Sender<AlliumDResponse>);
+
+/// Starts serving the JSON API on alliumd's Unix socket. Requests are passed on to `requests`
+/// one at a time, so that they're handled by the event loop like key events.
+pub fn start(requests: mpsc::Sender<PendingRequest>) -> Result<()> {
+    // A socket left behind by a previous run would make binding fail
+    if ALLIUMD_SOCKET.exists() {
+        fs::remove_file(ALLIUMD_SOCKET.as_path())?;
+    }
+    let listener = UnixListener::bind(ALLIUMD_SOCKET.as_path())?;
+    info!("serving API at {}", ALLIUMD_SOCKET.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle(stream, requests.clone()));
+                }
+                Err(e) => error!("failed to accept API connection: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Answers each line of the connection with a response line until it's closed.
+async fn handle(stream: UnixStream, requests: mpsc::Sender<PendingRequest>) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("failed to read API request: {}", e);
+                return;
+            }
+        };
+
+        let response = match serde_json::from_str::<AlliumDRequest>(&line) {
+            Ok(request) => {
+                debug!("API request: {:?}", request);
+                let (tx, rx) = oneshot::channel();
+                if requests.send((request, tx)).await.is_err() {
+                    return;
+                }
+                rx.await.unwrap_or_else(|_| AlliumDResponse::Error {
+                    message: "Request was dropped.".to_string(),
+                })
+            }
+            Err(e) => AlliumDResponse::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+
+        let Ok(mut json) = serde_json::to_string(&response) else {
+            return;
+        };
+        json.push('\n');
+        if let Err(e) = write.write_all(json.as_bytes()).await {
+            debug!("failed to write API response: {}", e);
+            return;
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use common::constants::{ALLIUM_SD_ROOT, FILE_SERVER_MAX_UPLOAD_SIZE, FILE_SERVER_PORT};
+use futures::StreamExt;
+use hyper::body::HttpBody;
+use hyper::header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use log::{error, info, warn};
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio_util::io::ReaderStream;
+
+/// Page served at `/`. It only talks to the API, which requires the token.
+const INDEX: &str = include_str!("file_server.html");
+
+/// Prefix of the file API. The rest of the path is relative to the SD card.
+const FILES_PREFIX: &str = "/api/files";
+
+/// Requests are handled one or two at a time so that transfers don't slow down a running game.
+const MAX_CONCURRENT_REQUESTS: usize = 2;
+
+/// Size of the chunks files are read in when downloading.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// HTTP server for browsing, uploading and downloading files on the SD card over Wi-Fi.
+/// It runs on alliumd's runtime and shuts down when dropped.
+#[derive(Debug)]
+pub struct FileServer {
+    token: String,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+struct State {
+    token: String,
+    rescan: mpsc::Sender<()>,
+    permits: Arc<Semaphore>,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+impl FileServer {
+    /// Starts the server. A message is sent on `rescan` when a library rescan is requested.
+    pub fn start(token: String, rescan: mpsc::Sender<()>) -> Result<Self> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], FILE_SERVER_PORT));
+        let state = Arc::new(State {
+            token: token.clone(),
+            rescan,
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)),
+        });
+
+        let make_service = make_service_fn(move |_| {
+            let state = Arc::clone(&state);
+            let service = service_fn(move |req| handle(Arc::clone(&state), req));
+            async move { Ok::<_, Infallible>(service) }
+        });
+        let server = Server::try_bind(&addr)?.serve(make_service);
+
+        let (shutdown, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let server = server.with_graceful_shutdown(async {
+                rx.await.ok();
+            });
+            if let Err(e) = server.await {
+                error!("file server error: {}", e);
+            }
+        });
+
+        info!("file server listening on {}", addr);
+        Ok(Self {
+            token,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Drop for FileServer {
+    fn drop(&mut self) {
+        info!("stopping file server");
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+async fn handle(state: Arc<State>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    Ok(match route(&state, req).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("file server: {} {} failed: {}", method, path, e);
+            status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    })
+}
+
+async fn route(state: &State, req: Request<Body>) -> Result<Response<Body>> {
+    if req.method() == Method::GET && req.uri().path() == "/" {
+        return Ok(Response::builder()
+            .header(CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(INDEX.into())?);
+    }
+
+    if !is_authorized(&req, &state.token) {
+        return Ok(status(StatusCode::UNAUTHORIZED, "invalid token"));
+    }
+
+    let permit = Arc::clone(&state.permits).acquire_owned().await?;
+
+    if req.uri().path() == "/api/rescan" {
+        if req.method() != Method::POST {
+            return Ok(status(StatusCode::METHOD_NOT_ALLOWED, "use POST"));
+        }
+        info!("file server: library rescan requested");
+        state.rescan.send(()).await?;
+        return Ok(status(StatusCode::ACCEPTED, "rescanning"));
+    }
+
+    let Some(relative) = req.uri().path().strip_prefix(FILES_PREFIX) else {
+        return Ok(status(StatusCode::NOT_FOUND, "not found"));
+    };
+    let Some(path) = resolve(relative) else {
+        return Ok(status(StatusCode::BAD_REQUEST, "invalid path"));
+    };
+
+    match *req.method() {
+        Method::GET if path.is_dir() => list(&path).await,
+        Method::GET if path.is_file() => {
+            let file = File::open(&path).await?;
+            let len = file.metadata().await?.len();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().replace('"', ""))
+                .unwrap_or_default();
+            // The permit is held until the download finishes
+            let stream = ReaderStream::with_capacity(file, CHUNK_SIZE).map(move |chunk| {
+                let _ = &permit;
+                chunk
+            });
+            Ok(Response::builder()
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_LENGTH, len)
+                .header(
+                    CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", name),
+                )
+                .body(Body::wrap_stream(stream))?)
+        }
+        Method::GET => Ok(status(StatusCode::NOT_FOUND, "not found")),
+        Method::PUT => {
+            let len = req
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            if len.is_some_and(|len| len > FILE_SERVER_MAX_UPLOAD_SIZE) {
+                return Ok(status(StatusCode::PAYLOAD_TOO_LARGE, "file is too large"));
+            }
+            upload(&path, req.into_body()).await
+        }
+        _ => Ok(status(StatusCode::METHOD_NOT_ALLOWED, "use GET or PUT")),
+    }
+}
+
+/// Lists a directory as JSON, directories first.
+async fn list(path: &Path) -> Result<Response<Body>> {
+    let mut entries = Vec::new();
+    let mut dir = fs::read_dir(path).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        });
+    }
+    entries.sort_unstable_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&entries)?.into())?)
+}
+
+/// Streams the request body to a file, chunk by chunk. The file is written next to its
+/// destination and moved into place once complete, so an interrupted or oversized upload doesn't
+/// leave a partial file behind.
+async fn upload(path: &Path, mut body: Body) -> Result<Response<Body>> {
+    if path == ALLIUM_SD_ROOT.as_path() || path.is_dir() {
+        return Ok(status(StatusCode::BAD_REQUEST, "path is a directory"));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    let mut file = File::create(&partial).await?;
+    let mut written = 0;
+    while let Some(chunk) = body.data().await {
+        let result: Result<()> = match chunk {
+            Ok(chunk) => {
+                written += chunk.len() as u64;
+                if written > FILE_SERVER_MAX_UPLOAD_SIZE {
+                    drop(file);
+                    fs::remove_file(&partial).await.ok();
+                    return Ok(status(StatusCode::PAYLOAD_TOO_LARGE, "file is too large"));
+                }
+                file.write_all(&chunk).await.map_err(Into::into)
+            }
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            drop(file);
+            fs::remove_file(&partial).await.ok();
+            return Err(e);
+        }
+    }
+    file.flush().await?;
+    drop(file);
+    fs::rename(&partial, path).await?;
+
+    info!("file server: uploaded {:?}", path);
+    Ok(status(StatusCode::CREATED, "uploaded"))
+}
+
+/// Checks the token, given either as a bearer token or a `token` query parameter.
+fn is_authorized(req: &Request<Body>, token: &str) -> bool {
+    let header = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let query = req.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+    });
+    let matches = |given: Option<&str>| given.is_some_and(|given| constant_time_eq(given, token));
+    // Both are checked so that the time taken doesn't depend on which one was given
+    let (header, query) = (matches(header), matches(query));
+    !token.is_empty() && (header | query)
+}
+
+/// Compares strings in time that depends only on their lengths, so that the token can't be
+/// guessed one character at a time from response times.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Resolves a percent-encoded path relative to the SD card. Returns None if the path would
+/// escape the SD card.
+fn resolve(path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode_str(path).decode_utf8().ok()?;
+    let mut resolved = ALLIUM_SD_ROOT.clone();
+    for component in Path::new(decoded.as_ref()).components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved)
+}
+
+fn status(code: StatusCode, message: impl Into<String>) -> Response<Body> {
+    let mut response = Response::new(Body::from(message.into()));
+    *response.status_mut() = code;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("ABCD2345", "ABCD2345"));
+        assert!(!constant_time_eq("ABCD2345", "ABCD2346"));
+        assert!(!constant_time_eq("ABCD2345", "ABCD234"));
+        assert!(constant_time_eq("", ""));
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve(""), Some(ALLIUM_SD_ROOT.clone()));
+        assert_eq!(
+            resolve("/Roms/GBA/Pok%C3%A9mon%20Emerald.gba"),
+            Some(ALLIUM_SD_ROOT.join("Roms/GBA/Pokémon Emerald.gba"))
+        );
+        assert_eq!(resolve("/Roms/../../etc/passwd"), None);
+        assert_eq!(resolve("/Roms/%2E%2E/%2E%2E/etc"), None);
+    }
+}
@@ -0,0 +1,300 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use common::alliumd::{BatteryStatus, GameStatus};
+use common::constants::{ALLIUM_SCRIPTS_DIR, HOOK_TIMEOUT};
+use log::{debug, warn};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+/// Events that user scripts can hook into. Each has a directory of scripts named after it in
+/// `scripts/hooks`, e.g. `scripts/hooks/game-end/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    Boot,
+    GameStart,
+    GameEnd,
+    MenuOpen,
+    LowBattery,
+    Suspend,
+    Resume,
+    Shutdown,
+}
+
+impl HookEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Boot => "boot",
+            HookEvent::GameStart => "game-start",
+            HookEvent::GameEnd => "game-end",
+            HookEvent::MenuOpen => "menu-open",
+            HookEvent::LowBattery => "low-battery",
+            HookEvent::Suspend => "suspend",
+            HookEvent::Resume => "resume",
+            HookEvent::Shutdown => "shutdown",
+        }
+    }
+}
+
+/// An event and its details, passed to hooks as JSON on stdin and as environment variables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    /// The game for game, menu, suspend and resume events. At `game-end`, its play time is the
+    /// session's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game: Option<GameStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery: Option<BatteryStatus>,
+}
+
+impl Hook {
+    pub fn new(event: HookEvent) -> Self {
+        Self {
+            event,
+            game: None,
+            battery: None,
+        }
+    }
+
+    pub fn with_game(mut self, game: GameStatus) -> Self {
+        self.game = Some(game);
+        self
+    }
+
+    pub fn with_battery(mut self, battery: BatteryStatus) -> Self {
+        self.battery = Some(battery);
+        self
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![("ALLIUM_EVENT", self.event.name().to_string())];
+        if let Some(game) = &self.game {
+            env.push(("ALLIUM_GAME_NAME", game.name.clone()));
+            env.push(("ALLIUM_GAME_PATH", game.path.display().to_string()));
+            if let Some(core) = &game.core {
+                env.push(("ALLIUM_GAME_CORE", core.clone()));
+            }
+            env.push(("ALLIUM_PLAY_TIME", game.play_time.to_string()));
+        }
+        if let Some(battery) = &self.battery {
+            env.push(("ALLIUM_BATTERY_PERCENTAGE", battery.percentage.to_string()));
+            env.push((
+                "ALLIUM_BATTERY_CHARGING",
+                (battery.charging as u8).to_string(),
+            ));
+        }
+        env
+    }
+}
+
+/// Runs the executables in `scripts/hooks/<event>/` when events happen.
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    dir: PathBuf,
+    /// How long each script may run before it's killed.
+    timeout: Duration,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            dir: ALLIUM_SCRIPTS_DIR.join("hooks"),
+            timeout: HOOK_TIMEOUT,
+        }
+    }
+}
+
+impl Hooks {
+    /// Runs the event's scripts in the background, one at a time in order of their names, so
+    /// that they never hold up the event loop. The handle finishes once they've all exited or
+    /// been killed.
+    pub fn run(&self, hook: Hook) -> JoinHandle<()> {
+        let hooks = self.clone();
+        tokio::spawn(async move { hooks.run_scripts(&hook).await })
+    }
+
+    async fn run_scripts(&self, hook: &Hook) {
+        let scripts = match self.scripts(hook.event) {
+            Ok(scripts) => scripts,
+            Err(e) => {
+                warn!("failed to list {} hooks: {}", hook.event.name(), e);
+                return;
+            }
+        };
+        if scripts.is_empty() {
+            return;
+        }
+
+        let json = match serde_json::to_string(hook) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("failed to serialize {} hook: {}", hook.event.name(), e);
+                return;
+            }
+        };
+        for script in scripts {
+            debug!("running hook {}", script.display());
+            if let Err(e) = self.run_script(&script, hook, &json).await {
+                warn!("hook {} failed: {}", script.display(), e);
+            }
+        }
+    }
+
+    /// Lists the executable files in the event's directory, sorted by name.
+    fn scripts(&self, event: HookEvent) -> Result<Vec<PathBuf>> {
+        let dir = self.dir.join(event.name());
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut scripts = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 {
+                scripts.push(entry.path());
+            }
+        }
+        scripts.sort();
+        Ok(scripts)
+    }
+
+    async fn run_script(&self, script: &Path, hook: &Hook, json: &str) -> Result<()> {
+        let mut child = Command::new(script)
+            .envs(hook.env())
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let run = async {
+            if let Some(mut stdin) = child.stdin.take() {
+                // Scripts don't have to read their stdin, in which case the pipe may be closed
+                stdin.write_all(json.as_bytes()).await.ok();
+            }
+            child.wait().await
+        };
+        let result = tokio::time::timeout(self.timeout, run).await;
+        let status = match result {
+            Ok(status) => status?,
+            Err(_) => {
+                child.kill().await?;
+                bail!("timed out after {:?}", self.timeout);
+            }
+        };
+        if !status.success() {
+            bail!("exited with {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Instant;
+
+    use common::testing::temp_dir;
+
+    use super::*;
+
+    fn add_script(dir: &Path, event: HookEvent, name: &str, script: &str) {
+        let dir = dir.join(event.name());
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let dir = temp_dir("hooks-run");
+        let out = dir.join("out");
+        add_script(&dir, HookEvent::GameEnd, "10-fail.sh", "exit 1");
+        add_script(
+            &dir,
+            HookEvent::GameEnd,
+            "20-record.sh",
+            &format!(
+                "echo \"$ALLIUM_EVENT $ALLIUM_GAME_NAME $ALLIUM_PLAY_TIME\" > {0}/env\ncat > {0}/stdin",
+                out.display()
+            ),
+        );
+        add_script(
+            &dir,
+            HookEvent::GameStart,
+            "10-start.sh",
+            &format!("touch {}/start", out.display()),
+        );
+        // Not executable, so it isn't run
+        fs::write(dir.join("game-end/readme.txt"), "").unwrap();
+        fs::create_dir_all(&out).unwrap();
+
+        let hooks = Hooks {
+            dir: dir.clone(),
+            timeout: Duration::from_secs(5),
+        };
+        let hook = Hook::new(HookEvent::GameEnd).with_game(GameStatus {
+            name: "Pokemon".to_string(),
+            path: PathBuf::from("/mnt/SDCARD/Roms/GBA/Pokemon.gba"),
+            core: Some("mgba".to_string()),
+            play_time: 90,
+        });
+        hooks.run(hook.clone()).await.unwrap();
+
+        assert_eq!(
+            fs::read_to_string(out.join("env")).unwrap(),
+            "game-end Pokemon 90\n"
+        );
+        let stdin: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out.join("stdin")).unwrap()).unwrap();
+        assert_eq!(stdin, serde_json::to_value(&hook).unwrap());
+        assert_eq!(stdin["event"], "game-end");
+        assert!(!out.join("start").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let dir = temp_dir("hooks-timeout");
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        add_script(&dir, HookEvent::Boot, "10-sleep.sh", "exec sleep 30");
+        add_script(
+            &dir,
+            HookEvent::Boot,
+            "20-touch.sh",
+            &format!("touch {}/touched", out.display()),
+        );
+
+        let hooks = Hooks {
+            dir: dir.clone(),
+            timeout: Duration::from_millis(200),
+        };
+        let start = Instant::now();
+        hooks.run(Hook::new(HookEvent::Boot)).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(out.join("touched").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_missing_dir() {
+        let dir = temp_dir("hooks-missing");
+        let hooks = Hooks {
+            dir: dir.join("hooks"),
+            timeout: Duration::from_secs(1),
+        };
+        hooks.run(Hook::new(HookEvent::Shutdown)).await.unwrap();
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -2,6 +2,9 @@
 #![warn(rust_2018_idioms)]
 
 mod alliumd;
+mod api;
+mod file_server;
+mod hooks;
 
 use anyhow::Result;
 use simple_logger::SimpleLogger;
@@ -1,3 +1,6 @@
 #!/bin/sh
 DIR=/mnt/SDCARD/RetroArch
-HOME=/mnt/SDCARD/RetroArch exec "$DIR/retroarch" -v -L "$DIR/.retroarch/cores/$1_libretro.so" "$2"
+CORE="$1"
+ROM="$2"
+shift 2
+HOME=/mnt/SDCARD/RetroArch exec "$DIR/retroarch" -v --appendconfig "$(dirname "$0")/allium.cfg" -L "$DIR/.retroarch/cores/${CORE}_libretro.so" "$ROM" "$@"
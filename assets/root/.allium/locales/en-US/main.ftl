@@ -6,18 +6,88 @@ tab-settings = Settings
 
 recents-sort-currently-last-played = Sort: Last Played
 recents-sort-currently-most-played = Sort: Most Played
+recents-sort-currently-favorites = Sort: Favorites
 recents-launch = Launch
 recents-remove = Remove
 
+browser-rename = Rename
+browser-delete = Delete
+browser-delete-confirm = Confirm Delete
+browser-hide = Hide
+browser-unhide = Unhide
+browser-show-hidden = Show Hidden
+browser-hide-hidden = Hide Hidden
+browser-random-game = Random Game
+browser-random-game-here = Random Game Here
+browser-exit-kid-mode = Exit Kid Mode
+
+random-game-reroll = Re-roll
+random-game-prefer-unplayed-on = Unplayed: Preferred
+random-game-prefer-unplayed-off = Unplayed: Any
+random-game-loading = Finding games...
+random-game-empty = No games found
+
+game-details = Details
+game-details-console = Console
+game-details-core = Core
+game-details-path = Path
+game-details-size = Size
+game-details-play-count = Play Count
+game-details-play-time = Play Time
+game-details-play-time-value = {$hours}h {$minutes}m
+game-details-last-played = Last Played
+game-details-guide = Guide
+game-details-save-states = Save States
+game-details-save-backups = Backups
+game-details-yes = Yes
+game-details-no = No
+game-details-never = Never
+game-details-unknown = Unknown
+game-details-launch = Launch
+game-details-launch-with = Launch With
+game-details-core-options = Core Options
+game-details-video-settings = Video Settings
+game-details-favorite = Favorite
+game-details-unfavorite = Unfavorite
+
+save-backups-entry = {$time} ({$files} files)
+save-backups-empty = No backups yet
+save-backups-restore = Restore
+save-backups-restore-confirm = Confirm Restore
+save-backups-restored = Backup restored
+save-backups-restore-failed = Failed to restore backup
+save-backups-export = Export All
+save-backups-exported = Exported to {$path}
+save-backups-export-failed = Failed to export saves
+
+save-conflicts-title = Saves changed on this device and on the sync server
+save-conflicts-entry = {$file}: device {$local}, server {$remote}
+save-conflicts-keep-local = Keep Device
+save-conflicts-keep-remote = Keep Server
+save-conflicts-keep-newer = Keep Newer
+save-conflicts-newer = (newer)
+save-conflicts-failed = Failed to sync saves
+
 settings-wifi = Wi-Fi
 settings-wifi-wifi-enabled = Wi-Fi Enabled
 settings-wifi-ip-address = IP Address
-settings-wifi-wifi-network = Wi-Fi Network Name
-settings-wifi-wifi-password = Wi-Fi Password
 settings-wifi-ntp-enabled = NTP Enabled
 settings-wifi-telnet-enabled = Telnet Enabled
 settings-wifi-ftp-enabled = FTP Enabled
+settings-wifi-file-server-enabled = File Server Enabled
+settings-wifi-file-server-address = File Server Address
+settings-wifi-file-server-token = File Server Token
 settings-wifi-connecting= Connecting...
+settings-wifi-status = Status
+settings-wifi-status-connected = Connected to {$ssid}
+settings-wifi-status-online = Connected
+settings-wifi-add-network = Add Network...
+settings-wifi-signal = {$strength}%
+settings-wifi-signal-secured = {$strength}% (Secured)
+settings-wifi-scan = Scan
+settings-wifi-forget = Forget
+settings-wifi-forget-confirm = Confirm Forget
+settings-wifi-invalid-password = Passwords must be 8 to 63 characters and can't contain "
 
 settings-display = Display
 settings-display-luminance = Luminance
@@ -49,6 +119,32 @@ settings-language = Language
 settings-language-language = Language
 settings-language-restart-to-apply-changes = *Restart device to apply changes
 
+settings-profiles = Profiles
+settings-profiles-separate-theme = Separate Theme
+settings-profiles-separate-language = Separate Language
+settings-profiles-active = Active
+settings-profiles-new = New
+settings-profiles-delete = Delete
+settings-profiles-delete-confirm = Confirm Delete
+
+settings-kid-mode = Kid Mode
+settings-kid-mode-enabled = Kid Mode Enabled
+settings-kid-mode-pin = PIN
+settings-kid-mode-pin-set = Set
+settings-kid-mode-pin-not-set = Not Set
+settings-kid-mode-daily-limit = Daily Limit (Minutes)
+
+settings-date-time = Date & Time
+settings-date-time-date = Date
+settings-date-time-time = Time
+settings-date-time-timezone = Timezone
+settings-date-time-24-hour = 24-Hour Clock
+
+settings-controls = Controls
+settings-controls-swap-buttons = Swap A/B and X/Y
+settings-controls-record = Hold the new keys, then release them
+settings-controls-reset = Reset
+
 settings-files = Files
 
 settings-about = About
@@ -59,6 +155,16 @@ settings-about-operating-system-version = OS Version
 settings-about-kernel-version = Kernel Version
 settings-about-storage-used = Storage Used
 settings-about-unknown-value = Unknown
+settings-about-available-version = Available Version
+settings-about-check-for-updates = Check for Updates
+settings-about-install-update = Install
+settings-about-update-checking = Checking...
+settings-about-update-up-to-date = Up to date
+settings-about-update-installing = Installing...
+settings-about-update-staged = Restart to apply
+settings-about-update-failed = Update failed
+settings-about-rollback = Roll Back Update
+settings-about-rollback-confirm = Press A again to confirm
 
 # Menu
 ingame-menu-continue = Continue
@@ -70,6 +176,56 @@ ingame-menu-guide = Guide
 ingame-menu-quit = Quit
 ingame-menu-slot = Slot { $slot }
 ingame-menu-disk = Disk { $disk }
+ingame-menu-cheats = Cheats
+ingame-menu-core-options = Core Options
+ingame-menu-video-settings = Video Settings
+ingame-menu-shaders = Shaders & Overlays
+ingame-menu-netplay = Netplay
+netplay-host = Host Game
+netplay-join-by-ip = Join by IP...
+netplay-searching = Searching for games...
+netplay-no-hosts = No games found
+netplay-password = (Password)
+netplay-refresh = Refresh
+
+cheats-restart = Restart
+cheats-restart-note = Cheats apply after a restart
+
+core-options-title = Core Options
+core-options-scope = Save For
+core-options-scope-game = This Game
+core-options-scope-core = All Games
+core-options-empty = Play the game once to see its options
+core-options-restart = Restart
+core-options-restart-note = Changes apply after a restart
+
+shaders-scope = Save For
+shaders-scope-game = This Game
+shaders-scope-console = This Console
+shaders-scope-core = All Games
+shaders-shader = Shader
+shaders-overlay = Overlay
+shaders-off = Off
+shaders-restart = Restart
+shaders-restart-note = Changes apply after a restart
+
+video-settings-title = Video Settings
+video-settings-scope = Save For
+video-settings-scope-game = This Game
+video-settings-scope-console = This Console
+video-settings-scope-core = All Games
+video-settings-aspect-ratio = Aspect Ratio
+video-settings-aspect-ratio-core = Core Provided
+video-settings-aspect-ratio-full = Full Screen
+video-settings-aspect-ratio-4-3 = 4:3
+video-settings-aspect-ratio-16-9 = 16:9
+video-settings-aspect-ratio-3-2 = 3:2
+video-settings-aspect-ratio-square-pixel = Square Pixel
+video-settings-integer-scale = Integer Scaling
+video-settings-smooth = Bilinear Filtering
+video-settings-reset = Reset
+video-settings-restart = Restart
+video-settings-restart-note = Changes apply after a restart
 
 guide-button-search = Search
 guide-button-next = Next
@@ -77,9 +233,32 @@ guide-button-prev = Prev
 
 # Common
 button-back = Back
+button-cancel = Cancel
 button-confirm = Confirm
 button-edit = Edit
 button-select = Select
 
 keyboard-button-backspace = Backspace
-keyboard-button-shift = Shift
\ No newline at end of file
+keyboard-button-shift = Shift
+
+kid-mode-set-pin = Press up to 8 buttons to set a PIN
+kid-mode-enter-pin = Enter PIN to exit kid mode
+kid-mode-enter-recovery-pin = Kid mode settings were lost. PIN: Up Up Down Down
+kid-mode-time-warning = Play time is almost up. The game will close in 1 minute.
+kid-mode-wrong-pin = Wrong PIN
+kid-mode-time-limit-reached = Play time is up for today
+
+hotkey-quick-save = State saved
+hotkey-quick-load = State loaded
+hotkey-fast-forward = Fast-forward
+hotkey-action-volume-up = Volume Up
+hotkey-action-volume-down = Volume Down
+hotkey-action-brightness-up = Brightness Up
+hotkey-action-brightness-down = Brightness Down
+hotkey-action-screenshot = Screenshot
+hotkey-action-menu = Menu
+hotkey-action-suspend = Sleep
+hotkey-action-quit = Quit Game
+hotkey-action-quick-save = Quick Save
+hotkey-action-quick-load = Quick Load
+hotkey-action-fast-forward = Fast-forward
\ No newline at end of file
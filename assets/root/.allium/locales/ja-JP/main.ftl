@@ -6,18 +6,88 @@ tab-settings = 設定
 
 recents-sort-currently-last-played = 最近順
 recents-sort-currently-most-played = 最も順
+recents-sort-currently-favorites = お気に入り
 recents-launch = 起動
 recents-remove = 削除
 
+browser-rename = 名前を変更
+browser-delete = 削除
+browser-delete-confirm = 削除を確認
+browser-hide = 非表示
+browser-unhide = 再表示
+browser-show-hidden = 非表示項目を表示
+browser-hide-hidden = 非表示項目を隠す
+browser-random-game = ランダムゲーム
+browser-random-game-here = このフォルダからランダム
+browser-exit-kid-mode = キッズモードを終了
+
+random-game-reroll = 選び直す
+random-game-prefer-unplayed-on = 未プレイ優先
+random-game-prefer-unplayed-off = すべてのゲーム
+random-game-loading = ゲームを検索中...
+random-game-empty = ゲームが見つかりません
+
+game-details = 詳細
+game-details-console = 機種
+game-details-core = コア
+game-details-path = パス
+game-details-size = サイズ
+game-details-play-count = プレイ回数
+game-details-play-time = プレイ時間
+game-details-play-time-value = {$hours}時間{$minutes}分
+game-details-last-played = 最終プレイ
+game-details-guide = ガイド
+game-details-save-states = ステートセーブ
+game-details-save-backups = バックアップ
+game-details-yes = あり
+game-details-no = なし
+game-details-never = なし
+game-details-unknown = 不明
+game-details-launch = 起動
+game-details-launch-with = コアを選んで起動
+game-details-core-options = コアオプション
+game-details-video-settings = 映像設定
+game-details-favorite = お気に入り
+game-details-unfavorite = お気に入り解除
+
+save-backups-entry = {$time}（{$files} ファイル）
+save-backups-empty = バックアップはまだありません
+save-backups-restore = 復元
+save-backups-restore-confirm = 復元を確認
+save-backups-restored = バックアップを復元しました
+save-backups-restore-failed = バックアップの復元に失敗しました
+save-backups-export = すべてエクスポート
+save-backups-exported = {$path} にエクスポートしました
+save-backups-export-failed = セーブのエクスポートに失敗しました
+
+save-conflicts-title = この端末と同期サーバーの両方でセーブが変更されました
+save-conflicts-entry = {$file}：端末 {$local}、サーバー {$remote}
+save-conflicts-keep-local = 端末を保持
+save-conflicts-keep-remote = サーバーを保持
+save-conflicts-keep-newer = 新しい方を保持
+save-conflicts-newer = (新しい)
+save-conflicts-failed = セーブの同期に失敗しました
+
 settings-wifi = Wi-Fi
 settings-wifi-wifi-enabled = Wi-Fi有効
 settings-wifi-ip-address = IPアドレス
-settings-wifi-wifi-network = Wi-Fiネットワーク
-settings-wifi-wifi-password = Wi-Fiパスワード
 settings-wifi-ntp-enabled = NTP有効
 settings-wifi-telnet-enabled = Telnet有効
 settings-wifi-ftp-enabled = FTP有効
+settings-wifi-file-server-enabled = ファイルサーバー有効
+settings-wifi-file-server-address = ファイルサーバーアドレス
+settings-wifi-file-server-token = ファイルサーバートークン
 settings-wifi-connecting= 接続中...
+settings-wifi-status = 状態
+settings-wifi-status-connected = {$ssid} に接続済み
+settings-wifi-status-online = 接続済み
+settings-wifi-add-network = ネットワークを追加...
+settings-wifi-signal = {$strength}%
+settings-wifi-signal-secured = {$strength}%（保護）
+settings-wifi-scan = スキャン
+settings-wifi-forget = 削除
+settings-wifi-forget-confirm = 削除を確認
+settings-wifi-invalid-password = パスワードは8～63文字で、" を含めることはできません
 
 settings-display = ディスプレイ
 settings-display-luminance = 輝度
@@ -49,6 +119,32 @@ settings-language = 言語
 settings-language-language = 言語
 settings-language-restart-to-apply-changes = *変更を適用するにはデバイスを再起動してください。
 
+settings-profiles = プロフィール
+settings-profiles-separate-theme = 個別のテーマ
+settings-profiles-separate-language = 個別の言語
+settings-profiles-active = 使用中
+settings-profiles-new = 新規
+settings-profiles-delete = 削除
+settings-profiles-delete-confirm = 削除を確認
+
+settings-kid-mode = キッズモード
+settings-kid-mode-enabled = キッズモード有効
+settings-kid-mode-pin = 暗証番号
+settings-kid-mode-pin-set = 設定済み
+settings-kid-mode-pin-not-set = 未設定
+settings-kid-mode-daily-limit = 1日の制限時間（分）
+
+settings-date-time = 日付と時刻
+settings-date-time-date = 日付
+settings-date-time-time = 時刻
+settings-date-time-timezone = タイムゾーン
+settings-date-time-24-hour = 24時間表示
+
+settings-controls = 操作設定
+settings-controls-swap-buttons = A/BとX/Yを入れ替える
+settings-controls-record = 新しいボタンを押してから離してください
+settings-controls-reset = リセット
+
 settings-files = ファイル
 
 settings-about = 情報
@@ -59,6 +155,16 @@ settings-about-operating-system-version = OSバージョン
 settings-about-kernel-version = カーネルバージョン
 settings-about-storage-used = 使用済みストレージ
 settings-about-unknown-value = 不明
+settings-about-available-version = 利用可能なバージョン
+settings-about-check-for-updates = アップデートを確認
+settings-about-install-update = インストール
+settings-about-update-checking = 確認中...
+settings-about-update-up-to-date = 最新です
+settings-about-update-installing = インストール中...
+settings-about-update-staged = 再起動で適用
+settings-about-update-failed = アップデート失敗
+settings-about-rollback = アップデートを元に戻す
+settings-about-rollback-confirm = もう一度Aを押して確認
 
 # Menu
 ingame-menu-continue = 続ける
@@ -70,6 +176,56 @@ ingame-menu-guide = ガイド
 ingame-menu-quit = 終了
 ingame-menu-slot = スロット{ $slot }
 ingame-menu-disk = ディスク{ $disk }
+ingame-menu-cheats = チート
+ingame-menu-core-options = コアオプション
+ingame-menu-video-settings = 映像設定
+ingame-menu-shaders = シェーダーとオーバーレイ
+ingame-menu-netplay = ネットプレイ
+netplay-host = ホストする
+netplay-join-by-ip = IPで参加...
+netplay-searching = ゲームを検索中...
+netplay-no-hosts = ゲームが見つかりません
+netplay-password = (パスワード)
+netplay-refresh = 更新
+
+cheats-restart = 再起動
+cheats-restart-note = 再起動後にチートが反映されます
+
+core-options-title = コアオプション
+core-options-scope = 保存先
+core-options-scope-game = このゲーム
+core-options-scope-core = すべてのゲーム
+core-options-empty = 一度ゲームを起動するとオプションが表示されます
+core-options-restart = 再起動
+core-options-restart-note = 再起動後に反映されます
+
+shaders-scope = 保存先
+shaders-scope-game = このゲーム
+shaders-scope-console = このゲーム機
+shaders-scope-core = すべてのゲーム
+shaders-shader = シェーダー
+shaders-overlay = オーバーレイ
+shaders-off = オフ
+shaders-restart = 再起動
+shaders-restart-note = 再起動後に反映されます
+
+video-settings-title = 映像設定
+video-settings-scope = 保存先
+video-settings-scope-game = このゲーム
+video-settings-scope-console = このゲーム機
+video-settings-scope-core = すべてのゲーム
+video-settings-aspect-ratio = アスペクト比
+video-settings-aspect-ratio-core = コア設定
+video-settings-aspect-ratio-full = フルスクリーン
+video-settings-aspect-ratio-4-3 = 4:3
+video-settings-aspect-ratio-16-9 = 16:9
+video-settings-aspect-ratio-3-2 = 3:2
+video-settings-aspect-ratio-square-pixel = 正方形ピクセル
+video-settings-integer-scale = 整数倍スケーリング
+video-settings-smooth = バイリニアフィルタ
+video-settings-reset = リセット
+video-settings-restart = 再起動
+video-settings-restart-note = 再起動後に反映されます
 
 guide-button-search = 捜索
 guide-button-next = 次へ
@@ -77,9 +233,32 @@ guide-button-prev = 前へ
 
 # Common
 button-back = 戻る
+button-cancel = キャンセル
 button-confirm = 確認
 button-edit = 編集
 button-select = 開く
 
 keyboard-button-backspace = 消す
-keyboard-button-shift = シフト
\ No newline at end of file
+keyboard-button-shift = シフト
+
+kid-mode-set-pin = 最大8個のボタンを押して暗証番号を設定
+kid-mode-enter-pin = キッズモードを終了するには暗証番号を入力
+kid-mode-enter-recovery-pin = キッズモードの設定が失われました。PIN: 上 上 下 下
+kid-mode-time-warning = プレイ時間がもうすぐ終わります。1分後にゲームを終了します。
+kid-mode-wrong-pin = PINが違います
+kid-mode-time-limit-reached = 今日のプレイ時間は終わりました
+
+hotkey-quick-save = ステートをセーブしました
+hotkey-quick-load = ステートをロードしました
+hotkey-fast-forward = 早送り
+hotkey-action-volume-up = 音量を上げる
+hotkey-action-volume-down = 音量を下げる
+hotkey-action-brightness-up = 明るさを上げる
+hotkey-action-brightness-down = 明るさを下げる
+hotkey-action-screenshot = スクリーンショット
+hotkey-action-menu = メニュー
+hotkey-action-suspend = スリープ
+hotkey-action-quit = ゲームを終了
+hotkey-action-quick-save = クイックセーブ
+hotkey-action-quick-load = クイックロード
+hotkey-action-fast-forward = 早送り
\ No newline at end of file
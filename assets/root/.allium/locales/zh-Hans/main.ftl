@@ -6,18 +6,88 @@ tab-settings = 设置
 
 recents-sort-currently-last-played = 最后播放
 recents-sort-currently-most-played = 最常播放
+recents-sort-currently-favorites = 收藏
 recents-launch = 启动
 recents-remove = 移除
 
+browser-rename = 重命名
+browser-delete = 删除
+browser-delete-confirm = 确认删除
+browser-hide = 隐藏
+browser-unhide = 取消隐藏
+browser-show-hidden = 显示隐藏项
+browser-hide-hidden = 不显示隐藏项
+browser-random-game = 随机游戏
+browser-random-game-here = 当前目录随机游戏
+browser-exit-kid-mode = 退出儿童模式
+
+random-game-reroll = 换一个
+random-game-prefer-unplayed-on = 优先未玩过
+random-game-prefer-unplayed-off = 全部游戏
+random-game-loading = 正在查找游戏...
+random-game-empty = 没有找到游戏
+
+game-details = 详情
+game-details-console = 主机
+game-details-core = 核心
+game-details-path = 路径
+game-details-size = 大小
+game-details-play-count = 游玩次数
+game-details-play-time = 游玩时间
+game-details-play-time-value = {$hours}小时{$minutes}分钟
+game-details-last-played = 最后游玩
+game-details-guide = 攻略
+game-details-save-states = 即时存档
+game-details-save-backups = 存档备份
+game-details-yes = 有
+game-details-no = 无
+game-details-never = 从未
+game-details-unknown = 未知
+game-details-launch = 启动
+game-details-launch-with = 选择核心启动
+game-details-core-options = 核心选项
+game-details-video-settings = 视频设置
+game-details-favorite = 收藏
+game-details-unfavorite = 取消收藏
+
+save-backups-entry = {$time}（{$files} 个文件）
+save-backups-empty = 暂无备份
+save-backups-restore = 恢复
+save-backups-restore-confirm = 确认恢复
+save-backups-restored = 备份已恢复
+save-backups-restore-failed = 恢复备份失败
+save-backups-export = 全部导出
+save-backups-exported = 已导出到 {$path}
+save-backups-export-failed = 导出存档失败
+
+save-conflicts-title = 本设备和同步服务器上的存档都已更改
+save-conflicts-entry = {$file}：设备 {$local}，服务器 {$remote}
+save-conflicts-keep-local = 保留设备
+save-conflicts-keep-remote = 保留服务器
+save-conflicts-keep-newer = 保留较新
+save-conflicts-newer = (较新)
+save-conflicts-failed = 同步存档失败
+
 settings-wifi = Wi-Fi
 settings-wifi-wifi-enabled = Wi-Fi启用
 settings-wifi-ip-address = IP地址
-settings-wifi-wifi-network = Wi-Fi网络名称
-settings-wifi-wifi-password = Wi-Fi密码
 settings-wifi-ntp-enabled = NTP启用
 settings-wifi-telnet-enabled = Telnet启用
 settings-wifi-ftp-enabled = FTP启用
+settings-wifi-file-server-enabled = 文件服务器启用
+settings-wifi-file-server-address = 文件服务器地址
+settings-wifi-file-server-token = 文件服务器令牌
 settings-wifi-connecting= 连接中...
+settings-wifi-status = 状态
+settings-wifi-status-connected = 已连接到 {$ssid}
+settings-wifi-status-online = 已连接
+settings-wifi-add-network = 添加网络...
+settings-wifi-signal = {$strength}%
+settings-wifi-signal-secured = {$strength}%（加密）
+settings-wifi-scan = 扫描
+settings-wifi-forget = 忘记
+settings-wifi-forget-confirm = 确认忘记
+settings-wifi-invalid-password = 密码必须为 8 到 63 个字符，且不能包含 "
 
 settings-display = 显示
 settings-display-luminance = 亮度
@@ -49,6 +119,32 @@ settings-language = 语言
 settings-language-language = 语言
 settings-language-restart-to-apply-changes = *重新启动设备以应用更改
 
+settings-profiles = 用户
+settings-profiles-separate-theme = 独立主题
+settings-profiles-separate-language = 独立语言
+settings-profiles-active = 当前
+settings-profiles-new = 新建
+settings-profiles-delete = 删除
+settings-profiles-delete-confirm = 确认删除
+
+settings-kid-mode = 儿童模式
+settings-kid-mode-enabled = 启用儿童模式
+settings-kid-mode-pin = 密码
+settings-kid-mode-pin-set = 已设置
+settings-kid-mode-pin-not-set = 未设置
+settings-kid-mode-daily-limit = 每日时限（分钟）
+
+settings-date-time = 日期和时间
+settings-date-time-date = 日期
+settings-date-time-time = 时间
+settings-date-time-timezone = 时区
+settings-date-time-24-hour = 24小时制
+
+settings-controls = 按键设置
+settings-controls-swap-buttons = 交换A/B和X/Y
+settings-controls-record = 按住新的按键，然后松开
+settings-controls-reset = 重置
+
 settings-files = 文件
 
 settings-about = 关于
@@ -59,6 +155,16 @@ settings-about-operating-system-version = 操作系统版本
 settings-about-kernel-version = 内核版本
 settings-about-storage-used = 已使用的存储空间
 settings-about-unknown-value = 未知
+settings-about-available-version = 可用版本
+settings-about-check-for-updates = 检查更新
+settings-about-install-update = 安装
+settings-about-update-checking = 正在检查...
+settings-about-update-up-to-date = 已是最新版本
+settings-about-update-installing = 正在安装...
+settings-about-update-staged = 重启后生效
+settings-about-update-failed = 更新失败
+settings-about-rollback = 回滚更新
+settings-about-rollback-confirm = 再次按A确认
 
 # Menu
 ingame-menu-continue = 继续
@@ -70,6 +176,56 @@ ingame-menu-guide = 指南
 ingame-menu-quit = 退出
 ingame-menu-slot = 槽位{ $slot }
 ingame-menu-disk = 磁盘{ $disk }
+ingame-menu-cheats = 金手指
+ingame-menu-core-options = 核心选项
+ingame-menu-video-settings = 视频设置
+ingame-menu-shaders = 着色器与遮罩
+ingame-menu-netplay = 联机
+netplay-host = 创建游戏
+netplay-join-by-ip = 通过IP加入...
+netplay-searching = 正在搜索游戏...
+netplay-no-hosts = 未找到游戏
+netplay-password = (密码)
+netplay-refresh = 刷新
+
+cheats-restart = 重启
+cheats-restart-note = 重启后金手指生效
+
+core-options-title = 核心选项
+core-options-scope = 保存到
+core-options-scope-game = 此游戏
+core-options-scope-core = 所有游戏
+core-options-empty = 运行一次游戏后即可查看选项
+core-options-restart = 重启
+core-options-restart-note = 重启后生效
+
+shaders-scope = 保存到
+shaders-scope-game = 此游戏
+shaders-scope-console = 此主机
+shaders-scope-core = 所有游戏
+shaders-shader = 着色器
+shaders-overlay = 遮罩
+shaders-off = 关闭
+shaders-restart = 重启
+shaders-restart-note = 重启后生效
+
+video-settings-title = 视频设置
+video-settings-scope = 保存到
+video-settings-scope-game = 此游戏
+video-settings-scope-console = 此主机
+video-settings-scope-core = 所有游戏
+video-settings-aspect-ratio = 宽高比
+video-settings-aspect-ratio-core = 核心提供
+video-settings-aspect-ratio-full = 全屏
+video-settings-aspect-ratio-4-3 = 4:3
+video-settings-aspect-ratio-16-9 = 16:9
+video-settings-aspect-ratio-3-2 = 3:2
+video-settings-aspect-ratio-square-pixel = 方形像素
+video-settings-integer-scale = 整数缩放
+video-settings-smooth = 双线性过滤
+video-settings-reset = 重置
+video-settings-restart = 重启
+video-settings-restart-note = 重启后生效
 
 guide-button-search = 搜索
 guide-button-next = 下一项
@@ -77,9 +233,32 @@ guide-button-prev = 上一项
 
 # Common
 button-back = 返回
+button-cancel = 取消
 button-confirm = 确认
 button-edit = 编辑
 button-select = 选择
 
 keyboard-button-backspace = 退格
-keyboard-button-shift = Shift
\ No newline at end of file
+keyboard-button-shift = Shift
+
+kid-mode-set-pin = 按下最多 8 个按键设置密码
+kid-mode-enter-pin = 输入密码以退出儿童模式
+kid-mode-enter-recovery-pin = 儿童模式设置已丢失。密码：上 上 下 下
+kid-mode-time-warning = 游戏时间即将用完，游戏将在 1 分钟后关闭。
+kid-mode-wrong-pin = PIN 错误
+kid-mode-time-limit-reached = 今天的游戏时间已用完
+
+hotkey-quick-save = 已保存即时存档
+hotkey-quick-load = 已读取即时存档
+hotkey-fast-forward = 快进
+hotkey-action-volume-up = 增大音量
+hotkey-action-volume-down = 减小音量
+hotkey-action-brightness-up = 提高亮度
+hotkey-action-brightness-down = 降低亮度
+hotkey-action-screenshot = 截图
+hotkey-action-menu = 菜单
+hotkey-action-suspend = 睡眠
+hotkey-action-quit = 退出游戏
+hotkey-action-quick-save = 快速存档
+hotkey-action-quick-load = 快速读档
+hotkey-action-fast-forward = 快进
\ No newline at end of file
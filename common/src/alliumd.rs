@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use crate::constants::ALLIUMD_SOCKET;
+use crate::game_info::GameInfo;
+use crate::netplay::Netplay;
+
+/// Requests accepted by alliumd on its Unix socket. The protocol is one JSON object per line in
+/// each direction, e.g. `{"command":"set-volume","volume":10}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum AlliumDRequest {
+    /// Returns an [`AlliumDStatus`].
+    Status,
+    /// Sets the volume, from 0 to 20.
+    SetVolume { volume: i32 },
+    /// Sets the brightness, from 0 to 100.
+    SetBrightness { brightness: u8 },
+    /// Launches a game through the launcher. Fails if a game is already running.
+    Launch { path: PathBuf },
+    /// Quits the running game. RetroArch is asked to quit so that it writes its save file.
+    Quit,
+    /// Quits RetroArch and launches the running game again, e.g. to apply settings that the core
+    /// only reads at startup. A netplay session, if given, is started with it.
+    Relaunch { netplay: Option<Netplay> },
+    /// Opens the in-game menu.
+    Menu,
+    /// Saves a screenshot to the Screenshots folder.
+    Screenshot,
+    /// Puts the device to sleep: the running game or launcher is paused and the screen is turned
+    /// off.
+    Suspend,
+    /// Wakes the device up.
+    Resume,
+    /// Sent by the launcher once it has drawn its first frame, to confirm that a freshly
+    /// installed update works.
+    LauncherStarted,
+    /// Sent by the launcher just before it replaces itself with the game it launched, to run
+    /// the game start hooks.
+    GameStarted,
+    /// Starts or stops the file server to match the saved Wi-Fi settings. Sent after saving
+    /// them.
+    ReloadWiFiSettings,
+    /// Quits the running game and powers off.
+    Shutdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "kebab-case")]
+pub enum AlliumDResponse {
+    Ok,
+    Status(AlliumDStatus),
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlliumDStatus {
+    /// The running game, if any.
+    pub game: Option<GameStatus>,
+    pub battery: BatteryStatus,
+    pub volume: i32,
+    pub brightness: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameStatus {
+    pub name: String,
+    pub path: PathBuf,
+    /// RetroArch core, if the game runs in RetroArch.
+    pub core: Option<String>,
+    /// Seconds played since the game was launched.
+    pub play_time: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatteryStatus {
+    pub percentage: i32,
+    pub charging: bool,
+}
+
+impl From<&GameInfo> for GameStatus {
+    fn from(game_info: &GameInfo) -> Self {
+        Self {
+            name: game_info.name.clone(),
+            path: game_info.path.clone(),
+            core: game_info.core().map(str::to_string),
+            play_time: game_info.play_time().num_seconds(),
+        }
+    }
+}
+
+impl AlliumDRequest {
+    /// Sends the request to alliumd and waits for the response.
+    pub async fn send(&self) -> Result<AlliumDResponse> {
+        self.send_to(&ALLIUMD_SOCKET).await
+    }
+
+    async fn send_to(&self, socket: &Path) -> Result<AlliumDResponse> {
+        debug!("Sending alliumd request: {:?}", self);
+        let stream = UnixStream::connect(socket)
+            .await
+            .with_context(|| format!("Failed to connect to alliumd at {}.", socket.display()))?;
+        let (read, mut write) = stream.into_split();
+
+        let mut json = serde_json::to_string(self)?;
+        json.push('\n');
+        write.write_all(json.as_bytes()).await?;
+
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await?;
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+impl AlliumDResponse {
+    /// Turns an error response into an error.
+    pub fn into_result(self) -> Result<Self> {
+        match self {
+            AlliumDResponse::Error { message } => Err(anyhow!(message)),
+            response => Ok(response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::UnixListener;
+
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let cases = [
+            (AlliumDRequest::Status, r#"{"command":"status"}"#),
+            (
+                AlliumDRequest::SetVolume { volume: 10 },
+                r#"{"command":"set-volume","volume":10}"#,
+            ),
+            (
+                AlliumDRequest::Launch {
+                    path: PathBuf::from("/mnt/SDCARD/Roms/GBA/Game.gba"),
+                },
+                r#"{"command":"launch","path":"/mnt/SDCARD/Roms/GBA/Game.gba"}"#,
+            ),
+            (
+                AlliumDRequest::Relaunch { netplay: None },
+                r#"{"command":"relaunch","netplay":null}"#,
+            ),
+            (AlliumDRequest::Suspend, r#"{"command":"suspend"}"#),
+            (
+                AlliumDRequest::LauncherStarted,
+                r#"{"command":"launcher-started"}"#,
+            ),
+        ];
+        for (request, json) in cases {
+            assert_eq!(serde_json::to_string(&request).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<AlliumDRequest>(json).unwrap(),
+                request
+            );
+        }
+
+        let status = AlliumDResponse::Status(AlliumDStatus {
+            game: None,
+            battery: BatteryStatus {
+                percentage: 80,
+                charging: false,
+            },
+            volume: 5,
+            brightness: 50,
+        });
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"result":"status","game":null,"battery":{"percentage":80,"charging":false},"volume":5,"brightness":50}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&AlliumDResponse::Ok).unwrap(),
+            r#"{"result":"ok"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let dir = temp_dir("alliumd-send");
+        let socket = dir.join("alliumd.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: AlliumDRequest = serde_json::from_str(&line).unwrap();
+            let response = match request {
+                AlliumDRequest::Quit => AlliumDResponse::Error {
+                    message: "No game is running.".to_string(),
+                },
+                _ => AlliumDResponse::Ok,
+            };
+            let mut json = serde_json::to_string(&response).unwrap();
+            json.push('\n');
+            write.write_all(json.as_bytes()).await.unwrap();
+        });
+
+        let response = AlliumDRequest::Quit.send_to(&socket).await.unwrap();
+        server.await.unwrap();
+        assert_eq!(
+            response.into_result().unwrap_err().to_string(),
+            "No game is running."
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+
+use crate::constants::RETROARCH_CHEATS_DIR;
+use crate::retroarch::{CoreInfo, RetroArchCommand};
+use crate::retroarch_config::RetroArchConfig;
+
+/// Cheats for a game, read from a RetroArch `.cht` file.
+///
+/// Changes are saved to the game specific cheat file, which RetroArch loads and applies when the
+/// game is launched, so enabled cheats carry over to the next launch. Cheats from anywhere else
+/// aren't loaded in the running game, so they only apply after a restart.
+#[derive(Debug, Clone)]
+pub struct Cheats {
+    /// Game specific cheat file that changes are saved to.
+    path: PathBuf,
+    config: RetroArchConfig,
+    /// Whether RetroArch loaded the cheats when the game was launched, so that they can be
+    /// toggled in the running game.
+    is_loaded: bool,
+}
+
+impl Cheats {
+    /// Finds the cheats for a rom run with the given core. Cheats saved earlier take priority,
+    /// followed by a `.cht` file next to the rom, then the cheat database for the core's consoles.
+    /// `launched_at` is when the running game was launched, to tell whether RetroArch loaded
+    /// the saved cheats.
+    pub fn find(core: &str, rom: &Path, launched_at: DateTime<Utc>) -> Result<Option<Self>> {
+        let info = CoreInfo::load(core)?;
+        Self::find_in(&RETROARCH_CHEATS_DIR, &info, rom, launched_at)
+    }
+
+    fn find_in(
+        cheats_dir: &Path,
+        info: &CoreInfo,
+        rom: &Path,
+        launched_at: DateTime<Utc>,
+    ) -> Result<Option<Self>> {
+        let Some(stem) = rom.file_stem() else {
+            return Ok(None);
+        };
+        let mut file_name = stem.to_os_string();
+        file_name.push(".cht");
+
+        let path = cheats_dir.join(&info.corename).join(&file_name);
+        let source = std::iter::once(path.clone())
+            .chain(std::iter::once(rom.with_file_name(&file_name)))
+            .chain(
+                info.databases
+                    .iter()
+                    .map(|d| cheats_dir.join(d).join(&file_name)),
+            )
+            .find(|p| p.exists());
+
+        Ok(match source {
+            Some(source) => {
+                debug!("found cheats at {}", source.display());
+                let is_loaded = source == path
+                    && DateTime::<Utc>::from(fs::metadata(&source)?.modified()?) <= launched_at;
+                let cheats = Self {
+                    path,
+                    config: RetroArchConfig::load(&source)?,
+                    is_loaded,
+                };
+                (!cheats.is_empty()).then_some(cheats)
+            }
+            None => None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.config
+            .get("cheats")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether changes apply to the running game. Otherwise, they apply after a restart.
+    pub fn is_loaded(&self) -> bool {
+        self.is_loaded
+    }
+
+    /// Description of the cheat, falling back to its code.
+    pub fn desc(&self, index: usize) -> &str {
+        self.config
+            .get(&format!("cheat{index}_desc"))
+            .filter(|d| !d.is_empty())
+            .or_else(|| self.config.get(&format!("cheat{index}_code")))
+            .unwrap_or_default()
+    }
+
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.config
+            .get_bool(&format!("cheat{index}_enable"))
+            .unwrap_or_default()
+    }
+
+    fn set(&mut self, index: usize, enabled: bool) {
+        self.config
+            .set_bool(&format!("cheat{index}_enable"), enabled);
+    }
+
+    /// Saves a cheat for the next launch, and enables or disables it in the running game if
+    /// RetroArch loaded the cheats.
+    pub async fn set_enabled(&mut self, index: usize, enabled: bool) -> Result<()> {
+        if self.is_enabled(index) == enabled {
+            return Ok(());
+        }
+        info!("setting cheat {} to {}", index, enabled);
+        self.set(index, enabled);
+        self.config.save(&self.path)?;
+        if !self.is_loaded {
+            return Ok(());
+        }
+
+        // RetroArch's cheat index can't be queried. It is at the first cheat when the game is
+        // loaded, and is moved back there after each toggle, which assumes that nothing else
+        // moves it, e.g. RetroArch's own cheat hotkeys.
+        for _ in 0..index {
+            RetroArchCommand::CheatIndexPlus.send().await?;
+        }
+        RetroArchCommand::CheatToggle.send().await?;
+        for _ in 0..index {
+            RetroArchCommand::CheatIndexMinus.send().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    const CHEATS: &str = r#"cheats = 3
+
+cheat0_desc = "Infinite Health"
+cheat0_code = "02000000:FF"
+cheat0_enable = false
+
+cheat1_desc = ""
+cheat1_code = "02000004:63"
+cheat1_enable = true
+
+cheat2_desc = "Max Money"
+cheat2_code = "02000008:99"
+"#;
+
+    fn info() -> CoreInfo {
+        CoreInfo {
+            corename: "mGBA".to_string(),
+            databases: vec![
+                "Nintendo - Game Boy".to_string(),
+                "Nintendo - Game Boy Advance".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut cheats = Cheats {
+            path: PathBuf::new(),
+            config: RetroArchConfig::parse(CHEATS),
+            is_loaded: true,
+        };
+        assert_eq!(cheats.len(), 3);
+        assert_eq!(cheats.desc(0), "Infinite Health");
+        assert_eq!(cheats.desc(1), "02000004:63");
+        assert!(!cheats.is_enabled(0));
+        assert!(cheats.is_enabled(1));
+        assert!(!cheats.is_enabled(2));
+
+        cheats.set(0, true);
+        cheats.set(2, true);
+        let config = RetroArchConfig::parse(&cheats.config.to_string());
+        assert_eq!(config.get_bool("cheat0_enable"), Some(true));
+        assert_eq!(config.get_bool("cheat2_enable"), Some(true));
+        assert_eq!(config.get("cheat2_code"), Some("02000008:99"));
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = temp_dir("cheats-find");
+        let cheats_dir = dir.join("cheats");
+        let rom = dir.join("Roms/GBA/Game.gba");
+        fs::create_dir_all(rom.parent().unwrap()).unwrap();
+        let launched_at = Utc::now() - chrono::Duration::seconds(10);
+
+        assert!(Cheats::find_in(&cheats_dir, &info(), &rom, launched_at)
+            .unwrap()
+            .is_none());
+
+        // Database for one of the core's consoles
+        let database = cheats_dir.join("Nintendo - Game Boy Advance/Game.cht");
+        fs::create_dir_all(database.parent().unwrap()).unwrap();
+        fs::write(&database, CHEATS).unwrap();
+        let cheats = Cheats::find_in(&cheats_dir, &info(), &rom, launched_at)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cheats.len(), 3);
+        assert_eq!(cheats.path, cheats_dir.join("mGBA/Game.cht"));
+        assert!(!cheats.is_loaded());
+
+        // Next to the rom takes priority over the database
+        fs::write(
+            rom.with_extension("cht"),
+            "cheats = 1\ncheat0_desc = \"Rom\"\n",
+        )
+        .unwrap();
+        let cheats = Cheats::find_in(&cheats_dir, &info(), &rom, launched_at)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cheats.desc(0), "Rom");
+
+        // Saved cheats take priority over everything
+        let mut saved = RetroArchConfig::parse(CHEATS);
+        saved.set_bool("cheat2_enable", true);
+        saved.save(&cheats_dir.join("mGBA/Game.cht")).unwrap();
+        let cheats = Cheats::find_in(&cheats_dir, &info(), &rom, launched_at)
+            .unwrap()
+            .unwrap();
+        assert!(cheats.is_enabled(2));
+        // RetroArch only loaded them if they were saved before the game was launched
+        assert!(!cheats.is_loaded());
+        let cheats = Cheats::find_in(&cheats_dir, &info(), &rom, Utc::now())
+            .unwrap()
+            .unwrap();
+        assert!(cheats.is_loaded());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,5 +1,9 @@
+use chrono::NaiveDateTime;
+
 use crate::display::color::Color;
+use crate::kid_mode::KidModeSettings;
 use crate::locale::LocaleSettings;
+use crate::platform::Key;
 use crate::{display::settings::DisplaySettings, stylesheet::Stylesheet};
 
 #[derive(Debug)]
@@ -9,6 +13,10 @@ pub enum Command {
     SaveStylesheet(Box<Stylesheet>),
     SaveDisplaySettings(Box<DisplaySettings>),
     SaveLocaleSettings(LocaleSettings),
+    SaveKidModeSettings(Box<KidModeSettings>),
+    SwitchProfile(String),
+    /// Shows a short message near the bottom of the screen.
+    Toast(String),
     CloseView,
     ValueChanged(usize, Value),
     TrapFocus,
@@ -22,6 +30,8 @@ pub enum Value {
     Int(i32),
     String(String),
     Color(Color),
+    Keys(Vec<Key>),
+    DateTime(NaiveDateTime),
 }
 
 impl Value {
@@ -52,6 +62,20 @@ impl Value {
             _ => None,
         }
     }
+
+    pub fn as_keys(self) -> Option<Vec<Key>> {
+        match self {
+            Value::Keys(k) => Some(k),
+            _ => None,
+        }
+    }
+
+    pub fn as_date_time(self) -> Option<NaiveDateTime> {
+        match self {
+            Value::DateTime(t) => Some(t),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Value {
@@ -26,12 +26,24 @@ lazy_static! {
     pub static ref ALLIUM_FONTS_DIR: PathBuf = ALLIUM_BASE_DIR.join("fonts");
     pub static ref ALLIUM_LOCALES_DIR: PathBuf = ALLIUM_BASE_DIR.join("locales");
     pub static ref ALLIUM_IMAGES_DIR: PathBuf = ALLIUM_BASE_DIR.join("images");
+    pub static ref ALLIUM_CURRENT_PROFILE_DIR: PathBuf = ALLIUM_SD_ROOT.join("Saves/CurrentProfile");
+    pub static ref ALLIUM_PROFILES_DIR: PathBuf = ALLIUM_SD_ROOT.join("Saves/Profiles");
+    pub static ref ALLIUM_SAVES_DIR: PathBuf = ALLIUM_CURRENT_PROFILE_DIR.join("saves");
+    pub static ref ALLIUM_SAVE_STATES_DIR: PathBuf = ALLIUM_CURRENT_PROFILE_DIR.join("states");
+    pub static ref ALLIUM_SAVE_BACKUPS_DIR: PathBuf = ALLIUM_CURRENT_PROFILE_DIR.join("backups");
+    pub static ref ALLIUM_SAVE_EXPORTS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Backups");
 
     // Config
     pub static ref ALLIUM_CONFIG_CONSOLES: PathBuf = ALLIUM_BASE_DIR.join("config/consoles.toml");
+    pub static ref ALLIUM_CONFIG_HOTKEYS: PathBuf = ALLIUM_BASE_DIR.join("config/hotkeys.toml");
+    pub static ref ALLIUM_CONFIG_INPUT: PathBuf = ALLIUM_BASE_DIR.join("config/input.toml");
 
     // State
     pub static ref ALLIUMD_STATE: PathBuf = ALLIUM_BASE_DIR.join("state/alliumd.json");
+    /// alliumd's API socket. The SD card can't hold sockets, so it lives in `/tmp`.
+    pub static ref ALLIUMD_SOCKET: PathBuf = PathBuf::from(
+        &env::var("ALLIUMD_SOCKET").unwrap_or_else(|_| "/tmp/alliumd.sock".to_string())
+    );
     pub static ref ALLIUM_LAUNCHER_STATE: PathBuf =
         ALLIUM_BASE_DIR.join("state/allium-launcher.json");
     pub static ref ALLIUM_MENU_STATE: PathBuf =
@@ -41,16 +53,52 @@ lazy_static! {
     pub static ref ALLIUM_DISPLAY_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/display.json");
     pub static ref ALLIUM_LOCALE_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/locale.json");
     pub static ref ALLIUM_WIFI_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/wifi.json");
+    pub static ref ALLIUM_KID_MODE_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/kid_mode.json");
+    pub static ref ALLIUM_PROFILES: PathBuf = ALLIUM_BASE_DIR.join("state/profiles.json");
+    pub static ref ALLIUM_SAVE_SYNC_SETTINGS: PathBuf =
+        ALLIUM_BASE_DIR.join("state/save_sync.json");
+    pub static ref ALLIUM_UPDATE_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/update.json");
+    pub static ref ALLIUM_DATETIME_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/datetime.json");
+
+    // Updates
+    /// Staged update, applied by the updater script on the next boot.
+    pub static ref ALLIUM_UPDATE_DIR: PathBuf = ALLIUM_BASE_DIR.join("update");
+    /// Folder to drop a release manifest and archive into for offline installs.
+    pub static ref ALLIUM_UPDATE_DROP_DIR: PathBuf = ALLIUM_SD_ROOT.join("Update");
 
     // Database
     pub static ref ALLIUM_DATABASE: PathBuf = env::var("ALLIUM_DATABASE")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| ALLIUM_SD_ROOT.join("Saves/CurrentProfile/allium.db"));
+        .unwrap_or_else(|_| ALLIUM_CURRENT_PROFILE_DIR.join("allium.db"));
 
     // Binaries & Scripts
     pub static ref ALLIUM_LAUNCHER: PathBuf = ALLIUM_BASE_DIR.join("bin/allium-launcher");
     pub static ref ALLIUM_MENU: PathBuf = ALLIUM_BASE_DIR.join("bin/allium-menu");
     pub static ref ALLIUM_RETROARCH: PathBuf = ALLIUM_BASE_DIR.join("cores/retroarch/launch.sh");
+    /// Can be overridden to scan with a script that prints canned results, e.g. in the simulator.
+    pub static ref ALLIUM_WPA_CLI: PathBuf = env::var("ALLIUM_WPA_CLI")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("wpa_cli"));
+
+    // RetroArch
+    pub static ref RETROARCH_DIR: PathBuf = ALLIUM_SD_ROOT.join("RetroArch/.retroarch");
+    pub static ref RETROARCH_CORES_DIR: PathBuf = RETROARCH_DIR.join("cores");
+    /// Main RetroArch config, which overrides are applied on top of.
+    pub static ref RETROARCH_MAIN_CONFIG: PathBuf = RETROARCH_DIR.join("retroarch.cfg");
+    /// Per-core and per-game core options and config overrides, in a folder per core.
+    pub static ref RETROARCH_CONFIG_DIR: PathBuf = RETROARCH_DIR.join("config");
+    /// Cheat database, with a folder per console. Game specific cheats are saved in a folder per
+    /// core, named after the core's `corename`.
+    pub static ref RETROARCH_CHEATS_DIR: PathBuf = RETROARCH_DIR.join("cheats");
+    /// Software video filters, which the device's RetroArch uses in place of shaders.
+    pub static ref RETROARCH_FILTERS_DIR: PathBuf = RETROARCH_DIR.join("filters/video");
+    pub static ref RETROARCH_OVERLAYS_DIR: PathBuf = RETROARCH_DIR.join("overlay");
+
+    // Netplay
+    /// Where netplay LAN discovery queries are sent. Can be overridden to find hosts on the same
+    /// machine, e.g. `127.0.0.1:55435` in the simulator.
+    pub static ref NETPLAY_DISCOVERY_ADDRESS: String = env::var("ALLIUM_NETPLAY_DISCOVERY")
+        .unwrap_or_else(|_| format!("255.255.255.255:{}", NETPLAY_PORT));
 }
 
 // Styles
@@ -60,12 +108,57 @@ pub const SELECTION_MARGIN: u32 = 8;
 /// After the battery level drops below this threshold, the device will shut down.
 pub const BATTERY_SHUTDOWN_THRESHOLD: i32 = 5;
 
+/// When the battery level drops to this threshold, the low battery hooks are run.
+pub const BATTERY_LOW_THRESHOLD: i32 = 15;
+
 /// The interval at which the battery level is updated.
 pub const BATTERY_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
 
 /// The interval at which the clock is updated.
 pub const CLOCK_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 
+/// How long a toast message stays on screen.
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/// The interval at which the kid mode play time limit is checked while in game.
+pub const KID_MODE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long before the kid mode play time limit is reached to warn that the game will quit.
+pub const KID_MODE_WARNING_TIME: Duration = Duration::from_secs(60);
+
+/// How long RetroArch gets to save and quit before it is terminated.
+pub const RETROARCH_QUIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a hook script may run before it is killed.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for a Wi-Fi scan to complete before reading the results.
+pub const WIFI_SCAN_DURATION: Duration = Duration::from_secs(3);
+
+/// The interval at which the Wi-Fi connection status is refreshed in settings.
+pub const WIFI_STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait for the save sync remote to respond.
+pub const SAVE_SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for the update server to respond, including the archive download.
+pub const UPDATE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Base64 Ed25519 public key that releases are signed with, set at build time. `make build`
+/// derives it from the private key that `make package-update` signs with, `UPDATE_KEY`
+/// (`allium-update.pem` by default), which is kept by the release maintainers and never
+/// committed. `make update-key` generates a new one. Builds without it can't install updates.
+pub const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("ALLIUM_UPDATE_PUBLIC_KEY");
+
+/// The port the file server listens on.
+pub const FILE_SERVER_PORT: u16 = 8080;
+
+/// Largest file that can be uploaded to the file server, the FAT32 file size limit.
+pub const FILE_SERVER_MAX_UPLOAD_SIZE: u64 = u32::MAX as u64;
+
+/// The number of save backups kept per game.
+pub const SAVE_BACKUP_LIMIT: usize = 5;
+
 /// The number of items to jump when pressing left/right in a listing.
 pub const LISTING_JUMP_SIZE: i32 = 5;
 
@@ -77,3 +170,9 @@ pub const RECENT_GAMES_LIMIT: i64 = 100;
 
 /// RetroArch network command interface.
 pub const RETROARCH_UDP_SOCKET: &str = "127.0.0.1:55355";
+
+/// RetroArch's default netplay port, used both for the TCP connection and UDP LAN discovery.
+pub const NETPLAY_PORT: u16 = 55435;
+
+/// How long to wait for netplay hosts to answer a LAN discovery query.
+pub const NETPLAY_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
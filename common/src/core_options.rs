@@ -0,0 +1,406 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{debug, info};
+
+use crate::constants::RETROARCH_CONFIG_DIR;
+use crate::retroarch::CoreInfo;
+use crate::retroarch_config::RetroArchConfig;
+
+/// Extension of options saved while RetroArch is running. RetroArch writes its own copy of the
+/// options file when it exits, so the pending file is moved into place afterwards.
+const PENDING_EXTENSION: &str = "opt.pending";
+
+/// Which options file to edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreOptionsScope {
+    /// Options shared by every game run with the core.
+    Core,
+    /// Options for a single game, which replace the core options entirely.
+    Game,
+}
+
+/// How an option can be edited. `.opt` files don't list the values a core accepts, so only
+/// values the core is known to accept are offered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreOptionKind {
+    /// Two values such as `enabled`/`disabled`.
+    Toggle { on: String, off: String },
+    /// Index of the current value, and the values to pick from.
+    Select(usize, Vec<String>),
+    /// No other value is known to be valid, so the option is only shown.
+    ReadOnly,
+}
+
+/// RetroArch core options from a per-core or per-game `.opt` file.
+#[derive(Debug, Clone)]
+pub struct CoreOptions {
+    path: PathBuf,
+    config: RetroArchConfig,
+    /// Values of each option across every options file of the core.
+    known: KnownValues,
+}
+
+type KnownValues = Vec<(String, BTreeSet<String>)>;
+
+impl CoreOptions {
+    /// Loads the options of a core for a rom. If the game has no options of its own yet, they
+    /// start as a copy of the core options.
+    pub fn load(core: &str, rom: &Path, scope: CoreOptionsScope) -> Result<Self> {
+        let info = CoreInfo::load(core)?;
+        Self::load_in(&RETROARCH_CONFIG_DIR, &info, rom, scope)
+    }
+
+    fn load_in(
+        config_dir: &Path,
+        info: &CoreInfo,
+        rom: &Path,
+        scope: CoreOptionsScope,
+    ) -> Result<Self> {
+        let dir = config_dir.join(&info.corename);
+        let core_path = dir.join(format!("{}.opt", info.corename));
+        let path = match scope {
+            CoreOptionsScope::Core => core_path.clone(),
+            CoreOptionsScope::Game => dir.join(format!(
+                "{}.opt",
+                rom.file_stem().unwrap_or_default().to_string_lossy()
+            )),
+        };
+
+        let config = [&path, &core_path]
+            .into_iter()
+            .find_map(|p| {
+                let pending = p.with_extension(PENDING_EXTENSION);
+                [pending, p.to_path_buf()].into_iter().find(|p| p.exists())
+            })
+            .map(|p| {
+                debug!("loading core options from {}", p.display());
+                RetroArchConfig::load(&p)
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let mut known: KnownValues = Vec::new();
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let p = entry.path();
+                if p.extension().and_then(|e| e.to_str()) != Some("opt") {
+                    continue;
+                }
+                for (key, value) in RetroArchConfig::load(&p)?.iter() {
+                    add_known(&mut known, key, value);
+                }
+            }
+        }
+        for (key, value) in config.iter() {
+            add_known(&mut known, key, value);
+        }
+
+        Ok(Self {
+            path,
+            config,
+            known,
+        })
+    }
+
+    /// Returns whether the game has options of its own.
+    pub fn has_game_options(core: &str, rom: &Path) -> bool {
+        Self::load(core, rom, CoreOptionsScope::Game)
+            .map(|o| o.path.exists() || o.path.with_extension(PENDING_EXTENSION).exists())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.config.is_empty()
+    }
+
+    /// Returns the option keys and their values, in file order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.config.iter()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.config.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        self.config.set(key, value);
+    }
+
+    /// Returns how an option can be edited.
+    pub fn kind(&self, key: &str) -> Option<CoreOptionKind> {
+        let value = self.config.get(key)?;
+        let known = self
+            .known
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default();
+        Some(option_kind(value, &known))
+    }
+
+    /// Saves the options. If RetroArch is running, they are saved as pending and applied by
+    /// [`CoreOptions::apply_pending`] once it exits.
+    pub fn save(&self, is_running: bool) -> Result<()> {
+        if is_running {
+            self.config
+                .save(&self.path.with_extension(PENDING_EXTENSION))
+        } else {
+            self.config.save(&self.path)
+        }
+    }
+
+    /// Moves options saved while RetroArch was running into place.
+    pub fn apply_pending(core: &str) -> Result<()> {
+        let info = CoreInfo::load(core)?;
+        Self::apply_pending_in(&RETROARCH_CONFIG_DIR, &info)
+    }
+
+    fn apply_pending_in(config_dir: &Path, info: &CoreInfo) -> Result<()> {
+        let Ok(entries) = fs::read_dir(config_dir.join(&info.corename)) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            let pending = entry.path();
+            if pending.to_string_lossy().ends_with(PENDING_EXTENSION) {
+                // `Game.opt.pending` -> `Game.opt`
+                let path = pending.with_extension("");
+                info!("applying pending core options to {}", path.display());
+                fs::rename(&pending, &path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn add_known(known: &mut KnownValues, key: &str, value: &str) {
+    match known.iter_mut().find(|(k, _)| k == key) {
+        Some((_, values)) => {
+            values.insert(value.to_string());
+        }
+        None => known.push((key.to_string(), BTreeSet::from([value.to_string()]))),
+    }
+}
+
+/// Offers both values of a common on/off pair, unless the core has used a third value for the
+/// option, or else the values the core has written to any of its options files. Anything else
+/// is read-only, since a value the core doesn't accept is reset to its default.
+fn option_kind(value: &str, known: &BTreeSet<String>) -> CoreOptionKind {
+    const TOGGLES: [(&str, &str); 4] = [
+        ("enabled", "disabled"),
+        ("true", "false"),
+        ("on", "off"),
+        ("yes", "no"),
+    ];
+    for (on, off) in TOGGLES {
+        let is_toggle = |v: &str| v.eq_ignore_ascii_case(on) || v.eq_ignore_ascii_case(off);
+        if is_toggle(value) && known.iter().all(|v| is_toggle(v)) {
+            // Keep the case the core uses, e.g. `On`/`Off` or `ON`/`OFF`
+            let case = |s: &str| {
+                if value.chars().all(|c| c.is_ascii_uppercase()) {
+                    s.to_ascii_uppercase()
+                } else if value.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    s[..1].to_ascii_uppercase() + &s[1..]
+                } else {
+                    s.to_string()
+                }
+            };
+            return CoreOptionKind::Toggle {
+                on: case(on),
+                off: case(off),
+            };
+        }
+    }
+
+    let mut values: Vec<String> = known.iter().cloned().collect();
+    if !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+    }
+    if values.len() <= 1 {
+        return CoreOptionKind::ReadOnly;
+    }
+    let index = values.iter().position(|v| v == value).unwrap_or_default();
+    CoreOptionKind::Select(index, values)
+}
+
+/// Turns an option key into a readable name, e.g. `mgba_solar_sensor_level` with prefix `mgba_`
+/// becomes `Solar sensor level`.
+pub fn option_name(key: &str, prefix: &str) -> String {
+    let name = key.strip_prefix(prefix).unwrap_or(key).replace('_', " ");
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().collect::<String>() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// Returns the prefix shared by all option keys, up to and including the last `_`.
+pub fn common_prefix<'a>(keys: impl IntoIterator<Item = &'a str>) -> String {
+    let mut keys = keys.into_iter();
+    let Some(first) = keys.next() else {
+        return String::new();
+    };
+    let mut prefix = first;
+    for key in keys {
+        let len = prefix
+            .char_indices()
+            .zip(key.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((i, c), _)| i + c.len_utf8())
+            .unwrap_or_default();
+        prefix = &prefix[..len];
+    }
+    match prefix.rfind('_') {
+        Some(i) => prefix[..=i].to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    const OPTIONS: &str = r#"mgba_audio_low_pass_filter = "disabled"
+mgba_frameskip = "0"
+mgba_gb_model = "Autodetect"
+mgba_solar_sensor_level = "0"
+mgba_use_bios = "ON"
+"#;
+
+    fn info() -> CoreInfo {
+        CoreInfo {
+            corename: "mGBA".to_string(),
+            databases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_opt_roundtrip() {
+        let mut config = RetroArchConfig::parse(OPTIONS);
+        assert_eq!(config.get("mgba_gb_model"), Some("Autodetect"));
+        config.set("mgba_frameskip", "2");
+        assert_eq!(
+            config.to_string(),
+            OPTIONS.replace("mgba_frameskip = \"0\"", "mgba_frameskip = \"2\"")
+        );
+    }
+
+    #[test]
+    fn test_option_kind() {
+        assert_eq!(
+            option_kind("disabled", &BTreeSet::new()),
+            CoreOptionKind::Toggle {
+                on: "enabled".to_string(),
+                off: "disabled".to_string()
+            }
+        );
+        assert_eq!(
+            option_kind("ON", &BTreeSet::new()),
+            CoreOptionKind::Toggle {
+                on: "ON".to_string(),
+                off: "OFF".to_string()
+            }
+        );
+        assert_eq!(
+            option_kind("Off", &BTreeSet::new()),
+            CoreOptionKind::Toggle {
+                on: "On".to_string(),
+                off: "Off".to_string()
+            }
+        );
+        // A third value means the option isn't a toggle
+        assert_eq!(
+            option_kind(
+                "disabled",
+                &BTreeSet::from(["disabled".to_string(), "auto".to_string()])
+            ),
+            CoreOptionKind::Select(1, vec!["auto".to_string(), "disabled".to_string()])
+        );
+        // Other values can't be guessed from a single one
+        assert_eq!(
+            option_kind("3", &BTreeSet::from(["3".to_string()])),
+            CoreOptionKind::ReadOnly
+        );
+        assert_eq!(
+            option_kind("Autodetect", &BTreeSet::new()),
+            CoreOptionKind::ReadOnly
+        );
+        assert_eq!(
+            option_kind(
+                "Game Boy",
+                &BTreeSet::from(["Autodetect".to_string(), "Game Boy".to_string()])
+            ),
+            CoreOptionKind::Select(1, vec!["Autodetect".to_string(), "Game Boy".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_option_name() {
+        let prefix = common_prefix(["mgba_frameskip", "mgba_gb_model", "mgba_gb_colors"]);
+        assert_eq!(prefix, "mgba_");
+        assert_eq!(option_name("mgba_gb_model", &prefix), "Gb model");
+        assert_eq!(common_prefix(["frameskip"]), "");
+        assert_eq!(common_prefix(Vec::<&str>::new()), "");
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = temp_dir("core-options-load");
+        let rom = Path::new("Roms/GBA/Game.gba");
+        let core_path = dir.join("mGBA/mGBA.opt");
+        let game_path = dir.join("mGBA/Game.opt");
+        RetroArchConfig::parse(OPTIONS).save(&core_path).unwrap();
+
+        // Game options start as a copy of the core options
+        let mut options = CoreOptions::load_in(&dir, &info(), rom, CoreOptionsScope::Game).unwrap();
+        assert_eq!(options.get("mgba_gb_model"), Some("Autodetect"));
+        options.set("mgba_gb_model", "Game Boy");
+        options.save(false).unwrap();
+        assert!(game_path.exists());
+        assert_eq!(
+            RetroArchConfig::load(&core_path)
+                .unwrap()
+                .get("mgba_gb_model"),
+            Some("Autodetect")
+        );
+
+        // Values from other games are offered as choices
+        let options = CoreOptions::load_in(&dir, &info(), rom, CoreOptionsScope::Core).unwrap();
+        assert_eq!(
+            options.kind("mgba_gb_model"),
+            Some(CoreOptionKind::Select(
+                0,
+                vec!["Autodetect".to_string(), "Game Boy".to_string()]
+            ))
+        );
+
+        // Options saved while running are applied afterwards
+        let mut options = CoreOptions::load_in(&dir, &info(), rom, CoreOptionsScope::Game).unwrap();
+        options.set("mgba_frameskip", "1");
+        options.save(true).unwrap();
+        assert_eq!(
+            RetroArchConfig::load(&game_path)
+                .unwrap()
+                .get("mgba_frameskip"),
+            Some("0")
+        );
+        let options = CoreOptions::load_in(&dir, &info(), rom, CoreOptionsScope::Game).unwrap();
+        assert_eq!(options.get("mgba_frameskip"), Some("1"));
+
+        CoreOptions::apply_pending_in(&dir, &info()).unwrap();
+        assert!(!dir.join("mGBA/Game.opt.pending").exists());
+        assert_eq!(
+            RetroArchConfig::load(&game_path)
+                .unwrap()
+                .get("mgba_frameskip"),
+            Some("1")
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,10 +1,11 @@
 use std::{
+    collections::HashSet,
     path::{Path, PathBuf},
     rc::Rc,
 };
 
 use anyhow::{Context, Result};
-use chrono::Duration;
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use log::info;
 use rusqlite::{params, Connection, OptionalExtension};
 use rusqlite_migration::{Migrations, M};
@@ -26,6 +27,16 @@ pub struct Game {
     pub last_played: i64,
 }
 
+/// Totals over the whole library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseStats {
+    pub games: i64,
+    /// Games that have been played at least once.
+    pub played: i64,
+    pub play_count: i64,
+    pub play_time: Duration,
+}
+
 impl Database {
     pub fn new() -> Result<Self> {
         if !ALLIUM_DATABASE.exists() {
@@ -82,6 +93,21 @@ CREATE TABLE IF NOT EXISTS guides (
     id INTEGER PRIMARY KEY,
     path TEXT NOT NULL UNIQUE,
     cursor INTEGER NOT NULL
+);"),
+M::up("
+CREATE TABLE IF NOT EXISTS hidden (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE
+);"),
+M::up("
+ALTER TABLE games ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN last_played_at INTEGER;"),
+M::up("
+CREATE TABLE IF NOT EXISTS play_sessions (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    start INTEGER NOT NULL,
+    duration INTEGER NOT NULL
 );"),
         ])
     }
@@ -96,15 +122,86 @@ CREATE TABLE IF NOT EXISTS guides (
     }
 
     pub fn update_game_path(&self, old: &Path, new: &Path) -> Result<()> {
-        let conn = self.conn.as_ref().unwrap();
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?;
+        Self::move_game(&tx, old, new)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Moves a game to a new path and updates its name and image, all in one transaction.
+    pub fn rename_game(
+        &self,
+        old: &Path,
+        new: &Path,
+        name: &str,
+        image: Option<&Path>,
+    ) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?;
+        Self::move_game(&tx, old, new)?;
+        tx.execute(
+            "UPDATE games SET name = ?, image = ? WHERE path = ?",
+            params![
+                name,
+                image.map(|p| p.display().to_string()),
+                new.display().to_string()
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn move_game(conn: &Connection, old: &Path, new: &Path) -> Result<()> {
         let mut stmt = conn.prepare("UPDATE games SET path = ? WHERE path = ?")?;
         stmt.execute(params![
             new.display().to_string(),
             old.display().to_string()
         ])?;
+        let mut stmt = conn.prepare("UPDATE hidden SET path = ? WHERE path = ?")?;
+        stmt.execute(params![
+            new.display().to_string(),
+            old.display().to_string()
+        ])?;
         Ok(())
     }
 
+    /// Hides a file or directory from the launcher.
+    pub fn hide(&self, path: &Path) -> Result<()> {
+        let conn = self.conn.as_ref().unwrap();
+        conn.execute(
+            "INSERT INTO hidden (path) VALUES (?) ON CONFLICT(path) DO NOTHING",
+            params![path.display().to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Unhides a file or directory. Does nothing if it isn't hidden.
+    pub fn unhide(&self, path: &Path) -> Result<()> {
+        let conn = self.conn.as_ref().unwrap();
+        conn.execute(
+            "DELETE FROM hidden WHERE path = ?",
+            params![path.display().to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Selects the paths of all hidden files and directories.
+    pub fn select_hidden(&self) -> Result<HashSet<PathBuf>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT path FROM hidden")?;
+
+        let rows = stmt.query_map([], |row| Ok(PathBuf::from(row.get::<_, String>(0)?)))?;
+
+        let mut paths = HashSet::new();
+        for row in rows {
+            paths.insert(row?);
+        }
+
+        Ok(paths)
+    }
+
     pub fn update_games(&self, games: &[Game]) -> Result<()> {
         let conn = self.conn.as_ref().unwrap();
         let mut stmt = conn.prepare("
@@ -133,6 +230,24 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, play_count = ?, play_time =
         Ok(())
     }
 
+    /// Selects the paths of all games that have been played at least once.
+    pub fn select_played(&self) -> Result<HashSet<PathBuf>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT path FROM games WHERE play_count > 0")?;
+
+        let rows = stmt.query_map([], |row| Ok(PathBuf::from(row.get::<_, String>(0)?)))?;
+
+        let mut paths = HashSet::new();
+        for row in rows {
+            paths.insert(row?);
+        }
+
+        Ok(paths)
+    }
+
     /// Selects played games sorted by most play time first.
     pub fn select_most_played(&self, limit: i64) -> Result<Vec<Game>> {
         let mut stmt = self.conn.as_ref().unwrap().prepare("SELECT name, path, image, play_count, play_time, last_played FROM games WHERE play_time > 0 ORDER BY play_time DESC LIMIT ?")?;
@@ -219,6 +334,48 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, play_count = ?, play_time =
         Ok(game)
     }
 
+    /// Selects every game, sorted by path.
+    pub fn select_all_games(&self) -> Result<Vec<Game>> {
+        let mut stmt = self.conn.as_ref().unwrap().prepare(
+            "SELECT name, path, image, play_count, play_time, last_played FROM games ORDER BY path",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(Game {
+                name: row.get(0)?,
+                path: PathBuf::from(row.get::<_, String>(1)?),
+                image: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+                play_count: row.get(3)?,
+                play_time: Duration::seconds(row.get(4)?),
+                last_played: row.get(5)?,
+            })
+        })?;
+
+        let mut games = Vec::new();
+        for row in rows {
+            games.push(row?);
+        }
+
+        Ok(games)
+    }
+
+    pub fn select_stats(&self) -> Result<DatabaseStats> {
+        let stats = self.conn.as_ref().unwrap().query_row(
+            "SELECT COUNT(*), COALESCE(SUM(play_count > 0), 0), COALESCE(SUM(play_count), 0), COALESCE(SUM(play_time), 0) FROM games",
+            [],
+            |row| {
+                Ok(DatabaseStats {
+                    games: row.get(0)?,
+                    played: row.get(1)?,
+                    play_count: row.get(2)?,
+                    play_time: Duration::seconds(row.get(3)?),
+                })
+            },
+        )?;
+
+        Ok(stats)
+    }
+
     /// Increment the play count of a game, inserting a new row if it doesn't exist.
     pub fn increment_play_count(
         &self,
@@ -242,9 +399,9 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         ])?;
 
         let mut stmt = self.conn.as_ref().unwrap().prepare(
-            "UPDATE games SET last_played = (SELECT MAX(last_played) FROM games) + 1 WHERE path = ?",
+            "UPDATE games SET last_played = (SELECT MAX(last_played) FROM games) + 1, last_played_at = ? WHERE path = ?",
         )?;
-        stmt.execute([path.display().to_string()])?;
+        stmt.execute(params![Utc::now().timestamp(), path.display().to_string()])?;
 
         Ok(())
     }
@@ -262,6 +419,121 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         Ok(())
     }
 
+    /// Returns when a game was last launched, if it has been launched since this was tracked.
+    pub fn select_last_played_at(&self, path: &Path) -> Result<Option<DateTime<Utc>>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT last_played_at FROM games WHERE path = ?")?;
+
+        let timestamp: Option<Option<i64>> = stmt
+            .query_row([path.display().to_string()], |row| row.get(0))
+            .optional()?;
+
+        Ok(timestamp
+            .flatten()
+            .and_then(|t| Utc.timestamp_opt(t, 0).single()))
+    }
+
+    /// Selects favorite games sorted by name.
+    pub fn select_favorites(&self, limit: i64) -> Result<Vec<Game>> {
+        let mut stmt = self.conn.as_ref().unwrap().prepare("SELECT name, path, image, play_count, play_time, last_played FROM games WHERE favorite = 1 ORDER BY name LIMIT ?")?;
+
+        let rows = stmt.query_map([limit], |row| {
+            Ok(Game {
+                name: row.get(0)?,
+                path: PathBuf::from(row.get::<_, String>(1)?),
+                image: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+                play_count: row.get(3)?,
+                play_time: Duration::seconds(row.get(4)?),
+                last_played: row.get(5)?,
+            })
+        })?;
+
+        let mut games = Vec::new();
+        for row in rows {
+            games.push(row?);
+        }
+
+        Ok(games)
+    }
+
+    pub fn is_favorite(&self, path: &Path) -> Result<bool> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT favorite FROM games WHERE path = ?")?;
+
+        let favorite = stmt
+            .query_row([path.display().to_string()], |row| row.get(0))
+            .optional()?;
+
+        Ok(favorite.unwrap_or(false))
+    }
+
+    /// Marks or unmarks a game as favorite, inserting a new row if it doesn't exist.
+    pub fn set_favorite(
+        &self,
+        name: &str,
+        path: &Path,
+        image: Option<&Path>,
+        favorite: bool,
+    ) -> Result<()> {
+        let mut stmt = self.conn.as_ref().unwrap().prepare(
+            "
+INSERT INTO games (name, path, image, play_count, play_time, last_played, favorite)
+VALUES (?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT(path) DO UPDATE SET favorite = ?;",
+        )?;
+        stmt.execute(params![
+            name,
+            path.display().to_string(),
+            image.map(|p| p.display().to_string()),
+            0,
+            0,
+            0,
+            favorite,
+            favorite
+        ])?;
+
+        Ok(())
+    }
+
+    /// Records a single play session of a game.
+    pub fn add_play_session(
+        &self,
+        path: &Path,
+        start: DateTime<Utc>,
+        duration: Duration,
+    ) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("INSERT INTO play_sessions (path, start, duration) VALUES (?, ?, ?)")?;
+
+        stmt.execute(params![
+            path.display().to_string(),
+            start.timestamp(),
+            duration.num_seconds()
+        ])?;
+
+        Ok(())
+    }
+
+    /// Returns the total play time of all sessions started at or after `since`.
+    pub fn select_play_time_since(&self, since: DateTime<Utc>) -> Result<Duration> {
+        let seconds: i64 = self.conn.as_ref().unwrap().query_row(
+            "SELECT COALESCE(SUM(duration), 0) FROM play_sessions WHERE start >= ?",
+            [since.timestamp()],
+            |row| row.get(0),
+        )?;
+
+        Ok(Duration::seconds(seconds))
+    }
+
     pub fn get_guide_cursor(&self, path: &Path) -> Result<u64> {
         let mut stmt = self
             .conn
@@ -441,4 +713,144 @@ mod tests {
         let results = database.search("Ga", 100).unwrap();
         assert_eq!(results[0].path, games[0].path);
     }
+
+    #[test]
+    fn test_hidden() {
+        let database = Database::in_memory().unwrap();
+
+        let game = Game {
+            name: "Game One".to_string(),
+            path: PathBuf::from("test_directory/Game One.rom"),
+            image: None,
+            play_count: 0,
+            play_time: Duration::zero(),
+            last_played: 0,
+        };
+        database.update_games(std::slice::from_ref(&game)).unwrap();
+
+        database.hide(&game.path).unwrap();
+        database.hide(&game.path).unwrap();
+        assert!(database.select_hidden().unwrap().contains(&game.path));
+
+        let renamed = PathBuf::from("test_directory/Game Two.rom");
+        database.update_game_path(&game.path, &renamed).unwrap();
+        let hidden = database.select_hidden().unwrap();
+        assert_eq!(hidden.len(), 1);
+        assert!(hidden.contains(&renamed));
+        assert!(database
+            .select_game(&renamed.display().to_string())
+            .unwrap()
+            .is_some());
+
+        database.unhide(&renamed).unwrap();
+        assert!(database.select_hidden().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_play_sessions() {
+        let database = Database::in_memory().unwrap();
+
+        let path = PathBuf::from("test_directory/Game One.rom");
+        let now = Utc::now();
+        database
+            .add_play_session(&path, now - Duration::days(1), Duration::minutes(30))
+            .unwrap();
+        database
+            .add_play_session(&path, now, Duration::minutes(5))
+            .unwrap();
+        database
+            .add_play_session(&path, now, Duration::minutes(10))
+            .unwrap();
+
+        assert_eq!(
+            database
+                .select_play_time_since(now - Duration::hours(1))
+                .unwrap(),
+            Duration::minutes(15)
+        );
+        assert_eq!(
+            database
+                .select_play_time_since(now + Duration::hours(1))
+                .unwrap(),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let database = Database::in_memory().unwrap();
+        assert_eq!(
+            database.select_stats().unwrap(),
+            DatabaseStats {
+                games: 0,
+                played: 0,
+                play_count: 0,
+                play_time: Duration::zero(),
+            }
+        );
+
+        let games = vec![
+            Game {
+                name: "Game Two".to_string(),
+                path: PathBuf::from("test_directory/Game Two.rom"),
+                image: None,
+                play_count: 3,
+                play_time: Duration::minutes(20),
+                last_played: 2,
+            },
+            Game {
+                name: "Game One".to_string(),
+                path: PathBuf::from("test_directory/Game One.rom"),
+                image: None,
+                play_count: 0,
+                play_time: Duration::zero(),
+                last_played: 0,
+            },
+        ];
+        database.update_games(&games).unwrap();
+
+        assert_eq!(
+            database.select_stats().unwrap(),
+            DatabaseStats {
+                games: 2,
+                played: 1,
+                play_count: 3,
+                play_time: Duration::minutes(20),
+            }
+        );
+        assert_eq!(
+            database.select_all_games().unwrap(),
+            vec![games[1].clone(), games[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_favorites() {
+        let database = Database::in_memory().unwrap();
+
+        let path = PathBuf::from("test_directory/Game One.rom");
+        assert!(!database.is_favorite(&path).unwrap());
+        assert!(database.select_last_played_at(&path).unwrap().is_none());
+
+        database
+            .set_favorite("Game One", &path, None, true)
+            .unwrap();
+        assert!(database.is_favorite(&path).unwrap());
+        let favorites = database.select_favorites(10).unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].path, path);
+        assert!(database.select_last_played(10).unwrap().is_empty());
+
+        database
+            .increment_play_count("Game One", &path, None)
+            .unwrap();
+        assert!(database.is_favorite(&path).unwrap());
+        assert!(database.select_last_played_at(&path).unwrap().is_some());
+
+        database
+            .set_favorite("Game One", &path, None, false)
+            .unwrap();
+        assert!(!database.is_favorite(&path).unwrap());
+        assert!(database.select_favorites(10).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,191 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_DATETIME_SETTINGS;
+
+/// Timezone and clock format. The system clock and RTC are always kept in UTC, and times are
+/// converted with the bundled tz database when displayed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateTimeSettings {
+    /// IANA timezone name, e.g. `Asia/Singapore`.
+    pub timezone: String,
+    pub use_24_hour: bool,
+}
+
+impl Default for DateTimeSettings {
+    fn default() -> Self {
+        Self {
+            timezone: "UTC".to_string(),
+            use_24_hour: true,
+        }
+    }
+}
+
+impl DateTimeSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_DATETIME_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            if let Ok(json) = fs::read_to_string(ALLIUM_DATETIME_SETTINGS.as_path()) {
+                if let Ok(json) = serde_json::from_str(&json) {
+                    return Ok(json);
+                }
+            }
+            warn!("failed to read state file, removing");
+            fs::remove_file(ALLIUM_DATETIME_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string(&self).unwrap();
+        File::create(ALLIUM_DATETIME_SETTINGS.as_path())?.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the configured timezone, falling back to UTC if it is unknown.
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// Returns the current local time.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.tz())
+    }
+
+    /// Converts a UTC timestamp to local time.
+    pub fn local<T: TimeZone>(&self, time: &DateTime<T>) -> DateTime<Tz> {
+        time.with_timezone(&self.tz())
+    }
+
+    /// Formats the time of day, e.g. `14:05` or `2:05 PM`.
+    pub fn format_time<T: TimeZone>(&self, time: &DateTime<T>) -> String {
+        self.local(time).format(self.time_format(false)).to_string()
+    }
+
+    /// Formats a date and time, e.g. `2023-07-01 14:05`. Seconds are included if `seconds` is
+    /// true.
+    pub fn format_timestamp<T: TimeZone>(&self, time: &DateTime<T>, seconds: bool) -> String {
+        self.local(time)
+            .format(&format!("%Y-%m-%d {}", self.time_format(seconds)))
+            .to_string()
+    }
+
+    /// Formats a date and time for use in file names, e.g. `2023-07-01_14-05-09`.
+    pub fn format_file_name<T: TimeZone>(&self, time: &DateTime<T>) -> String {
+        self.local(time).format("%Y-%m-%d_%H-%M-%S").to_string()
+    }
+
+    fn time_format(&self, seconds: bool) -> &'static str {
+        match (self.use_24_hour, seconds) {
+            (true, false) => "%H:%M",
+            (true, true) => "%H:%M:%S",
+            (false, false) => "%-I:%M %p",
+            (false, true) => "%-I:%M:%S %p",
+        }
+    }
+
+    /// Sets the system clock and RTC to the given local date and time.
+    pub fn set_system_time(&self, time: NaiveDateTime) -> Result<()> {
+        let time = self
+            .tz()
+            .from_local_datetime(&time)
+            .earliest()
+            .ok_or_else(|| anyhow!("{} does not exist in {}", time, self.timezone))?
+            .with_timezone(&Utc);
+        info!("setting system time to {}", time.format("%F %T"));
+
+        let status = Command::new("date")
+            .env("TZ", "UTC")
+            .arg("-s")
+            .arg(time.format("%F %T").to_string())
+            .status()?;
+        if !status.success() {
+            bail!("failed to set system time");
+        }
+
+        let status = Command::new("/sbin/hwclock").arg("-w").arg("-u").status()?;
+        if !status.success() {
+            warn!("failed to write time to RTC");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the names of all timezones in the bundled tz database, sorted alphabetically.
+pub fn timezones() -> Vec<String> {
+    let mut timezones: Vec<String> = TZ_VARIANTS.iter().map(|tz| tz.name().to_string()).collect();
+    timezones.sort();
+    timezones
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = Utc.with_ymd_and_hms(2023, 7, 1, 6, 5, 9).unwrap();
+
+        let mut settings = DateTimeSettings::new();
+        assert_eq!(settings.format_time(&time), "06:05");
+        assert_eq!(
+            settings.format_timestamp(&time, true),
+            "2023-07-01 06:05:09"
+        );
+
+        settings.timezone = "America/New_York".to_string();
+        settings.use_24_hour = false;
+        assert_eq!(settings.format_time(&time), "2:05 AM");
+        assert_eq!(
+            settings.format_timestamp(&time, false),
+            "2023-07-01 2:05 AM"
+        );
+        assert_eq!(settings.format_file_name(&time), "2023-07-01_02-05-09");
+
+        settings.timezone = "Asia/Tokyo".to_string();
+        assert_eq!(
+            settings.format_timestamp(&time, false),
+            "2023-07-01 3:05 PM"
+        );
+    }
+
+    #[test]
+    fn test_tz() {
+        let mut settings = DateTimeSettings::new();
+        settings.timezone = "Not/A_Timezone".to_string();
+        assert_eq!(settings.tz(), Tz::UTC);
+
+        settings.timezone = "Europe/London".to_string();
+        let local = NaiveDate::from_ymd_opt(2023, 7, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let utc = settings
+            .tz()
+            .from_local_datetime(&local)
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(utc, Utc.with_ymd_and_hms(2023, 7, 1, 11, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_timezones() {
+        let timezones = timezones();
+        assert!(timezones.contains(&"UTC".to_string()));
+        assert!(timezones.contains(&"Asia/Singapore".to_string()));
+        assert!(timezones.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -8,7 +8,10 @@ use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::alliumd::AlliumDRequest;
 use crate::constants::{ALLIUM_GAMES_DIR, ALLIUM_GAME_INFO};
+use crate::netplay::Netplay;
+use crate::profile::Profiles;
 
 #[derive(Debug, Serialize, Deserialize)]
 /// Information about a game. Used to restore a game after a restart, and to calculate playtime.
@@ -29,6 +32,15 @@ pub struct GameInfo {
     pub guide: Option<PathBuf>,
     /// Start time. Used to measure playtime.
     pub start_time: DateTime<Utc>,
+    /// Name of the profile that was active when the game was launched.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Netplay session to start the game with.
+    #[serde(default)]
+    pub netplay: Option<Netplay>,
+    /// Whether to launch the game again once it exits, e.g. to start a netplay session.
+    #[serde(default)]
+    pub relaunch: bool,
 }
 
 impl Default for GameInfo {
@@ -42,6 +54,9 @@ impl Default for GameInfo {
             image: None,
             guide: None,
             start_time: Utc::now(),
+            profile: None,
+            netplay: None,
+            relaunch: false,
         }
     }
 }
@@ -57,6 +72,7 @@ impl GameInfo {
         has_menu: bool,
     ) -> Self {
         let guide = find_guide(&path);
+        let profile = Profiles::load().ok().map(|p| p.active);
 
         Self {
             name,
@@ -67,6 +83,9 @@ impl GameInfo {
             image,
             guide,
             start_time: Utc::now(),
+            profile,
+            netplay: None,
+            relaunch: false,
         }
     }
 
@@ -99,10 +118,32 @@ impl GameInfo {
         Ok(())
     }
 
+    /// Asks alliumd to quit RetroArch and launch the current game again, e.g. to apply settings
+    /// that the core only reads at startup.
+    pub async fn request_relaunch() -> Result<()> {
+        AlliumDRequest::Relaunch { netplay: None }
+            .send()
+            .await?
+            .into_result()?;
+        Ok(())
+    }
+
+    /// Returns the RetroArch core the game runs with, if any.
+    pub fn core(&self) -> Option<&str> {
+        if self.has_menu {
+            self.args.first().map(String::as_str)
+        } else {
+            None
+        }
+    }
+
     /// Returns a command to run the game.
     pub fn command(self) -> Command {
         let mut command = Command::new(self.command);
         command.args(self.args);
+        if let Some(netplay) = self.netplay {
+            command.args(netplay.args());
+        }
         command
     }
 
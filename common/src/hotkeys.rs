@@ -0,0 +1,470 @@
+use std::fs;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use enum_map::EnumMap;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_CONFIG_HOTKEYS;
+use crate::platform::{Key, KeyEvent};
+
+/// What a hotkey does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotkeyAction {
+    VolumeUp,
+    VolumeDown,
+    BrightnessUp,
+    BrightnessDown,
+    Screenshot,
+    /// Opens or closes the in-game menu.
+    Menu,
+    /// Puts the device to sleep, or wakes it up.
+    Suspend,
+    /// Quits the game, or shuts down outside of a game.
+    Quit,
+    /// Saves a state in the current slot.
+    QuickSave,
+    /// Loads the state in the current slot.
+    QuickLoad,
+    /// Fast-forwards while the chord is held.
+    FastForward,
+}
+
+/// When a hotkey fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyTrigger {
+    /// Once the chord is complete.
+    Press,
+    /// Once the chord is complete, and again while its last key repeats.
+    Repeat,
+    /// When a key is released, if no other keys were pressed with the chord.
+    Release,
+    /// When a key is long pressed on its own, or for chords of several keys, while the chord is
+    /// held long enough for its last key to repeat.
+    Hold,
+}
+
+impl HotkeyAction {
+    pub fn trigger(self) -> HotkeyTrigger {
+        match self {
+            HotkeyAction::VolumeUp
+            | HotkeyAction::VolumeDown
+            | HotkeyAction::BrightnessUp
+            | HotkeyAction::BrightnessDown
+            | HotkeyAction::FastForward => HotkeyTrigger::Repeat,
+            HotkeyAction::Screenshot | HotkeyAction::QuickSave | HotkeyAction::QuickLoad => {
+                HotkeyTrigger::Press
+            }
+            HotkeyAction::Menu | HotkeyAction::Suspend => HotkeyTrigger::Release,
+            HotkeyAction::Quit => HotkeyTrigger::Hold,
+        }
+    }
+
+    /// Locale key of the action's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            HotkeyAction::VolumeUp => "hotkey-action-volume-up",
+            HotkeyAction::VolumeDown => "hotkey-action-volume-down",
+            HotkeyAction::BrightnessUp => "hotkey-action-brightness-up",
+            HotkeyAction::BrightnessDown => "hotkey-action-brightness-down",
+            HotkeyAction::Screenshot => "hotkey-action-screenshot",
+            HotkeyAction::Menu => "hotkey-action-menu",
+            HotkeyAction::Suspend => "hotkey-action-suspend",
+            HotkeyAction::Quit => "hotkey-action-quit",
+            HotkeyAction::QuickSave => "hotkey-action-quick-save",
+            HotkeyAction::QuickLoad => "hotkey-action-quick-load",
+            HotkeyAction::FastForward => "hotkey-action-fast-forward",
+        }
+    }
+}
+
+/// A chord of keys that triggers an action once all of them are held.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hotkey {
+    pub keys: Vec<Key>,
+    pub action: HotkeyAction,
+}
+
+/// Hotkeys handled by alliumd, read from `config/hotkeys.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hotkeys {
+    #[serde(rename = "hotkey", default)]
+    pub hotkeys: Vec<Hotkey>,
+}
+
+impl Hotkeys {
+    /// Loads the hotkeys, falling back to the defaults if the config is missing.
+    pub fn load() -> Result<Self> {
+        if !ALLIUM_CONFIG_HOTKEYS.exists() {
+            debug!("hotkeys config not found, using defaults");
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(ALLIUM_CONFIG_HOTKEYS.as_path())?)
+    }
+
+    fn parse(config: &str) -> Result<Self> {
+        toml::from_str(config).context("Failed to parse hotkeys.toml.")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = ALLIUM_CONFIG_HOTKEYS.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(ALLIUM_CONFIG_HOTKEYS.as_path(), toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Last modification time of the config, used to reload it when changed.
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(ALLIUM_CONFIG_HOTKEYS.as_path())
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    /// Returns the hotkey completed by `key`: the key is part of the chord, every key of the
+    /// chord is held, and the action fires on one of the given triggers. The longest completed
+    /// chord wins, so that e.g. Menu+Power doesn't also count as Power.
+    fn matching(
+        &self,
+        key: Key,
+        held: &EnumMap<Key, bool>,
+        triggers: &[HotkeyTrigger],
+    ) -> Option<&Hotkey> {
+        let completed = || {
+            self.hotkeys
+                .iter()
+                .filter(|h| h.keys.contains(&key) && h.keys.iter().all(|k| held[*k]))
+        };
+        let longest = completed().map(|h| h.keys.len()).max()?;
+        completed()
+            .filter(|h| h.keys.len() == longest)
+            .find(|h| triggers.contains(&h.action.trigger()))
+    }
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        let hotkey = |keys: &[Key], action| Hotkey {
+            keys: keys.to_vec(),
+            action,
+        };
+        Self {
+            hotkeys: vec![
+                hotkey(&[Key::VolUp], HotkeyAction::VolumeUp),
+                hotkey(&[Key::VolDown], HotkeyAction::VolumeDown),
+                hotkey(&[Key::Select, Key::R2], HotkeyAction::VolumeUp),
+                hotkey(&[Key::Select, Key::L2], HotkeyAction::VolumeDown),
+                hotkey(&[Key::Menu, Key::VolUp], HotkeyAction::BrightnessUp),
+                hotkey(&[Key::Menu, Key::VolDown], HotkeyAction::BrightnessDown),
+                hotkey(&[Key::Start, Key::R2], HotkeyAction::BrightnessUp),
+                hotkey(&[Key::Start, Key::L2], HotkeyAction::BrightnessDown),
+                hotkey(&[Key::Menu, Key::Power], HotkeyAction::Screenshot),
+                hotkey(&[Key::Menu], HotkeyAction::Menu),
+                hotkey(&[Key::Power], HotkeyAction::Suspend),
+                hotkey(&[Key::Power], HotkeyAction::Quit),
+                hotkey(&[Key::Menu, Key::R], HotkeyAction::QuickSave),
+                hotkey(&[Key::Menu, Key::L], HotkeyAction::QuickLoad),
+                hotkey(&[Key::Menu, Key::Select], HotkeyAction::FastForward),
+            ],
+        }
+    }
+}
+
+/// Formats a chord for display, e.g. `Menu + R`.
+pub fn chord_name(keys: &[Key]) -> String {
+    keys.iter()
+        .map(|k| format!("{:?}", k))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Matches the events of an [`InputProcessor`] against hotkey chords. Chords fire on the
+/// processor's `Chord` events and single keys on `Pressed` while no other key is held, so that
+/// e.g. Menu+VolUp doesn't also count as VolUp. Single keys are held on `LongPress`, so the
+/// processor's timings apply.
+///
+/// [`InputProcessor`]: crate::platform::InputProcessor
+#[derive(Debug, Default)]
+pub struct HotkeyMatcher {
+    held: EnumMap<Key, bool>,
+    /// Keys pressed since all keys were last released.
+    pressed: EnumMap<Key, bool>,
+    /// The last key pressed, which completed the chord that follows it.
+    last_pressed: Option<Key>,
+    /// Whether a hotkey has fired since all keys were last released.
+    fired: bool,
+}
+
+impl HotkeyMatcher {
+    pub fn held(&self) -> &EnumMap<Key, bool> {
+        &self.held
+    }
+
+    /// Updates the held keys with the event, and returns the action it triggers.
+    pub fn handle(&mut self, hotkeys: &Hotkeys, event: KeyEvent) -> Option<HotkeyAction> {
+        let triggers = [HotkeyTrigger::Press, HotkeyTrigger::Repeat];
+        let action = match event {
+            KeyEvent::Pressed(key) => {
+                let is_alone = !self.held.values().any(|held| *held);
+                if is_alone {
+                    self.pressed = EnumMap::default();
+                    self.fired = false;
+                }
+                self.held[key] = true;
+                self.pressed[key] = true;
+                self.last_pressed = Some(key);
+                if is_alone {
+                    hotkeys
+                        .matching(key, &self.held, &triggers)
+                        .map(|h| h.action)
+                } else {
+                    None
+                }
+            }
+            KeyEvent::Chord(_) => {
+                let key = self.last_pressed?;
+                hotkeys
+                    .matching(key, &self.held, &triggers)
+                    .map(|h| h.action)
+            }
+            KeyEvent::Autorepeat(key) => hotkeys
+                .matching(
+                    key,
+                    &self.held,
+                    &[HotkeyTrigger::Repeat, HotkeyTrigger::Hold],
+                )
+                // Single keys are held with long presses instead
+                .filter(|h| h.action.trigger() == HotkeyTrigger::Repeat || h.keys.len() > 1)
+                .map(|h| h.action),
+            KeyEvent::LongPress(key) => hotkeys
+                .hotkeys
+                .iter()
+                .find(|h| h.keys == [key] && h.action.trigger() == HotkeyTrigger::Hold)
+                .map(|h| h.action),
+            KeyEvent::Released(key) => {
+                let action = if self.fired {
+                    None
+                } else {
+                    // Only the chord's keys may have been pressed
+                    let pressed = &self.pressed;
+                    hotkeys
+                        .hotkeys
+                        .iter()
+                        .filter(|h| h.action.trigger() == HotkeyTrigger::Release)
+                        .find(|h| {
+                            h.keys.contains(&key)
+                                && pressed
+                                    .iter()
+                                    .all(|(k, pressed)| *pressed == h.keys.contains(&k))
+                        })
+                        .map(|h| h.action)
+                };
+                self.held[key] = false;
+                action
+            }
+            KeyEvent::DoubleTap(_) => None,
+        };
+        if action.is_some() {
+            self.fired = true;
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::platform::InputProcessor;
+
+    use super::*;
+
+    /// Runs raw events through an input processor and the matcher, 10ms apart.
+    fn run(hotkeys: &Hotkeys, events: &[KeyEvent]) -> Vec<HotkeyAction> {
+        let timed = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| (i as u64 * 10, *event))
+            .collect::<Vec<_>>();
+        run_timed(hotkeys, &timed)
+    }
+
+    /// Runs raw events, each with its time in milliseconds, through an input processor and the
+    /// matcher.
+    fn run_timed(hotkeys: &Hotkeys, events: &[(u64, KeyEvent)]) -> Vec<HotkeyAction> {
+        let start = Instant::now();
+        let mut input = InputProcessor::default();
+        let mut matcher = HotkeyMatcher::default();
+        events
+            .iter()
+            .flat_map(|(ms, event)| input.process(*event, start + Duration::from_millis(*ms)))
+            .filter_map(|event| matcher.handle(hotkeys, event))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = include_str!("../../assets/root/.allium/config/hotkeys.toml");
+        assert_eq!(Hotkeys::parse(config).unwrap(), Hotkeys::default());
+    }
+
+    #[test]
+    fn test_parse() {
+        let hotkeys = Hotkeys::parse(
+            r#"
+[[hotkey]]
+keys = ["Menu", "X"]
+action = "quick-save"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hotkeys.hotkeys,
+            vec![Hotkey {
+                keys: vec![Key::Menu, Key::X],
+                action: HotkeyAction::QuickSave,
+            }]
+        );
+
+        assert!(Hotkeys::parse("").unwrap().hotkeys.is_empty());
+        assert!(Hotkeys::parse("[[hotkey]]\nkeys = [\"Menu\"]\naction = \"nope\"\n").is_err());
+
+        let hotkeys = Hotkeys::default();
+        assert_eq!(
+            Hotkeys::parse(&toml::to_string(&hotkeys).unwrap()).unwrap(),
+            hotkeys
+        );
+    }
+
+    #[test]
+    fn test_chords() {
+        use KeyEvent::*;
+        let hotkeys = Hotkeys::default();
+
+        assert_eq!(
+            run(&hotkeys, &[Pressed(Key::Menu), Pressed(Key::R)]),
+            vec![HotkeyAction::QuickSave]
+        );
+
+        // Pressing the modifier last completes the chord too
+        assert_eq!(
+            run(&hotkeys, &[Pressed(Key::L), Pressed(Key::Menu)]),
+            vec![HotkeyAction::QuickLoad]
+        );
+
+        // Unrelated keys don't match
+        assert!(run(&hotkeys, &[Pressed(Key::Menu), Pressed(Key::A)]).is_empty());
+        assert!(run(&hotkeys, &[Pressed(Key::R), Released(Key::R)]).is_empty());
+
+        // Repeating actions fire again while held
+        assert_eq!(
+            run(
+                &hotkeys,
+                &[Pressed(Key::Select), Pressed(Key::R2), Autorepeat(Key::R2)]
+            ),
+            vec![HotkeyAction::VolumeUp, HotkeyAction::VolumeUp]
+        );
+        assert!(run(&hotkeys, &[Pressed(Key::Start), Autorepeat(Key::Start)]).is_empty());
+
+        // The longest chord wins
+        assert_eq!(
+            run(&hotkeys, &[Pressed(Key::Menu), Pressed(Key::VolUp)]),
+            vec![HotkeyAction::BrightnessUp]
+        );
+    }
+
+    #[test]
+    fn test_release_and_hold() {
+        use KeyEvent::*;
+        let hotkeys = Hotkeys::default();
+
+        // Menu fires when released alone
+        assert_eq!(
+            run(&hotkeys, &[Pressed(Key::Menu), Released(Key::Menu)]),
+            vec![HotkeyAction::Menu]
+        );
+        assert!(run(
+            &hotkeys,
+            &[
+                Pressed(Key::Menu),
+                Pressed(Key::A),
+                Released(Key::A),
+                Released(Key::Menu)
+            ]
+        )
+        .is_empty());
+        assert_eq!(
+            run(
+                &hotkeys,
+                &[
+                    Pressed(Key::Menu),
+                    Pressed(Key::R),
+                    Released(Key::R),
+                    Released(Key::Menu)
+                ]
+            ),
+            vec![HotkeyAction::QuickSave]
+        );
+
+        // Power suspends when tapped, and quits when long pressed but not when repeated
+        assert_eq!(
+            run(&hotkeys, &[Pressed(Key::Power), Released(Key::Power)]),
+            vec![HotkeyAction::Suspend]
+        );
+        assert_eq!(
+            run(
+                &hotkeys,
+                &[
+                    Pressed(Key::Power),
+                    Autorepeat(Key::Power),
+                    Released(Key::Power)
+                ]
+            ),
+            vec![HotkeyAction::Suspend]
+        );
+        assert_eq!(
+            run_timed(
+                &hotkeys,
+                &[
+                    (0, Pressed(Key::Power)),
+                    (400, Autorepeat(Key::Power)),
+                    (600, Released(Key::Power))
+                ]
+            ),
+            vec![HotkeyAction::Quit]
+        );
+
+        // Menu+Power takes a screenshot without suspending or quitting
+        assert_eq!(
+            run_timed(
+                &hotkeys,
+                &[
+                    (0, Pressed(Key::Menu)),
+                    (10, Pressed(Key::Power)),
+                    (400, Autorepeat(Key::Power)),
+                    (1000, Released(Key::Power)),
+                    (1010, Released(Key::Menu))
+                ]
+            ),
+            vec![HotkeyAction::Screenshot]
+        );
+
+        // A new chord starts once every key has been released
+        assert_eq!(
+            run(
+                &hotkeys,
+                &[
+                    Pressed(Key::Menu),
+                    Pressed(Key::R),
+                    Released(Key::R),
+                    Released(Key::Menu),
+                    Pressed(Key::Menu),
+                    Released(Key::Menu)
+                ]
+            ),
+            vec![HotkeyAction::QuickSave, HotkeyAction::Menu]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_KID_MODE_SETTINGS;
+use crate::database::Database;
+use crate::datetime::DateTimeSettings;
+use crate::platform::Key;
+
+/// Keys that can be part of a kid mode PIN.
+pub const PIN_KEYS: [Key; 10] = [
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::A,
+    Key::B,
+    Key::X,
+    Key::Y,
+    Key::L,
+    Key::R,
+];
+
+/// PIN that leaves kid mode when its settings can't be read or have no PIN, so that the device
+/// can't be locked in kid mode for good. It's shown when asked for.
+pub const RECOVERY_PIN: [Key; 4] = [Key::Up, Key::Up, Key::Down, Key::Down];
+
+/// Restricted mode for handing the device to children. While enabled, only allow-listed
+/// directories are shown, settings and file actions are hidden, and play time can be limited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KidModeSettings {
+    pub enabled: bool,
+    /// Button combination needed to leave kid mode, as physical buttons.
+    pub pin: Vec<Key>,
+    /// Directories that are shown while kid mode is enabled, along with their contents.
+    pub allowed: Vec<PathBuf>,
+    /// Daily play time limit in minutes. 0 means no limit.
+    pub daily_limit: u32,
+    /// Whether the settings couldn't be read or had no PIN, in which case the PIN is
+    /// [`RECOVERY_PIN`].
+    #[serde(skip)]
+    pub is_recovery: bool,
+}
+
+impl KidModeSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the settings. If the file exists but can't be read, kid mode stays enabled with no
+    /// allowed directories rather than being turned off, and the file is kept as it is.
+    pub fn load() -> Result<Self> {
+        Ok(Self::load_from(&ALLIUM_KID_MODE_SETTINGS))
+    }
+
+    fn load_from(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::new();
+        }
+        debug!("found state, loading from file");
+        let settings = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok());
+        match settings {
+            Some(settings) if !settings.enabled || !settings.pin.is_empty() => settings,
+            Some(settings) => {
+                warn!("kid mode is enabled without a PIN, using the recovery PIN");
+                settings.recovery()
+            }
+            None => {
+                warn!("failed to read kid mode settings, keeping kid mode enabled");
+                Self::new().recovery()
+            }
+        }
+    }
+
+    /// Keeps kid mode enabled, but with the recovery PIN.
+    fn recovery(self) -> Self {
+        Self {
+            enabled: true,
+            pin: RECOVERY_PIN.to_vec(),
+            is_recovery: true,
+            ..self
+        }
+    }
+
+    /// Saves the settings. They're written to a temporary file first, so that losing power
+    /// midway leaves the old settings in place rather than a truncated file.
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&ALLIUM_KID_MODE_SETTINGS)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string(&self).unwrap();
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Returns true if the path should be shown. Paths leading to an allowed directory are shown
+    /// so that it can be navigated to.
+    pub fn is_visible(&self, path: &Path) -> bool {
+        !self.enabled
+            || self
+                .allowed
+                .iter()
+                .any(|allowed| path.starts_with(allowed) || allowed.starts_with(path))
+    }
+
+    /// Returns the play time left today, or None if there is no limit. `current` is the play time
+    /// of the running game that hasn't been recorded yet.
+    pub fn play_time_remaining(
+        &self,
+        database: &Database,
+        current: Duration,
+    ) -> Result<Option<Duration>> {
+        if !self.enabled || self.daily_limit == 0 {
+            return Ok(None);
+        }
+
+        let played = database.select_play_time_since(start_of_day())? + current;
+        Ok(Some(Duration::minutes(self.daily_limit as i64) - played))
+    }
+}
+
+/// Returns the start of the current day in local time.
+fn start_of_day() -> DateTime<Utc> {
+    let tz = DateTimeSettings::load().unwrap_or_default().tz();
+    let now = Utc::now().with_timezone(&tz);
+    now.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(tz).earliest())
+        .map_or_else(|| now.with_timezone(&Utc), |t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_is_visible() {
+        let mut settings = KidModeSettings::new();
+        assert!(settings.is_visible(Path::new("Roms/GBA/Game.gba")));
+
+        settings.enabled = true;
+        settings.allowed = vec![PathBuf::from("Roms/GBA/Pokemon")];
+        assert!(settings.is_visible(Path::new("Roms")));
+        assert!(settings.is_visible(Path::new("Roms/GBA")));
+        assert!(settings.is_visible(Path::new("Roms/GBA/Pokemon/Red.gba")));
+        assert!(!settings.is_visible(Path::new("Roms/GBA/Zelda.gba")));
+        assert!(!settings.is_visible(Path::new("Roms/GB")));
+    }
+
+    #[test]
+    fn test_play_time_remaining() {
+        let database = Database::in_memory().unwrap();
+        let mut settings = KidModeSettings::new();
+        settings.daily_limit = 30;
+        assert_eq!(
+            settings
+                .play_time_remaining(&database, Duration::zero())
+                .unwrap(),
+            None
+        );
+
+        settings.enabled = true;
+        database
+            .add_play_session(Path::new("Game.gba"), Utc::now(), Duration::minutes(20))
+            .unwrap();
+        assert_eq!(
+            settings
+                .play_time_remaining(&database, Duration::minutes(5))
+                .unwrap(),
+            Some(Duration::minutes(5))
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = temp_dir("kid-mode-save-and-load");
+        let path = dir.join("kid_mode.json");
+        assert!(!KidModeSettings::load_from(&path).enabled);
+
+        let mut settings = KidModeSettings::new();
+        settings.enabled = true;
+        settings.pin = vec![Key::A, Key::B];
+        settings.save_to(&path).unwrap();
+        let loaded = KidModeSettings::load_from(&path);
+        assert!(loaded.enabled);
+        assert_eq!(loaded.pin, vec![Key::A, Key::B]);
+        assert!(!loaded.is_recovery);
+        assert!(!dir.join("kid_mode.json.tmp").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_recovery() {
+        let dir = temp_dir("kid-mode-recovery");
+        let path = dir.join("kid_mode.json");
+
+        // A truncated file keeps kid mode on, but can be left with the recovery PIN
+        fs::write(&path, r#"{"enabled":true,"pin":["#).unwrap();
+        let loaded = KidModeSettings::load_from(&path);
+        assert!(loaded.enabled);
+        assert!(loaded.is_recovery);
+        assert_eq!(loaded.pin, RECOVERY_PIN);
+        assert!(loaded.allowed.is_empty());
+
+        // So can kid mode without a PIN
+        let mut settings = KidModeSettings::new();
+        settings.enabled = true;
+        settings.allowed = vec![PathBuf::from("Roms/GBA")];
+        settings.save_to(&path).unwrap();
+        let loaded = KidModeSettings::load_from(&path);
+        assert!(loaded.is_recovery);
+        assert_eq!(loaded.pin, RECOVERY_PIN);
+        assert_eq!(loaded.allowed, vec![PathBuf::from("Roms/GBA")]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
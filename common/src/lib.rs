@@ -1,17 +1,34 @@
 #![deny(clippy::all, unsafe_op_in_unsafe_fn)]
 #![warn(rust_2018_idioms)]
 
+pub mod alliumd;
 pub mod battery;
+pub mod cheats;
 pub mod command;
 pub mod constants;
+pub mod core_options;
 pub mod database;
+pub mod datetime;
 pub mod display;
 pub mod game_info;
 pub mod geom;
+pub mod hotkeys;
+pub mod kid_mode;
 pub mod locale;
+pub mod netplay;
+pub mod overrides;
 pub mod platform;
+pub mod profile;
 pub mod resources;
 pub mod retroarch;
+pub mod retroarch_config;
+pub mod save_backup;
+pub mod save_sync;
+pub mod shaders;
 pub mod stylesheet;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod update;
+pub mod video_settings;
 pub mod view;
 pub mod wifi;
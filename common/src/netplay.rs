@@ -0,0 +1,196 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+
+use crate::constants::{NETPLAY_DISCOVERY_ADDRESS, NETPLAY_DISCOVERY_TIMEOUT, NETPLAY_PORT};
+
+/// Magic number of a LAN discovery query, "RANQ".
+const DISCOVERY_QUERY_MAGIC: u32 = 0x5241_4e51;
+
+/// Magic number of a LAN discovery response, "RANS".
+const DISCOVERY_RESPONSE_MAGIC: u32 = 0x5241_4e53;
+
+// Layout of RetroArch's `struct ad_packet`, which hosts send in response to a query. The header
+// is followed by the content CRC, port and password flag as big-endian u32s, then fixed size
+// NUL-terminated strings.
+const NICK_OFFSET: usize = 16;
+const NICK_LEN: usize = 32;
+const CORE_OFFSET: usize = NICK_OFFSET + NICK_LEN + 32;
+const CORE_LEN: usize = 32;
+const CONTENT_OFFSET: usize = CORE_OFFSET + CORE_LEN * 3;
+const CONTENT_LEN: usize = 256;
+
+/// Netplay session to start the game with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Netplay {
+    /// Host the game for others to join.
+    Host,
+    /// Join a game hosted at the address.
+    Join(SocketAddr),
+}
+
+impl Netplay {
+    /// Returns the RetroArch arguments that start the session.
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Netplay::Host => vec!["--host".to_string(), format!("--port={}", NETPLAY_PORT)],
+            Netplay::Join(address) => vec![
+                format!("--connect={}", address.ip()),
+                format!("--port={}", address.port()),
+            ],
+        }
+    }
+}
+
+/// A game being hosted on the LAN, found through RetroArch's LAN discovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetplayHost {
+    pub address: SocketAddr,
+    pub nick: String,
+    pub core: String,
+    pub content: String,
+    pub content_crc: u32,
+    pub has_password: bool,
+}
+
+/// Broadcasts a LAN discovery query, and returns the hosts that answer in time.
+pub async fn discover() -> Result<Vec<NetplayHost>> {
+    discover_at(
+        NETPLAY_DISCOVERY_ADDRESS.as_str(),
+        NETPLAY_DISCOVERY_TIMEOUT,
+    )
+    .await
+}
+
+async fn discover_at(address: &str, timeout: Duration) -> Result<Vec<NetplayHost>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.set_broadcast(true)?;
+    debug!("sending netplay discovery query to {}", address);
+    socket
+        .send_to(&DISCOVERY_QUERY_MAGIC.to_be_bytes(), address)
+        .await?;
+
+    let mut hosts: Vec<NetplayHost> = Vec::new();
+    let mut packet = [0; 1024];
+    let deadline = tokio::time::Instant::now() + timeout;
+    while let Ok(result) = tokio::time::timeout_at(deadline, socket.recv_from(&mut packet)).await {
+        let (len, from) = result?;
+        trace!("received {} bytes from {}", len, from);
+        if let Some(host) = parse_response(&packet[..len], from) {
+            if !hosts.iter().any(|h| h.address == host.address) {
+                debug!("found netplay host: {:?}", host);
+                hosts.push(host);
+            }
+        }
+    }
+    Ok(hosts)
+}
+
+fn parse_response(packet: &[u8], from: SocketAddr) -> Option<NetplayHost> {
+    let read_u32 = |offset: usize| {
+        packet
+            .get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let read_str = |offset: usize, len: usize| {
+        let bytes = packet.get(offset..packet.len().min(offset + len))?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    };
+
+    if read_u32(0)? != DISCOVERY_RESPONSE_MAGIC {
+        return None;
+    }
+    let port = u16::try_from(read_u32(8)?).ok()?;
+
+    Some(NetplayHost {
+        address: SocketAddr::new(from.ip(), port),
+        nick: read_str(NICK_OFFSET, NICK_LEN).unwrap_or_default(),
+        core: read_str(CORE_OFFSET, CORE_LEN).unwrap_or_default(),
+        content: read_str(CONTENT_OFFSET, CONTENT_LEN).unwrap_or_default(),
+        content_crc: read_u32(4)?,
+        has_password: read_u32(12)? != 0,
+    })
+}
+
+/// Parses an address entered by the user, either `ip` or `ip:port`.
+pub fn parse_address(address: &str) -> Option<SocketAddr> {
+    let address = address.trim();
+    address.parse().ok().or_else(|| {
+        address
+            .parse::<IpAddr>()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, NETPLAY_PORT))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(port: u32, nick: &str, core: &str, content: &str) -> Vec<u8> {
+        let mut packet = vec![0; CONTENT_OFFSET + CONTENT_LEN * 2];
+        packet[0..4].copy_from_slice(&DISCOVERY_RESPONSE_MAGIC.to_be_bytes());
+        packet[4..8].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+        packet[8..12].copy_from_slice(&port.to_be_bytes());
+        packet[NICK_OFFSET..NICK_OFFSET + nick.len()].copy_from_slice(nick.as_bytes());
+        packet[CORE_OFFSET..CORE_OFFSET + core.len()].copy_from_slice(core.as_bytes());
+        packet[CONTENT_OFFSET..CONTENT_OFFSET + content.len()].copy_from_slice(content.as_bytes());
+        packet
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let from: SocketAddr = "192.168.1.20:40000".parse().unwrap();
+        let host = parse_response(&response(55435, "Player", "mGBA", "Pokemon"), from).unwrap();
+        assert_eq!(host.address, "192.168.1.20:55435".parse().unwrap());
+        assert_eq!(host.nick, "Player");
+        assert_eq!(host.core, "mGBA");
+        assert_eq!(host.content, "Pokemon");
+        assert_eq!(host.content_crc, 0x1234_5678);
+        assert!(!host.has_password);
+
+        assert!(parse_response(&DISCOVERY_QUERY_MAGIC.to_be_bytes(), from).is_none());
+        assert!(parse_response(&response(70000, "", "", ""), from).is_none());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("192.168.1.20"),
+            Some("192.168.1.20:55435".parse().unwrap())
+        );
+        assert_eq!(
+            parse_address(" 192.168.1.20:1234 "),
+            Some("192.168.1.20:1234".parse().unwrap())
+        );
+        assert_eq!(parse_address("not an address"), None);
+    }
+
+    #[tokio::test]
+    async fn test_discover() {
+        // Stand-in for a RetroArch host on the same machine
+        let host = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = host.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut query = [0; 4];
+            let (len, from) = host.recv_from(&mut query).await.unwrap();
+            assert_eq!(&query[..len], &DISCOVERY_QUERY_MAGIC.to_be_bytes());
+            let packet = response(55435, "Host", "mGBA", "Pokemon");
+            // Hosts on multiple interfaces answer more than once
+            host.send_to(&packet, from).await.unwrap();
+            host.send_to(&packet, from).await.unwrap();
+        });
+
+        let hosts = discover_at(&address, Duration::from_millis(500))
+            .await
+            .unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].address, "127.0.0.1:55435".parse().unwrap());
+        assert_eq!(hosts[0].nick, "Host");
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{debug, info};
+
+use crate::constants::RETROARCH_CONFIG_DIR;
+use crate::retroarch::CoreInfo;
+use crate::retroarch_config::RetroArchConfig;
+
+/// Which RetroArch override file to use. RetroArch loads the core, content directory and game
+/// overrides in that order, each on top of the last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideScope {
+    /// Every game run with the core.
+    Core,
+    /// Every game in the same folder, which is a console in Allium.
+    Console,
+    /// A single game.
+    Game,
+}
+
+impl OverrideScope {
+    /// Scopes from most to least specific.
+    pub const ALL: [OverrideScope; 3] = [
+        OverrideScope::Game,
+        OverrideScope::Console,
+        OverrideScope::Core,
+    ];
+}
+
+/// A RetroArch config override file.
+#[derive(Debug, Clone)]
+pub struct Overrides {
+    path: PathBuf,
+    config: RetroArchConfig,
+}
+
+impl Overrides {
+    pub fn load(core: &str, rom: &Path, scope: OverrideScope) -> Result<Self> {
+        let info = CoreInfo::load(core)?;
+        Self::load_in(&RETROARCH_CONFIG_DIR, &info, rom, scope)
+    }
+
+    pub(crate) fn load_in(
+        config_dir: &Path,
+        info: &CoreInfo,
+        rom: &Path,
+        scope: OverrideScope,
+    ) -> Result<Self> {
+        let dir = config_dir.join(&info.corename);
+        let name = match scope {
+            OverrideScope::Core => info.corename.clone(),
+            OverrideScope::Console => rom
+                .parent()
+                .and_then(Path::file_name)
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            OverrideScope::Game => rom
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        };
+        let path = dir.join(format!("{name}.cfg"));
+        debug!("loading overrides from {}", path.display());
+        Ok(Self {
+            config: RetroArchConfig::load(&path)?,
+            path,
+        })
+    }
+
+    /// Returns the value of a key, and the scope it comes from, as RetroArch would resolve it.
+    pub fn resolve(core: &str, rom: &Path, key: &str) -> Result<Option<(String, OverrideScope)>> {
+        let info = CoreInfo::load(core)?;
+        Self::resolve_in(&RETROARCH_CONFIG_DIR, &info, rom, key)
+    }
+
+    fn resolve_in(
+        config_dir: &Path,
+        info: &CoreInfo,
+        rom: &Path,
+        key: &str,
+    ) -> Result<Option<(String, OverrideScope)>> {
+        for scope in OverrideScope::ALL {
+            let overrides = Self::load_in(config_dir, info, rom, scope)?;
+            if let Some(value) = overrides.get(key) {
+                return Ok(Some((value.to_string(), scope)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.config.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        self.config.set(key, value);
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.config.remove(key)
+    }
+
+    /// Saves the overrides, removing the file once nothing is overridden.
+    pub fn save(&self) -> Result<()> {
+        if self.config.is_empty() {
+            if self.path.exists() {
+                info!("removing empty overrides {}", self.path.display());
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+        info!("saving overrides {}", self.path.display());
+        self.config.save(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    fn info() -> CoreInfo {
+        CoreInfo {
+            corename: "mGBA".to_string(),
+            databases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let dir = temp_dir("overrides-resolve");
+        let rom = Path::new("/mnt/SDCARD/Roms/GBA/Game.gba");
+
+        let mut core = Overrides::load_in(&dir, &info(), rom, OverrideScope::Core).unwrap();
+        core.set("video_scale_integer", "true");
+        core.set("video_smooth", "false");
+        core.save().unwrap();
+        assert!(dir.join("mGBA/mGBA.cfg").exists());
+
+        let mut console = Overrides::load_in(&dir, &info(), rom, OverrideScope::Console).unwrap();
+        console.set("video_smooth", "true");
+        console.save().unwrap();
+        assert!(dir.join("mGBA/GBA.cfg").exists());
+
+        let mut game = Overrides::load_in(&dir, &info(), rom, OverrideScope::Game).unwrap();
+        game.set("video_scale_integer", "false");
+        game.save().unwrap();
+        assert!(dir.join("mGBA/Game.cfg").exists());
+
+        assert_eq!(
+            Overrides::resolve_in(&dir, &info(), rom, "video_scale_integer").unwrap(),
+            Some(("false".to_string(), OverrideScope::Game))
+        );
+        assert_eq!(
+            Overrides::resolve_in(&dir, &info(), rom, "video_smooth").unwrap(),
+            Some(("true".to_string(), OverrideScope::Console))
+        );
+        assert_eq!(
+            Overrides::resolve_in(&dir, &info(), rom, "aspect_ratio_index").unwrap(),
+            None
+        );
+
+        // Another game in the same folder only gets the console and core overrides
+        let other = Path::new("/mnt/SDCARD/Roms/GBA/Other.gba");
+        assert_eq!(
+            Overrides::resolve_in(&dir, &info(), other, "video_scale_integer").unwrap(),
+            Some(("true".to_string(), OverrideScope::Core))
+        );
+
+        // Empty overrides are removed
+        let mut game = Overrides::load_in(&dir, &info(), rom, OverrideScope::Game).unwrap();
+        assert_eq!(
+            game.remove("video_scale_integer"),
+            Some("false".to_string())
+        );
+        game.save().unwrap();
+        assert!(!dir.join("mGBA/Game.cfg").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use crate::constants::MAXIMUM_FRAME_TIME;
+use crate::platform::{Key, KeyEvent};
+
+/// Event type of key and button events, `EV_KEY`.
+const EV_KEY: u16 = 0x01;
+/// Event type of absolute axis events, `EV_ABS`.
+const EV_ABS: u16 = 0x03;
+/// Horizontal axis of the d-pad hat, `ABS_HAT0X`.
+const ABS_HAT0X: u16 = 0x10;
+/// Vertical axis of the d-pad hat, `ABS_HAT0Y`.
+const ABS_HAT0Y: u16 = 0x11;
+
+/// An event read from an input device, as reported by evdev.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub timestamp: SystemTime,
+    pub event_type: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+/// Translates a device's events into key events.
+#[derive(Debug)]
+pub struct DeviceKeys {
+    /// Keys mapped to each evdev key code.
+    keys: HashMap<u16, Key>,
+    /// Current position of the d-pad hat, for each axis.
+    hat: [i32; 2],
+}
+
+impl DeviceKeys {
+    pub fn new(keys: HashMap<u16, Key>) -> Self {
+        Self { keys, hat: [0; 2] }
+    }
+
+    pub fn translate(&mut self, event: InputEvent) -> Vec<KeyEvent> {
+        match event.event_type {
+            EV_KEY => {
+                let key = self.keys.get(&event.code).copied().unwrap_or(Key::Unknown);
+                match event.value {
+                    0 => vec![KeyEvent::Released(key)],
+                    1 => vec![KeyEvent::Pressed(key)],
+                    2 => {
+                        if event
+                            .timestamp
+                            .elapsed()
+                            .map_or(false, |elapsed| elapsed > MAXIMUM_FRAME_TIME)
+                        {
+                            return vec![];
+                        }
+                        vec![KeyEvent::Autorepeat(key)]
+                    }
+                    _ => vec![],
+                }
+            }
+            EV_ABS => {
+                let (axis, negative, positive) = match event.code {
+                    ABS_HAT0X => (0, Key::Left, Key::Right),
+                    ABS_HAT0Y => (1, Key::Up, Key::Down),
+                    _ => return vec![],
+                };
+                let key = |value: i32| if value < 0 { negative } else { positive };
+
+                let value = event.value.signum();
+                let previous = std::mem::replace(&mut self.hat[axis], value);
+                let mut events = Vec::new();
+                if previous == value {
+                    return events;
+                }
+                if previous != 0 {
+                    events.push(KeyEvent::Released(key(previous)));
+                }
+                if value != 0 {
+                    events.push(KeyEvent::Pressed(key(value)));
+                }
+                events
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BTN_SOUTH: u16 = 0x130;
+    const BTN_EAST: u16 = 0x131;
+
+    fn event(event_type: u16, code: u16, value: i32) -> InputEvent {
+        InputEvent {
+            timestamp: SystemTime::now(),
+            event_type,
+            code,
+            value,
+        }
+    }
+
+    fn device_keys() -> DeviceKeys {
+        DeviceKeys::new(HashMap::from([(BTN_EAST, Key::A), (BTN_SOUTH, Key::B)]))
+    }
+
+    #[test]
+    fn test_translate_keys() {
+        let mut keys = device_keys();
+        assert_eq!(
+            keys.translate(event(EV_KEY, BTN_EAST, 1)),
+            vec![KeyEvent::Pressed(Key::A)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_KEY, BTN_EAST, 2)),
+            vec![KeyEvent::Autorepeat(Key::A)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_KEY, BTN_EAST, 0)),
+            vec![KeyEvent::Released(Key::A)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_KEY, BTN_SOUTH, 1)),
+            vec![KeyEvent::Pressed(Key::B)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_KEY, 0x2ff, 1)),
+            vec![KeyEvent::Pressed(Key::Unknown)]
+        );
+        assert_eq!(keys.translate(event(0x02, 0x00, 1)), vec![]);
+    }
+
+    #[test]
+    fn test_translate_stale_autorepeat() {
+        let mut keys = device_keys();
+        let mut stale = event(EV_KEY, BTN_EAST, 2);
+        stale.timestamp -= MAXIMUM_FRAME_TIME + Duration::from_millis(50);
+        assert_eq!(keys.translate(stale), vec![]);
+    }
+
+    #[test]
+    fn test_translate_hat() {
+        let mut keys = device_keys();
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0X, -1)),
+            vec![KeyEvent::Pressed(Key::Left)]
+        );
+        // Repeated positions are ignored
+        assert_eq!(keys.translate(event(EV_ABS, ABS_HAT0X, -1)), vec![]);
+        // Moving straight to the other side releases the previous direction
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0X, 1)),
+            vec![KeyEvent::Released(Key::Left), KeyEvent::Pressed(Key::Right)]
+        );
+        // Axes are tracked separately
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0Y, 1)),
+            vec![KeyEvent::Pressed(Key::Down)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0X, 0)),
+            vec![KeyEvent::Released(Key::Right)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0Y, 0)),
+            vec![KeyEvent::Released(Key::Down)]
+        );
+        assert_eq!(
+            keys.translate(event(EV_ABS, ABS_HAT0Y, -1)),
+            vec![KeyEvent::Pressed(Key::Up)]
+        );
+        // Other axes are ignored
+        assert_eq!(keys.translate(event(EV_ABS, 0x00, 100)), vec![]);
+    }
+}
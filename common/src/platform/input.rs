@@ -0,0 +1,371 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use enum_map::EnumMap;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::{InputMapping, Key, KeyEvent, KeySet, Platform};
+
+/// Timings used to detect long presses and double taps, set in the `[timings]` table of
+/// `config/input.toml` in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputTimings {
+    /// How long a key must be held on its own to count as a long press.
+    #[serde(rename = "long_press_ms", with = "millis")]
+    pub long_press: Duration,
+    /// Longest time between two presses of a key for them to count as a double tap.
+    #[serde(rename = "double_tap_ms", with = "millis")]
+    pub double_tap: Duration,
+}
+
+impl Default for InputTimings {
+    fn default() -> Self {
+        Self {
+            long_press: Duration::from_millis(500),
+            double_tap: Duration::from_millis(300),
+        }
+    }
+}
+
+/// (De)serializes a duration as whole milliseconds.
+mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Per-key state of a held key.
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    since: Instant,
+    /// Whether the key may still become a long press. Pressing another key or firing the long
+    /// press clears it.
+    can_long_press: bool,
+}
+
+/// Turns raw key events into higher-level ones. Every raw event is passed through, with A/B and
+/// X/Y swapped if enabled, and is followed by the events it completes:
+///
+/// - `Chord` when a key is pressed while others are held, with every held key;
+/// - `DoubleTap` when a key is pressed twice in a row within [`InputTimings::double_tap`];
+/// - `LongPress` once a key has been held on its own for [`InputTimings::long_press`].
+///
+/// Time is passed in explicitly so that detection is deterministic; [`InputProcessor::poll`]
+/// drives it from a platform in real time.
+#[derive(Debug)]
+pub struct InputProcessor {
+    timings: InputTimings,
+    held: EnumMap<Key, Option<Held>>,
+    /// The last key pressed on its own and when, while it may still be double tapped.
+    last_tap: Option<(Key, Instant)>,
+    /// Whether A/B and X/Y are swapped, see [`Key::swap_buttons`].
+    swap_buttons: bool,
+    queue: VecDeque<KeyEvent>,
+}
+
+impl InputProcessor {
+    pub fn new(timings: InputTimings) -> Self {
+        Self {
+            timings,
+            held: EnumMap::default(),
+            last_tap: None,
+            swap_buttons: false,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Creates a processor with the timings from `config/input.toml`, or the defaults if it
+    /// can't be read.
+    pub fn load() -> Self {
+        let timings = InputMapping::load()
+            .map(|mapping| mapping.timings)
+            .unwrap_or_else(|e| {
+                warn!("failed to load input timings: {}", e);
+                InputTimings::default()
+            });
+        Self::new(timings)
+    }
+
+    pub fn set_swap_buttons(&mut self, swap_buttons: bool) {
+        self.swap_buttons = swap_buttons;
+    }
+
+    /// Handles a raw key event received at `now`, returning it followed by any higher-level
+    /// events, including long presses that became due before it.
+    pub fn process(&mut self, event: KeyEvent, now: Instant) -> Vec<KeyEvent> {
+        let event = if self.swap_buttons {
+            event.swap_buttons()
+        } else {
+            event
+        };
+
+        let mut events = self.tick(now);
+        events.push(event);
+
+        match event {
+            KeyEvent::Pressed(key) => {
+                let others = self
+                    .held
+                    .iter()
+                    .filter(|(k, held)| *k != key && held.is_some())
+                    .map(|(k, _)| k)
+                    .collect::<Vec<_>>();
+
+                if others.is_empty() {
+                    match self.last_tap {
+                        Some((k, at)) if k == key && now - at <= self.timings.double_tap => {
+                            events.push(KeyEvent::DoubleTap(key));
+                            self.last_tap = None;
+                        }
+                        _ => self.last_tap = Some((key, now)),
+                    }
+                } else {
+                    for held in self.held.values_mut().flatten() {
+                        held.can_long_press = false;
+                    }
+                    let mut chord = KeySet::from(others.as_slice());
+                    chord.insert(key);
+                    events.push(KeyEvent::Chord(chord));
+                    self.last_tap = None;
+                }
+
+                self.held[key] = Some(Held {
+                    since: now,
+                    can_long_press: others.is_empty(),
+                });
+            }
+            KeyEvent::Released(key) => {
+                self.held[key] = None;
+            }
+            _ => {}
+        }
+
+        events
+    }
+
+    /// Returns the long presses that became due by `now`.
+    pub fn tick(&mut self, now: Instant) -> Vec<KeyEvent> {
+        let long_press = self.timings.long_press;
+        let mut events = Vec::new();
+        for (key, held) in self.held.iter_mut() {
+            if let Some(held) = held {
+                if held.can_long_press && now - held.since >= long_press {
+                    held.can_long_press = false;
+                    events.push(KeyEvent::LongPress(key));
+                    // A long press isn't the first half of a double tap
+                    if matches!(self.last_tap, Some((k, _)) if k == key) {
+                        self.last_tap = None;
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    /// When the next long press becomes due, if any key may still become one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.held
+            .values()
+            .flatten()
+            .filter(|held| held.can_long_press)
+            .map(|held| held.since + self.timings.long_press)
+            .min()
+    }
+
+    /// Polls the platform for the next event, raw or higher-level.
+    pub async fn poll<P: Platform>(&mut self, platform: &mut P) -> KeyEvent {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return event;
+            }
+
+            let deadline = self.deadline();
+            tokio::select! {
+                event = platform.poll() => {
+                    let events = self.process(event, Instant::now());
+                    self.queue.extend(events);
+                }
+                _ = async {
+                    match deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    let events = self.tick(Instant::now());
+                    self.queue.extend(events);
+                }
+            }
+        }
+    }
+}
+
+impl Default for InputProcessor {
+    fn default() -> Self {
+        Self::new(InputTimings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use KeyEvent::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let t = Instant::now();
+        let mut input = InputProcessor::default();
+        assert_eq!(input.process(Pressed(Key::A), t), vec![Pressed(Key::A)]);
+        assert_eq!(
+            input.process(Autorepeat(Key::A), t + ms(100)),
+            vec![Autorepeat(Key::A)]
+        );
+        assert_eq!(
+            input.process(Released(Key::A), t + ms(200)),
+            vec![Released(Key::A)]
+        );
+        assert_eq!(input.deadline(), None);
+    }
+
+    #[test]
+    fn test_long_press() {
+        let t = Instant::now();
+        let mut input = InputProcessor::default();
+        input.process(Pressed(Key::Power), t);
+        assert_eq!(input.deadline(), Some(t + ms(500)));
+        assert!(input.tick(t + ms(499)).is_empty());
+        assert_eq!(input.tick(t + ms(500)), vec![LongPress(Key::Power)]);
+        // Only fires once
+        assert!(input.tick(t + ms(1000)).is_empty());
+        assert_eq!(input.deadline(), None);
+
+        // A late event also flushes the long press first
+        input.process(Released(Key::Power), t + ms(1100));
+        input.process(Pressed(Key::A), t + ms(2000));
+        assert_eq!(
+            input.process(Released(Key::A), t + ms(2600)),
+            vec![LongPress(Key::A), Released(Key::A)]
+        );
+
+        // Chorded keys don't long press
+        input.process(Pressed(Key::Menu), t + ms(3000));
+        input.process(Pressed(Key::R), t + ms(3100));
+        assert_eq!(input.deadline(), None);
+        assert!(input.tick(t + ms(4000)).is_empty());
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let t = Instant::now();
+        let mut input = InputProcessor::default();
+        input.process(Pressed(Key::X), t);
+        input.process(Released(Key::X), t + ms(100));
+        assert_eq!(
+            input.process(Pressed(Key::X), t + ms(300)),
+            vec![Pressed(Key::X), DoubleTap(Key::X)]
+        );
+        input.process(Released(Key::X), t + ms(350));
+
+        // A third tap starts over
+        assert_eq!(
+            input.process(Pressed(Key::X), t + ms(400)),
+            vec![Pressed(Key::X)]
+        );
+        input.process(Released(Key::X), t + ms(450));
+
+        // Too slow
+        assert_eq!(
+            input.process(Pressed(Key::X), t + ms(1000)),
+            vec![Pressed(Key::X)]
+        );
+        input.process(Released(Key::X), t + ms(1050));
+
+        // Another key in between
+        input.process(Pressed(Key::Y), t + ms(1100));
+        input.process(Released(Key::Y), t + ms(1150));
+        assert_eq!(
+            input.process(Pressed(Key::X), t + ms(1200)),
+            vec![Pressed(Key::X)]
+        );
+        input.process(Released(Key::X), t + ms(1250));
+
+        // A long press isn't a tap
+        let mut input = InputProcessor::new(InputTimings {
+            long_press: ms(200),
+            double_tap: ms(300),
+        });
+        input.process(Pressed(Key::X), t);
+        input.process(Released(Key::X), t + ms(250));
+        assert_eq!(
+            input.process(Pressed(Key::X), t + ms(280)),
+            vec![Pressed(Key::X)]
+        );
+    }
+
+    #[test]
+    fn test_swap_buttons() {
+        let t = Instant::now();
+        let mut input = InputProcessor::default();
+        input.set_swap_buttons(true);
+        assert_eq!(input.process(Pressed(Key::B), t), vec![Pressed(Key::A)]);
+        assert_eq!(
+            input.process(Pressed(Key::Y), t + ms(10)),
+            vec![
+                Pressed(Key::X),
+                Chord(KeySet::from([Key::A, Key::X].as_slice()))
+            ]
+        );
+        assert_eq!(
+            input.process(Pressed(Key::Start), t + ms(20)),
+            vec![
+                Pressed(Key::Start),
+                Chord(KeySet::from([Key::A, Key::X, Key::Start].as_slice()))
+            ]
+        );
+        assert_eq!(
+            input.process(Released(Key::B), t + ms(30)),
+            vec![Released(Key::A)]
+        );
+    }
+
+    #[test]
+    fn test_chord() {
+        let t = Instant::now();
+        let mut input = InputProcessor::default();
+        input.process(Pressed(Key::Menu), t);
+        assert_eq!(
+            input.process(Pressed(Key::R), t + ms(50)),
+            vec![
+                Pressed(Key::R),
+                Chord(KeySet::from([Key::Menu, Key::R].as_slice()))
+            ]
+        );
+        let events = input.process(Pressed(Key::L), t + ms(60));
+        let Chord(chord) = events[1] else {
+            panic!("expected a chord, got {:?}", events);
+        };
+        assert_eq!(chord.keys(), vec![Key::L, Key::R, Key::Menu]);
+
+        input.process(Released(Key::L), t + ms(100));
+        input.process(Released(Key::R), t + ms(100));
+        input.process(Released(Key::Menu), t + ms(100));
+        assert_eq!(
+            input.process(Pressed(Key::R), t + ms(200)),
+            vec![Pressed(Key::R)]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_CONFIG_INPUT;
+use crate::platform::{InputTimings, Key};
+
+/// Maps the keys and buttons of input devices to [`Key`]s, read from `config/input.toml`. Keys are
+/// named as in evdev, e.g. `KEY_SPACE` or `BTN_SOUTH`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMapping {
+    /// Timings of long presses and double taps, see [`InputProcessor`].
+    ///
+    /// [`InputProcessor`]: crate::platform::InputProcessor
+    #[serde(default)]
+    pub timings: InputTimings,
+    /// Mapping shared by every device.
+    #[serde(default)]
+    pub keys: BTreeMap<String, Key>,
+    /// Mappings of specific devices, applied on top of the shared one.
+    #[serde(rename = "device", default)]
+    pub devices: Vec<DeviceMapping>,
+}
+
+/// Mapping of the devices whose name contains `name`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceMapping {
+    pub name: String,
+    #[serde(default)]
+    pub keys: BTreeMap<String, Key>,
+}
+
+impl InputMapping {
+    /// Loads the mapping, falling back to the defaults if the config is missing.
+    pub fn load() -> Result<Self> {
+        if !ALLIUM_CONFIG_INPUT.exists() {
+            debug!("input config not found, using defaults");
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(ALLIUM_CONFIG_INPUT.as_path())?)
+    }
+
+    fn parse(config: &str) -> Result<Self> {
+        toml::from_str(config).context("Failed to parse input.toml.")
+    }
+
+    /// Returns the mapping of the named device: the shared mapping, overridden by every device
+    /// mapping that matches it in order.
+    pub fn for_device(&self, name: &str) -> BTreeMap<String, Key> {
+        let mut keys = self.keys.clone();
+        for device in self.devices.iter().filter(|d| name.contains(&d.name)) {
+            keys.extend(device.keys.iter().map(|(k, v)| (k.clone(), *v)));
+        }
+        keys
+    }
+}
+
+impl Default for InputMapping {
+    fn default() -> Self {
+        let keys = [
+            // Miyoo Mini
+            ("KEY_UP", Key::Up),
+            ("KEY_DOWN", Key::Down),
+            ("KEY_LEFT", Key::Left),
+            ("KEY_RIGHT", Key::Right),
+            ("KEY_SPACE", Key::A),
+            ("KEY_LEFTCTRL", Key::B),
+            ("KEY_LEFTSHIFT", Key::X),
+            ("KEY_LEFTALT", Key::Y),
+            ("KEY_ENTER", Key::Start),
+            ("KEY_RIGHTCTRL", Key::Select),
+            ("KEY_E", Key::L),
+            ("KEY_T", Key::R),
+            ("KEY_ESC", Key::Menu),
+            ("KEY_TAB", Key::L2),
+            ("KEY_BACKSPACE", Key::R2),
+            ("KEY_POWER", Key::Power),
+            ("KEY_VOLUMEDOWN", Key::VolDown),
+            ("KEY_VOLUMEUP", Key::VolUp),
+            // Gamepads, by the position of the buttons
+            ("BTN_DPAD_UP", Key::Up),
+            ("BTN_DPAD_DOWN", Key::Down),
+            ("BTN_DPAD_LEFT", Key::Left),
+            ("BTN_DPAD_RIGHT", Key::Right),
+            ("BTN_EAST", Key::A),
+            ("BTN_SOUTH", Key::B),
+            ("BTN_NORTH", Key::X),
+            ("BTN_WEST", Key::Y),
+            ("BTN_START", Key::Start),
+            ("BTN_SELECT", Key::Select),
+            ("BTN_TL", Key::L),
+            ("BTN_TR", Key::R),
+            ("BTN_TL2", Key::L2),
+            ("BTN_TR2", Key::R2),
+            ("BTN_MODE", Key::Menu),
+        ];
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|(name, key)| (name.to_string(), key))
+                .collect(),
+            devices: Vec::new(),
+            timings: InputTimings::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = include_str!("../../../assets/root/.allium/config/input.toml");
+        assert_eq!(
+            InputMapping::parse(config).unwrap(),
+            InputMapping::default()
+        );
+    }
+
+    #[test]
+    fn test_for_device() {
+        let mapping = InputMapping::parse(
+            r#"
+[keys]
+BTN_EAST = "A"
+BTN_SOUTH = "B"
+BTN_MODE = "Menu"
+
+[[device]]
+name = "8BitDo"
+[device.keys]
+BTN_EAST = "B"
+BTN_SOUTH = "A"
+
+[[device]]
+name = "8BitDo SN30"
+[device.keys]
+BTN_MODE = "Unknown"
+"#,
+        )
+        .unwrap();
+
+        let keys = mapping.for_device("Xbox Wireless Controller");
+        assert_eq!(keys["BTN_EAST"], Key::A);
+        assert_eq!(keys["BTN_SOUTH"], Key::B);
+
+        let keys = mapping.for_device("8BitDo Pro 2");
+        assert_eq!(keys["BTN_EAST"], Key::B);
+        assert_eq!(keys["BTN_SOUTH"], Key::A);
+        assert_eq!(keys["BTN_MODE"], Key::Menu);
+
+        let keys = mapping.for_device("8BitDo SN30 Pro");
+        assert_eq!(keys["BTN_EAST"], Key::B);
+        assert_eq!(keys["BTN_MODE"], Key::Unknown);
+    }
+
+    #[test]
+    fn test_timings() {
+        let mapping = InputMapping::parse("[timings]\nlong_press_ms = 800\n").unwrap();
+        assert_eq!(mapping.timings.long_press, Duration::from_millis(800));
+        assert_eq!(
+            mapping.timings.double_tap,
+            InputTimings::default().double_tap
+        );
+        assert_eq!(
+            InputMapping::parse("").unwrap().timings,
+            InputTimings::default()
+        );
+    }
+}
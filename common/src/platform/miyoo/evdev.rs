@@ -1,69 +1,185 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
 use anyhow::Result;
-use evdev::{Device, EventStream, EventType};
-
-use crate::constants::MAXIMUM_FRAME_TIME;
-use crate::platform::{Key, KeyEvent};
-
-impl From<evdev::Key> for Key {
-    fn from(value: evdev::Key) -> Self {
-        match value {
-            evdev::Key::KEY_UP => Key::Up,
-            evdev::Key::KEY_DOWN => Key::Down,
-            evdev::Key::KEY_LEFT => Key::Left,
-            evdev::Key::KEY_RIGHT => Key::Right,
-            evdev::Key::KEY_SPACE => Key::A,
-            evdev::Key::KEY_LEFTCTRL => Key::B,
-            evdev::Key::KEY_LEFTSHIFT => Key::X,
-            evdev::Key::KEY_LEFTALT => Key::Y,
-            evdev::Key::KEY_ENTER => Key::Start,
-            evdev::Key::KEY_RIGHTCTRL => Key::Select,
-            evdev::Key::KEY_E => Key::L,
-            evdev::Key::KEY_T => Key::R,
-            evdev::Key::KEY_ESC => Key::Menu,
-            evdev::Key::KEY_TAB => Key::L2,
-            evdev::Key::KEY_BACKSPACE => Key::R2,
-            evdev::Key::KEY_POWER => Key::Power,
-            evdev::Key::KEY_VOLUMEDOWN => Key::VolDown,
-            evdev::Key::KEY_VOLUMEUP => Key::VolUp,
-            _ => Key::Unknown,
-        }
-    }
-}
+use evdev::{AbsoluteAxisType, Device};
+use log::{debug, info, warn};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use tokio::io::unix::AsyncFd;
+use tokio::sync::mpsc;
+
+use crate::platform::device_keys::{DeviceKeys, InputEvent};
+use crate::platform::{InputMapping, KeyEvent};
 
+const INPUT_DIR: &str = "/dev/input";
+
+/// Reads key events from every input device, including ones plugged in later.
 pub struct EvdevKeys {
-    pub events: EventStream,
+    events: mpsc::Receiver<KeyEvent>,
 }
 
 impl EvdevKeys {
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            events: Device::open("/dev/input/event0")
-                .unwrap()
-                .into_event_stream()?,
-        })
+        let mapping = InputMapping::load().unwrap_or_else(|e| {
+            warn!("failed to load input mapping, using defaults: {}", e);
+            InputMapping::default()
+        });
+        let (tx, rx) = mpsc::channel(32);
+        let devices = Devices {
+            mapping,
+            tx,
+            opened: Arc::new(Mutex::new(HashSet::new())),
+        };
+
+        // Watch before listing, so that no device is missed in between
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+        inotify.add_watch(
+            INPUT_DIR,
+            AddWatchFlags::IN_CREATE | AddWatchFlags::IN_ATTRIB,
+        )?;
+
+        for entry in std::fs::read_dir(INPUT_DIR)? {
+            devices.open(&entry?.path());
+        }
+        tokio::spawn(devices.watch(inotify));
+
+        Ok(Self { events: rx })
     }
 
     pub async fn poll(&mut self) -> KeyEvent {
+        match self.events.recv().await {
+            Some(event) => event,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Opens input devices and forwards their key events.
+struct Devices {
+    mapping: InputMapping,
+    tx: mpsc::Sender<KeyEvent>,
+    /// Paths of the devices currently open.
+    opened: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl Devices {
+    /// Opens new devices as they appear in `/dev/input`. A device node may only become readable
+    /// after it's created, so changes to its attributes are retried too.
+    async fn watch(self, inotify: Inotify) {
+        let inotify = match AsyncFd::new(inotify) {
+            Ok(inotify) => inotify,
+            Err(e) => {
+                warn!("failed to watch input devices: {}", e);
+                return;
+            }
+        };
         loop {
-            let event = self.events.next_event().await.unwrap();
-            match event.event_type() {
-                EventType::KEY => {
-                    let key = event.code();
-                    let key: Key = evdev::Key(key).into();
-                    return match event.value() {
-                        0 => KeyEvent::Released(key),
-                        1 => KeyEvent::Pressed(key),
-                        2 => {
-                            if event.timestamp().elapsed().unwrap() > MAXIMUM_FRAME_TIME {
-                                continue;
+            let mut guard = match inotify.readable().await {
+                Ok(guard) => guard,
+                Err(e) => {
+                    warn!("failed to watch input devices: {}", e);
+                    return;
+                }
+            };
+            let events = match guard.try_io(|inotify| Ok(inotify.get_ref().read_events()?)) {
+                Ok(Ok(events)) => events,
+                Ok(Err(e)) => {
+                    warn!("failed to read input device changes: {}", e);
+                    return;
+                }
+                Err(_would_block) => continue,
+            };
+            for event in events {
+                if let Some(name) = event.name {
+                    self.open(&Path::new(INPUT_DIR).join(name));
+                }
+            }
+        }
+    }
+
+    /// Opens the device and forwards its events until it's removed. Does nothing if it's
+    /// already open, or isn't an event device with keys or a hat.
+    fn open(&self, path: &Path) {
+        if !path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with("event"))
+        {
+            return;
+        }
+        if self.opened.lock().unwrap().contains(path) {
+            return;
+        }
+
+        let device = match Device::open(path) {
+            Ok(device) => device,
+            Err(e) => {
+                debug!("failed to open input device {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let has_hat = device.supported_absolute_axes().map_or(false, |axes| {
+            axes.contains(AbsoluteAxisType::ABS_HAT0X) || axes.contains(AbsoluteAxisType::ABS_HAT0Y)
+        });
+        if device.supported_keys().is_none() && !has_hat {
+            return;
+        }
+
+        let name = device.name().unwrap_or_default().to_string();
+        let mut keys = device_keys(&self.mapping, &name);
+        let mut events = match device.into_event_stream() {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("failed to read input device {}: {}", path.display(), e);
+                return;
+            }
+        };
+        info!("opened input device {}: {}", path.display(), name);
+        self.opened.lock().unwrap().insert(path.to_path_buf());
+
+        let path = path.to_path_buf();
+        let tx = self.tx.clone();
+        let opened = Arc::clone(&self.opened);
+        tokio::spawn(async move {
+            loop {
+                match events.next_event().await {
+                    Ok(event) => {
+                        let event = InputEvent {
+                            timestamp: event.timestamp(),
+                            event_type: event.event_type().0,
+                            code: event.code(),
+                            value: event.value(),
+                        };
+                        for event in keys.translate(event) {
+                            if tx.send(event).await.is_err() {
+                                return;
                             }
-                            KeyEvent::Autorepeat(key)
                         }
-                        _ => unreachable!(),
-                    };
+                    }
+                    Err(e) => {
+                        info!("closed input device {}: {}", path.display(), e);
+                        break;
+                    }
                 }
-                _ => {}
             }
-        }
+            opened.lock().unwrap().remove(&path);
+        });
     }
 }
+
+/// Resolves the key codes of a device's mapping, skipping the ones evdev doesn't know.
+fn device_keys(mapping: &InputMapping, name: &str) -> DeviceKeys {
+    let keys = mapping
+        .for_device(name)
+        .into_iter()
+        .filter_map(|(code, key)| match code.parse::<evdev::Key>() {
+            Ok(code) => Some((code.code(), key)),
+            Err(_) => {
+                warn!("unknown key in input mapping: {}", code);
+                None
+            }
+        })
+        .collect::<HashMap<_, _>>();
+    DeviceKeys::new(keys)
+}
@@ -85,6 +85,14 @@ impl Platform for MiyooPlatform {
         Ok(())
     }
 
+    fn suspend(&mut self) -> Result<()> {
+        screen::set_backlight(false)
+    }
+
+    fn unsuspend(&mut self) -> Result<()> {
+        screen::set_backlight(true)
+    }
+
     fn set_volume(&mut self, volume: i32) -> Result<()> {
         match self.model {
             MiyooDeviceModel::Miyoo283 => Ok(()),
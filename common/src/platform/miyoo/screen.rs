@@ -19,6 +19,13 @@ pub fn set_brightness(brightness: u8) -> Result<()> {
     Ok(())
 }
 
+/// Turns the backlight on or off, keeping the brightness.
+pub fn set_backlight(enabled: bool) -> Result<()> {
+    let mut file = File::create("/sys/devices/soc0/soc/1f003400.pwm/pwm/pwmchip0/pwm0/enable")?;
+    file.write_all(if enabled { b"1" } else { b"0" })?;
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SystemConfig {
     vol: u8,
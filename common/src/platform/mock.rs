@@ -39,6 +39,14 @@ impl Platform for MockPlatform {
         Ok(())
     }
 
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn unsuspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     fn set_volume(&mut self, _volume: i32) -> Result<()> {
         Ok(())
     }
@@ -1,3 +1,7 @@
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+mod device_keys;
+mod input;
+mod mapping;
 #[cfg(not(any(feature = "miyoo", feature = "simulator")))]
 mod mock;
 
@@ -16,6 +20,9 @@ use crate::{
     display::{settings::DisplaySettings, Display},
 };
 
+pub use self::input::{InputProcessor, InputTimings};
+pub use self::mapping::{DeviceMapping, InputMapping};
+
 #[cfg(feature = "miyoo")]
 pub type DefaultPlatform = miyoo::MiyooPlatform;
 
@@ -43,6 +50,13 @@ pub trait Platform {
 
     fn shutdown(&self) -> Result<()>;
 
+    /// Turns the screen off while the device sleeps. Pausing the running processes is up to
+    /// the caller.
+    fn suspend(&mut self) -> Result<()>;
+
+    /// Turns the screen back on after [`Platform::suspend`].
+    fn unsuspend(&mut self) -> Result<()>;
+
     fn set_volume(&mut self, volume: i32) -> Result<()>;
 
     fn get_brightness(&self) -> Result<u8>;
@@ -63,6 +77,12 @@ pub enum KeyEvent {
     Pressed(Key),
     Released(Key),
     Autorepeat(Key),
+    /// A key held on its own, see [`InputProcessor`].
+    LongPress(Key),
+    /// A key pressed twice in quick succession, see [`InputProcessor`].
+    DoubleTap(Key),
+    /// Keys held together, sent when the last of them is pressed. See [`InputProcessor`].
+    Chord(KeySet),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
@@ -87,3 +107,88 @@ pub enum Key {
     VolUp,
     Unknown,
 }
+
+impl KeyEvent {
+    /// Swaps A with B and X with Y, see [`Key::swap_buttons`].
+    pub fn swap_buttons(self) -> Self {
+        match self {
+            KeyEvent::Pressed(key) => KeyEvent::Pressed(key.swap_buttons()),
+            KeyEvent::Released(key) => KeyEvent::Released(key.swap_buttons()),
+            KeyEvent::Autorepeat(key) => KeyEvent::Autorepeat(key.swap_buttons()),
+            KeyEvent::LongPress(key) => KeyEvent::LongPress(key.swap_buttons()),
+            KeyEvent::DoubleTap(key) => KeyEvent::DoubleTap(key.swap_buttons()),
+            KeyEvent::Chord(keys) => KeyEvent::Chord(KeySet::from(
+                keys.keys()
+                    .into_iter()
+                    .map(Key::swap_buttons)
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+            )),
+        }
+    }
+}
+
+impl Key {
+    /// Swaps A with B and X with Y, for Xbox-style confirm and back buttons. Views always see
+    /// A as confirm and B as back; the swap is applied to the keys read from the device, and
+    /// again when drawing button icons.
+    pub fn swap_buttons(self) -> Self {
+        match self {
+            Key::A => Key::B,
+            Key::B => Key::A,
+            Key::X => Key::Y,
+            Key::Y => Key::X,
+            key => key,
+        }
+    }
+}
+
+/// A set of keys, kept small so that [`KeyEvent`] stays `Copy`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeySet(u32);
+
+impl KeySet {
+    pub fn insert(&mut self, key: Key) {
+        self.0 |= 1 << key.into_usize();
+    }
+
+    pub fn remove(&mut self, key: Key) {
+        self.0 &= !(1 << key.into_usize());
+    }
+
+    pub fn contains(&self, key: Key) -> bool {
+        self.0 & (1 << key.into_usize()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The keys in the set, in declaration order.
+    pub fn keys(&self) -> Vec<Key> {
+        (0..Key::LENGTH)
+            .map(Key::from_usize)
+            .filter(|k| self.contains(*k))
+            .collect()
+    }
+}
+
+impl From<&[Key]> for KeySet {
+    fn from(keys: &[Key]) -> Self {
+        let mut set = Self::default();
+        for key in keys {
+            set.insert(*key);
+        }
+        set
+    }
+}
+
+impl std::fmt::Debug for KeySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.keys()).finish()
+    }
+}
@@ -97,6 +97,14 @@ impl Platform for SimulatorPlatform {
         process::exit(0);
     }
 
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn unsuspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     fn set_volume(&mut self, _volume: i32) -> Result<()> {
         Ok(())
     }
@@ -0,0 +1,232 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    ALLIUM_CURRENT_PROFILE_DIR, ALLIUM_LOCALE_SETTINGS, ALLIUM_PROFILES, ALLIUM_PROFILES_DIR,
+    ALLIUM_STYLESHEET,
+};
+use crate::game_info::GameInfo;
+
+/// Name of the profile that exists before any profiles are created.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// File names used to keep a profile's own theme and locale inside its directory.
+const PROFILE_STYLESHEET: &str = "stylesheet.json";
+const PROFILE_LOCALE: &str = "locale.json";
+
+/// A user profile. Each profile has its own database, saves and states. The active profile's
+/// files live in `Saves/CurrentProfile`, and inactive profiles are kept in `Saves/Profiles/<name>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Whether the profile keeps its own theme instead of sharing it with other profiles.
+    #[serde(default)]
+    pub separate_theme: bool,
+    /// Whether the profile keeps its own language instead of sharing it with other profiles.
+    #[serde(default)]
+    pub separate_locale: bool,
+}
+
+impl Profile {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            separate_theme: false,
+            separate_locale: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profiles {
+    /// Name of the active profile.
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile::new(DEFAULT_PROFILE.to_string())],
+        }
+    }
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_PROFILES.exists() {
+            debug!("found state, loading from file");
+            if let Ok(json) = fs::read_to_string(ALLIUM_PROFILES.as_path()) {
+                if let Ok(json) = serde_json::from_str(&json) {
+                    return Ok(json);
+                }
+            }
+            warn!("failed to read state file, removing");
+            fs::remove_file(ALLIUM_PROFILES.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string(&self).unwrap();
+        File::create(ALLIUM_PROFILES.as_path())?.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the active profile.
+    pub fn active(&self) -> Option<&Profile> {
+        self.get(&self.active)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    /// Creates a new, empty profile.
+    pub fn create(&mut self, name: &str) -> Result<()> {
+        let name = name.trim();
+        validate_name(name)?;
+        if self.get(name).is_some() {
+            bail!("profile \"{}\" already exists", name);
+        }
+
+        let dir = profile_dir(name);
+        fs::create_dir_all(dir.join("saves"))?;
+        fs::create_dir_all(dir.join("states"))?;
+
+        self.profiles.push(Profile::new(name.to_string()));
+        self.save()
+    }
+
+    /// Deletes an inactive profile along with its saves and states.
+    pub fn delete(&mut self, name: &str) -> Result<()> {
+        if name == self.active {
+            bail!("cannot delete the active profile");
+        }
+
+        let dir = profile_dir(name);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+
+        self.profiles.retain(|p| p.name != name);
+        self.save()
+    }
+
+    /// Switches to another profile by swapping the contents of `Saves/CurrentProfile`. This must
+    /// not be done while a game is running, and the database must be closed beforehand.
+    pub fn switch(&mut self, name: &str) -> Result<()> {
+        if GameInfo::load()?.is_some() {
+            bail!("cannot switch profiles while a game is running");
+        }
+        let Some(next) = self.get(name).cloned() else {
+            bail!("profile \"{}\" does not exist", name);
+        };
+        if next.name == self.active {
+            return Ok(());
+        }
+
+        info!("switching profile from \"{}\" to \"{}\"", self.active, name);
+
+        if let Some(current) = self.active() {
+            if current.separate_theme {
+                copy_if_exists(
+                    &ALLIUM_STYLESHEET,
+                    &ALLIUM_CURRENT_PROFILE_DIR.join(PROFILE_STYLESHEET),
+                )?;
+            }
+            if current.separate_locale {
+                copy_if_exists(
+                    &ALLIUM_LOCALE_SETTINGS,
+                    &ALLIUM_CURRENT_PROFILE_DIR.join(PROFILE_LOCALE),
+                )?;
+            }
+        }
+
+        let stash = profile_dir(&self.active);
+        if stash.exists() {
+            bail!("profile directory {} already exists", stash.display());
+        }
+        fs::create_dir_all(ALLIUM_PROFILES_DIR.as_path())?;
+        fs::rename(ALLIUM_CURRENT_PROFILE_DIR.as_path(), &stash)?;
+
+        let incoming = profile_dir(name);
+        let result = if incoming.exists() {
+            fs::rename(&incoming, ALLIUM_CURRENT_PROFILE_DIR.as_path())
+        } else {
+            fs::create_dir_all(ALLIUM_CURRENT_PROFILE_DIR.as_path())
+        };
+        if let Err(e) = result {
+            warn!("failed to restore profile \"{}\", rolling back", name);
+            fs::rename(&stash, ALLIUM_CURRENT_PROFILE_DIR.as_path())?;
+            return Err(e.into());
+        }
+
+        if next.separate_theme {
+            copy_if_exists(
+                &ALLIUM_CURRENT_PROFILE_DIR.join(PROFILE_STYLESHEET),
+                &ALLIUM_STYLESHEET,
+            )?;
+        }
+        if next.separate_locale {
+            copy_if_exists(
+                &ALLIUM_CURRENT_PROFILE_DIR.join(PROFILE_LOCALE),
+                &ALLIUM_LOCALE_SETTINGS,
+            )?;
+        }
+
+        self.active = next.name;
+        self.save()
+    }
+}
+
+/// Returns the directory an inactive profile is kept in.
+fn profile_dir(name: &str) -> PathBuf {
+    ALLIUM_PROFILES_DIR.join(name)
+}
+
+fn copy_if_exists(from: &Path, to: &Path) -> Result<()> {
+    if from.exists() {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// Profile names are used as directory names, so they must be valid file names.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|'])
+    {
+        bail!("invalid profile name \"{}\"", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("Player 2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name(".hidden").is_err());
+        assert!(validate_name("../Roms").is_err());
+        assert!(validate_name("a:b").is_err());
+    }
+}
@@ -1,10 +1,11 @@
-use std::{borrow::Cow, time::Duration};
+use std::{borrow::Cow, path::PathBuf, str::FromStr, time::Duration};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use log::{debug, error, trace};
 use tokio::{net::UdpSocket, select};
 
-use crate::constants::RETROARCH_UDP_SOCKET;
+use crate::constants::{RETROARCH_CORES_DIR, RETROARCH_UDP_SOCKET};
+use crate::retroarch_config::RetroArchConfig;
 
 #[allow(unused)]
 #[derive(Debug)]
@@ -46,6 +47,8 @@ pub enum RetroArchCommand {
     SetStateSlot(u8),
     SaveStateSlot(u8),
     LoadStateSlot(u8),
+    ShowMsg(String),
+    GetConfigParam(String),
 }
 
 impl RetroArchCommand {
@@ -91,6 +94,26 @@ impl RetroArchCommand {
         }
     }
 
+    /// Reads a config value from the running RetroArch. RetroArch only answers for some keys, so
+    /// `None` means the value has to be read from the config files instead.
+    pub async fn get_config_param(key: &str) -> Result<Option<String>> {
+        let reply = RetroArchCommand::GetConfigParam(key.to_string())
+            .send_recv()
+            .await?;
+        Ok(reply.and_then(|reply| parse_config_param(key, &reply)))
+    }
+
+    /// Whether RetroArch replies to the command.
+    pub fn has_reply(&self) -> bool {
+        matches!(
+            self,
+            RetroArchCommand::GetDiskCount
+                | RetroArchCommand::GetDiskSlot
+                | RetroArchCommand::GetStateSlot
+                | RetroArchCommand::GetConfigParam(_)
+        )
+    }
+
     fn as_str(&self) -> Cow<'static, str> {
         match self {
             RetroArchCommand::FastForward => Cow::Borrowed("FAST_FORWARD"),
@@ -130,6 +153,201 @@ impl RetroArchCommand {
             RetroArchCommand::SetStateSlot(slot) => Cow::Owned(format!("SET_STATE_SLOT {slot}")),
             RetroArchCommand::SaveStateSlot(slot) => Cow::Owned(format!("SAVE_STATE_SLOT {slot}")),
             RetroArchCommand::LoadStateSlot(slot) => Cow::Owned(format!("LOAD_STATE_SLOT {slot}")),
+            RetroArchCommand::ShowMsg(msg) => Cow::Owned(format!("SHOW_MSG {msg}")),
+            RetroArchCommand::GetConfigParam(key) => Cow::Owned(format!("GET_CONFIG_PARAM {key}")),
+        }
+    }
+}
+
+/// Parses a command as sent to RetroArch, e.g. `SAVE_STATE` or `SET_STATE_SLOT 2`. The name is
+/// case insensitive.
+impl FromStr for RetroArchCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, arg) = match s.trim().split_once(' ') {
+            Some((name, arg)) => (name, Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        let name = name.to_ascii_uppercase();
+        let slot = || -> Result<u8> {
+            let arg = arg.ok_or_else(|| anyhow!("{} takes a slot number.", name))?;
+            arg.parse()
+                .map_err(|_| anyhow!("Invalid slot number: {}", arg))
+        };
+        let text = || -> Result<String> {
+            arg.map(str::to_string)
+                .ok_or_else(|| anyhow!("{} takes an argument.", name))
+        };
+
+        let command = match name.as_str() {
+            "FAST_FORWARD" => RetroArchCommand::FastForward,
+            "FAST_FORWARD_HOLD" => RetroArchCommand::FastForwardHold,
+            "LOAD_STATE" => RetroArchCommand::LoadState,
+            "SAVE_STATE" => RetroArchCommand::SaveState,
+            "FULLSCREEN_TOGGLE" => RetroArchCommand::FullscreenToggle,
+            "QUIT" => RetroArchCommand::Quit,
+            "STATE_SLOT_PLUS" => RetroArchCommand::StateSlotPlus,
+            "STATE_SLOT_MINUS" => RetroArchCommand::StateSlotMinus,
+            "REWIND" => RetroArchCommand::Rewind,
+            "MOVIE_RECORD_TOGGLE" => RetroArchCommand::MovieRecordToggle,
+            "PAUSE_TOGGLE" => RetroArchCommand::PauseToggle,
+            "FRAMEADVANCE" => RetroArchCommand::FrameAdvance,
+            "RESET" => RetroArchCommand::Reset,
+            "SHADER_NEXT" => RetroArchCommand::ShaderNext,
+            "SHADER_PREV" => RetroArchCommand::ShaderPrev,
+            "CHEAT_INDEX_PLUS" => RetroArchCommand::CheatIndexPlus,
+            "CHEAT_INDEX_MINUS" => RetroArchCommand::CheatIndexMinus,
+            "CHEAT_TOGGLE" => RetroArchCommand::CheatToggle,
+            "SCREENSHOT" => RetroArchCommand::Screenshot,
+            "MUTE" => RetroArchCommand::Mute,
+            "NETPLAY_FLIP" => RetroArchCommand::NetplayFlip,
+            "SLOWMOTION" => RetroArchCommand::SlowMotion,
+            "VOLUME_UP" => RetroArchCommand::VolumeUp,
+            "VOLUME_DOWN" => RetroArchCommand::VolumeDown,
+            "OVERLAY_NEXT" => RetroArchCommand::OverlayNext,
+            "DISK_EJECT_TOGGLE" => RetroArchCommand::DiskEjectToggle,
+            "DISK_NEXT" => RetroArchCommand::DiskNext,
+            "DISK_PREV" => RetroArchCommand::DiskPrev,
+            "GRAB_MOUSE_TOGGLE" => RetroArchCommand::GrabMouseToggle,
+            "MENU_TOGGLE" => RetroArchCommand::MenuToggle,
+            "GET_DISK_COUNT" => RetroArchCommand::GetDiskCount,
+            "GET_DISK_SLOT" => RetroArchCommand::GetDiskSlot,
+            "SET_DISK_SLOT" => RetroArchCommand::SetDiskSlot(slot()?),
+            "GET_STATE_SLOT" => RetroArchCommand::GetStateSlot,
+            "SET_STATE_SLOT" => RetroArchCommand::SetStateSlot(slot()?),
+            "SAVE_STATE_SLOT" => RetroArchCommand::SaveStateSlot(slot()?),
+            "LOAD_STATE_SLOT" => RetroArchCommand::LoadStateSlot(slot()?),
+            "SHOW_MSG" => RetroArchCommand::ShowMsg(text()?),
+            "GET_CONFIG_PARAM" => RetroArchCommand::GetConfigParam(text()?),
+            _ => bail!("Unknown RetroArch command: {}", name),
+        };
+        Ok(command)
+    }
+}
+
+/// Parses a `GET_CONFIG_PARAM <key> <value>` reply.
+fn parse_config_param(key: &str, reply: &str) -> Option<String> {
+    let value = reply
+        .trim_end()
+        .strip_prefix("GET_CONFIG_PARAM ")?
+        .strip_prefix(key)?
+        .strip_prefix(' ')?;
+    (value != "unsupported").then(|| value.to_string())
+}
+
+/// Information about a RetroArch core, read from its `.info` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreInfo {
+    /// Name the core reports to RetroArch, used for per-core config and cheat folders.
+    pub corename: String,
+    /// Names of the consoles the core supports, as used by the libretro database.
+    pub databases: Vec<String>,
+}
+
+impl CoreInfo {
+    /// Loads the info of a core, e.g. `mgba`. Falls back to the core's file name if the info file
+    /// is missing.
+    pub fn load(core: &str) -> Result<Self> {
+        Ok(Self::from_config(
+            core,
+            &RetroArchConfig::load(&Self::path(core))?,
+        ))
+    }
+
+    fn path(core: &str) -> PathBuf {
+        RETROARCH_CORES_DIR.join(format!("{core}_libretro.info"))
+    }
+
+    fn from_config(core: &str, config: &RetroArchConfig) -> Self {
+        Self {
+            corename: config.get("corename").unwrap_or(core).to_string(),
+            databases: config
+                .get("database")
+                .map(|d| d.split('|').map(str::to_string).collect())
+                .unwrap_or_default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_param() {
+        assert_eq!(
+            parse_config_param(
+                "video_fullscreen",
+                "GET_CONFIG_PARAM video_fullscreen true\n"
+            ),
+            Some("true".to_string())
+        );
+        assert_eq!(
+            parse_config_param(
+                "savestate_directory",
+                "GET_CONFIG_PARAM savestate_directory /mnt/SDCARD/Saves/states\n"
+            ),
+            Some("/mnt/SDCARD/Saves/states".to_string())
+        );
+        assert_eq!(
+            parse_config_param(
+                "video_smooth",
+                "GET_CONFIG_PARAM video_smooth unsupported\n"
+            ),
+            None
+        );
+        assert_eq!(
+            parse_config_param("video_smooth", "GET_DISK_COUNT 1\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        for command in [
+            "SAVE_STATE",
+            "FRAMEADVANCE",
+            "SET_STATE_SLOT 2",
+            "SHOW_MSG Hello world",
+            "GET_CONFIG_PARAM video_fullscreen",
+        ] {
+            assert_eq!(
+                command.parse::<RetroArchCommand>().unwrap().as_str(),
+                command
+            );
+        }
+        assert_eq!(
+            "load_state_slot 3"
+                .parse::<RetroArchCommand>()
+                .unwrap()
+                .as_str(),
+            "LOAD_STATE_SLOT 3"
+        );
+        assert!("SET_STATE_SLOT".parse::<RetroArchCommand>().is_err());
+        assert!("SET_STATE_SLOT x".parse::<RetroArchCommand>().is_err());
+        assert!("SHOW_MSG".parse::<RetroArchCommand>().is_err());
+        assert!("JUMP".parse::<RetroArchCommand>().is_err());
+    }
+
+    #[test]
+    fn test_core_info() {
+        let config = RetroArchConfig::parse(
+            r#"
+display_name = "Nintendo - Game Boy Advance (mGBA)"
+corename = "mGBA"
+database = "Nintendo - Game Boy|Nintendo - Game Boy Advance"
+"#,
+        );
+        let info = CoreInfo::from_config("mgba", &config);
+        assert_eq!(info.corename, "mGBA");
+        assert_eq!(
+            info.databases,
+            vec!["Nintendo - Game Boy", "Nintendo - Game Boy Advance"]
+        );
+
+        let info = CoreInfo::from_config("missing", &RetroArchConfig::new());
+        assert_eq!(info.corename, "missing");
+        assert!(info.databases.is_empty());
+    }
+}
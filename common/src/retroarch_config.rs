@@ -0,0 +1,147 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+
+/// A RetroArch `key = "value"` file, e.g. `.cfg` overrides, `.opt` core options, `.cht` cheats
+/// and `.info` core info. Entries keep their order so that files are rewritten with minimal
+/// changes. Comments and blank lines are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetroArchConfig {
+    entries: Vec<(String, String)>,
+}
+
+impl RetroArchConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a config file. Returns an empty config if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Saves the config file, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (key, value) = line.split_once('=')?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some((key.trim().to_string(), value.to_string()))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the value as a bool, accepting RetroArch's `true`/`false`.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Sets the value of a key, keeping its position if it already exists.
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn set_bool(&mut self, key: &str, value: bool) {
+        self.set(key, if value { "true" } else { "false" });
+    }
+
+    /// Removes a key, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let i = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl fmt::Display for RetroArchConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.entries {
+            writeln!(f, "{} = \"{}\"", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = RetroArchConfig::parse(
+            r#"
+# Comment
+video_scale_integer = "true"
+aspect_ratio_index=22
+  video_smooth = "false"
+input_player1_a = "x"
+not a key value
+"#,
+        );
+        assert_eq!(config.get("video_scale_integer"), Some("true"));
+        assert_eq!(config.get("aspect_ratio_index"), Some("22"));
+        assert_eq!(config.get_bool("video_smooth"), Some(false));
+        assert_eq!(config.get_bool("input_player1_a"), None);
+        assert_eq!(config.get("missing"), None);
+        assert_eq!(config.iter().count(), 4);
+    }
+
+    #[test]
+    fn test_write() {
+        let mut config = RetroArchConfig::parse("b = \"1\"\na = \"2\"\n");
+        config.set("b", "3");
+        config.set("c", "with spaces");
+        config.set_bool("d", true);
+        assert_eq!(config.remove("a"), Some("2".to_string()));
+        assert_eq!(config.remove("a"), None);
+
+        let text = config.to_string();
+        assert_eq!(text, "b = \"3\"\nc = \"with spaces\"\nd = \"true\"\n");
+        assert_eq!(RetroArchConfig::parse(&text), config);
+    }
+}
@@ -0,0 +1,359 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use log::{debug, info};
+
+use crate::constants::{
+    ALLIUM_CURRENT_PROFILE_DIR, ALLIUM_GAMES_DIR, ALLIUM_SAVE_EXPORTS_DIR, SAVE_BACKUP_LIMIT,
+};
+use crate::datetime::DateTimeSettings;
+
+/// Format of the backup directory names.
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A snapshot of a game's save files and states. Backups are kept in
+/// `Saves/CurrentProfile/backups/<game>/<time>`, with the files at the same paths relative to
+/// the profile directory as the originals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveBackup {
+    pub path: PathBuf,
+    pub time: DateTime<Utc>,
+}
+
+impl SaveBackup {
+    /// Backs up a game's save files and states, then removes the oldest backups over the limit.
+    /// Returns None if there was nothing to back up or nothing changed since the last backup.
+    pub fn create(game: &Path) -> Result<Option<Self>> {
+        Self::create_in(&ALLIUM_CURRENT_PROFILE_DIR, &ALLIUM_GAMES_DIR, game)
+    }
+
+    fn create_in(profile: &Path, games: &Path, game: &Path) -> Result<Option<Self>> {
+        let backup = Self::create_unpruned(profile, games, game)?;
+        prune(profile, games, game)?;
+        Ok(backup)
+    }
+
+    fn create_unpruned(profile: &Path, games: &Path, game: &Path) -> Result<Option<Self>> {
+        let files = save_files_in(profile, game);
+        if files.is_empty() {
+            debug!("no save files found for {:?}", game);
+            return Ok(None);
+        }
+
+        if let Some(latest) = Self::list_in(profile, games, game)?.first() {
+            if latest.matches(profile, &files)? {
+                debug!(
+                    "save files for {:?} have not changed, skipping backup",
+                    game
+                );
+                return Ok(None);
+            }
+        }
+
+        let time = Utc::now();
+        let path = backups_dir(profile, games, game).join(time.format(TIME_FORMAT).to_string());
+        if path.exists() {
+            return Ok(None);
+        }
+
+        info!("backing up {} save files for {:?}", files.len(), game);
+        for file in &files {
+            let Ok(relative) = file.strip_prefix(profile) else {
+                continue;
+            };
+            let dest = path.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(file, dest)?;
+        }
+
+        Ok(Some(Self { path, time }))
+    }
+
+    /// Returns the backups of a game, newest first.
+    pub fn list(game: &Path) -> Result<Vec<Self>> {
+        Self::list_in(&ALLIUM_CURRENT_PROFILE_DIR, &ALLIUM_GAMES_DIR, game)
+    }
+
+    fn list_in(profile: &Path, games: &Path, game: &Path) -> Result<Vec<Self>> {
+        let dir = backups_dir(profile, games, game);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups: Vec<Self> = fs::read_dir(dir)?
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_name()?.to_str()?;
+                let time = NaiveDateTime::parse_from_str(name, TIME_FORMAT).ok()?;
+                Some(Self {
+                    time: Utc.from_utc_datetime(&time),
+                    path,
+                })
+            })
+            .collect();
+        backups.sort_unstable_by_key(|b| std::cmp::Reverse(b.time));
+        Ok(backups)
+    }
+
+    /// Returns the files in this backup, relative to the backup directory.
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.path.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir)?.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.path) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Restores the files in this backup, removing save files and states of the game that it
+    /// doesn't contain. The current save files are backed up first so that the restore can be
+    /// undone.
+    pub fn restore(&self, game: &Path) -> Result<()> {
+        self.restore_in(&ALLIUM_CURRENT_PROFILE_DIR, &ALLIUM_GAMES_DIR, game)
+    }
+
+    fn restore_in(&self, profile: &Path, games: &Path, game: &Path) -> Result<()> {
+        if !self.path.is_dir() {
+            bail!("backup {:?} does not exist", self.path);
+        }
+
+        Self::create_unpruned(profile, games, game)?;
+
+        info!("restoring save backup {:?}", self.path);
+        let files = self.files()?;
+        for file in save_files_in(profile, game) {
+            if file
+                .strip_prefix(profile)
+                .is_ok_and(|relative| !files.iter().any(|f| f == relative))
+            {
+                debug!("removing {:?}, which is not in the backup", file);
+                fs::remove_file(file)?;
+            }
+        }
+        for file in files {
+            let dest = profile.join(&file);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(self.path.join(&file), dest)?;
+        }
+
+        prune(profile, games, game)
+    }
+
+    /// Returns true if the backup contains exactly the given files with the same contents.
+    fn matches(&self, profile: &Path, files: &[PathBuf]) -> Result<bool> {
+        if self.files()?.len() != files.len() {
+            return Ok(false);
+        }
+        for file in files {
+            let Ok(relative) = file.strip_prefix(profile) else {
+                return Ok(false);
+            };
+            let backup = self.path.join(relative);
+            if !backup.is_file() || fs::read(&backup)? != fs::read(file)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Exports all saves, states and backups of the current profile to a single archive on the SD
+/// card. Returns the path of the archive.
+pub fn export() -> Result<PathBuf> {
+    fs::create_dir_all(ALLIUM_SAVE_EXPORTS_DIR.as_path())?;
+    let path = ALLIUM_SAVE_EXPORTS_DIR.join(format!(
+        "saves-{}.tar.gz",
+        DateTimeSettings::load()?.format_file_name(&Utc::now())
+    ));
+
+    info!("exporting saves to {:?}", path);
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&path)
+        .arg("-C")
+        .arg(ALLIUM_CURRENT_PROFILE_DIR.as_path())
+        .args(
+            ["saves", "states", "backups"]
+                .iter()
+                .filter(|dir| ALLIUM_CURRENT_PROFILE_DIR.join(dir).exists()),
+        )
+        .status()?;
+    if !status.success() {
+        bail!("tar exited with {}", status);
+    }
+
+    Ok(path)
+}
+
+/// Returns the save files and states of a game. Both are searched along with a folder per core.
+pub fn save_files(game: &Path) -> Vec<PathBuf> {
+    save_files_in(&ALLIUM_CURRENT_PROFILE_DIR, game)
+}
+
+fn save_files_in(profile: &Path, game: &Path) -> Vec<PathBuf> {
+    let Some(stem) = game.file_stem().and_then(OsStr::to_str) else {
+        return Vec::new();
+    };
+
+    let mut dirs = Vec::new();
+    for dir in [profile.join("saves"), profile.join("states")] {
+        dirs.push(dir.clone());
+        if let Ok(entries) = fs::read_dir(&dir) {
+            dirs.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+        }
+    }
+
+    let mut files = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        files.extend(entries.flatten().map(|e| e.path()).filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| is_save_file(name, stem))
+        }));
+    }
+    files.sort();
+    files
+}
+
+/// Returns true if the file name belongs to the game, e.g. `Game.srm` or `Game.state1`.
+/// Screenshots of save states are skipped.
+pub(crate) fn is_save_file(name: &str, stem: &str) -> bool {
+    name.strip_prefix(stem)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .is_some_and(|ext| !ext.is_empty() && !ext.ends_with("png"))
+}
+
+/// Returns the directory the backups of a game are kept in.
+fn backups_dir(profile: &Path, games: &Path, game: &Path) -> PathBuf {
+    let relative = game
+        .strip_prefix(games)
+        .ok()
+        .or_else(|| game.file_name().map(Path::new))
+        .unwrap_or(game);
+    profile.join("backups").join(relative)
+}
+
+/// Removes the oldest backups of a game over the limit.
+fn prune(profile: &Path, games: &Path, game: &Path) -> Result<()> {
+    for backup in SaveBackup::list_in(profile, games, game)?
+        .iter()
+        .skip(SAVE_BACKUP_LIMIT)
+    {
+        debug!("removing old save backup {:?}", backup.path);
+        fs::remove_dir_all(&backup.path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_create_prune_and_restore() {
+        let dir = temp_dir("save-backup");
+        let profile = dir.join("profile");
+        let games = dir.join("Roms");
+        let game = games.join("GBA/Game.gba");
+        let save = profile.join("saves/Game.srm");
+        let state = profile.join("states/mGBA/Game.state1");
+        fs::create_dir_all(save.parent().unwrap()).unwrap();
+        fs::create_dir_all(state.parent().unwrap()).unwrap();
+
+        assert!(SaveBackup::create_in(&profile, &games, &game)
+            .unwrap()
+            .is_none());
+
+        fs::write(&save, "1").unwrap();
+        let created = SaveBackup::create_in(&profile, &games, &game)
+            .unwrap()
+            .unwrap();
+        let backups_dir = profile.join("backups/GBA/Game.gba");
+        assert_eq!(created.path.parent(), Some(backups_dir.as_path()));
+        assert_eq!(
+            created.files().unwrap(),
+            vec![PathBuf::from("saves/Game.srm")]
+        );
+        // Unchanged saves aren't backed up again
+        assert!(SaveBackup::create_in(&profile, &games, &game)
+            .unwrap()
+            .is_none());
+
+        // Backups are named by the second, so this one is moved back so that the backup taken
+        // when restoring gets a name of its own
+        let time = (created.time - Duration::hours(1))
+            .format(TIME_FORMAT)
+            .to_string();
+        fs::rename(&created.path, backups_dir.join(time)).unwrap();
+        let backups = SaveBackup::list_in(&profile, &games, &game).unwrap();
+        assert_eq!(backups.len(), 1);
+        let backup = backups[0].clone();
+
+        // The oldest backups over the limit are removed
+        for days in 0..SAVE_BACKUP_LIMIT as i64 {
+            let time = Utc.timestamp_opt(days * 86400, 0).unwrap();
+            fs::create_dir_all(backups_dir.join(time.format(TIME_FORMAT).to_string())).unwrap();
+        }
+        prune(&profile, &games, &game).unwrap();
+        let backups = SaveBackup::list_in(&profile, &games, &game).unwrap();
+        assert_eq!(backups.len(), SAVE_BACKUP_LIMIT);
+        assert_eq!(backups[0], backup);
+        assert!(!backups_dir.join("19700101T000000Z").exists());
+
+        // Restoring replaces the saves and removes the ones that weren't backed up, after backing
+        // up the current ones
+        fs::write(&save, "2").unwrap();
+        fs::write(&state, "state").unwrap();
+        backup.restore_in(&profile, &games, &game).unwrap();
+        assert_eq!(fs::read_to_string(&save).unwrap(), "1");
+        assert!(!state.exists());
+        let backups = SaveBackup::list_in(&profile, &games, &game).unwrap();
+        assert_eq!(backups.len(), SAVE_BACKUP_LIMIT);
+        assert_eq!(
+            backups[0].files().unwrap(),
+            vec![
+                PathBuf::from("saves/Game.srm"),
+                PathBuf::from("states/mGBA/Game.state1")
+            ]
+        );
+        assert_eq!(backups[1], backup);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_is_save_file() {
+        assert!(is_save_file("Pokemon.srm", "Pokemon"));
+        assert!(is_save_file("Pokemon.state", "Pokemon"));
+        assert!(is_save_file("Pokemon.state2", "Pokemon"));
+        assert!(!is_save_file("Pokemon.state.png", "Pokemon"));
+        assert!(!is_save_file("Pokemon Blue.srm", "Pokemon"));
+        assert!(!is_save_file("Pokemon", "Pokemon"));
+    }
+}
@@ -0,0 +1,804 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hyper::client::HttpConnector;
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    ALLIUM_CURRENT_PROFILE_DIR, ALLIUM_SAVE_SYNC_SETTINGS, ALLIUM_SD_ROOT, SAVE_SYNC_TIMEOUT,
+};
+use crate::profile::Profiles;
+use crate::save_backup::{is_save_file, save_files};
+
+/// Name of the manifest kept on the remote, listing the synced files.
+const MANIFEST: &str = "allium-sync.json";
+
+/// Name of the file in the profile directory that records the files as of the last sync.
+const SYNC_STATE: &str = "sync.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveSyncSettings {
+    pub enabled: bool,
+    pub remote: RemoteSettings,
+}
+
+/// Where saves are synced to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteSettings {
+    /// A folder, e.g. a mounted network share.
+    Folder { path: PathBuf },
+    /// A WebDAV collection. Only plain HTTP is supported.
+    WebDav {
+        url: String,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        password: String,
+    },
+}
+
+impl Default for SaveSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            remote: RemoteSettings::Folder {
+                path: ALLIUM_SD_ROOT.join("Sync"),
+            },
+        }
+    }
+}
+
+impl SaveSyncSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_SAVE_SYNC_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            if let Ok(json) = fs::read_to_string(ALLIUM_SAVE_SYNC_SETTINGS.as_path()) {
+                if let Ok(json) = serde_json::from_str(&json) {
+                    return Ok(json);
+                }
+            }
+            warn!("failed to read state file, removing");
+            fs::remove_file(ALLIUM_SAVE_SYNC_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string(&self).unwrap();
+        File::create(ALLIUM_SAVE_SYNC_SETTINGS.as_path())?.write_all(json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// The version of a file, identified by its hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileVersion {
+    pub hash: u32,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+}
+
+impl FileVersion {
+    fn new(data: &[u8], modified: DateTime<Utc>) -> Self {
+        Self {
+            hash: crc32fast::hash(data),
+            size: data.len() as u64,
+            modified,
+        }
+    }
+
+    fn same_contents(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.size == other.size
+    }
+}
+
+/// A file that changed both locally and on the remote since the last sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Path relative to the profile directory, e.g. `saves/Game.srm`.
+    pub path: String,
+    pub local: FileVersion,
+    pub remote: FileVersion,
+}
+
+impl Conflict {
+    /// Whether the remote file was modified after the local one.
+    pub fn is_remote_newer(&self) -> bool {
+        self.remote.modified > self.local.modified
+    }
+}
+
+/// Which side to keep when resolving conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    /// Keeps whichever side of each conflict was modified last.
+    KeepNewer,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+type Manifest = BTreeMap<String, FileVersion>;
+
+/// Storage that files are synced to. Paths are relative and `/` separated.
+#[async_trait]
+pub trait Remote: std::fmt::Debug + Send + Sync {
+    /// Returns the contents of a file, or None if it doesn't exist.
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>>;
+    async fn put(&self, path: &str, data: Vec<u8>) -> Result<()>;
+}
+
+/// Syncs save files and states between a profile directory and a remote.
+///
+/// Files are compared by hash against the versions recorded at the last sync: a file that only
+/// changed on one side is copied to the other, and a file that changed on both is a conflict
+/// that has to be resolved with [`SaveSync::resolve`], e.g. by keeping the side modified last.
+/// Files are never deleted.
+#[derive(Debug)]
+pub struct SaveSync {
+    local_root: PathBuf,
+    remote: Box<dyn Remote>,
+}
+
+impl SaveSync {
+    pub fn new(local_root: PathBuf, remote: Box<dyn Remote>) -> Self {
+        Self { local_root, remote }
+    }
+
+    /// Returns the sync for the current profile, or None if save sync is disabled.
+    pub fn load() -> Result<Option<Self>> {
+        let settings = SaveSyncSettings::load()?;
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let profile = Profiles::load()?.active;
+        let remote: Box<dyn Remote> = match settings.remote {
+            RemoteSettings::Folder { path } => Box::new(FolderRemote::new(path.join(&profile))),
+            RemoteSettings::WebDav {
+                url,
+                username,
+                password,
+            } => Box::new(WebDavRemote::new(
+                format!("{}/{}", url.trim_end_matches('/'), profile),
+                username,
+                password,
+            )),
+        };
+        Ok(Some(Self::new(ALLIUM_CURRENT_PROFILE_DIR.clone(), remote)))
+    }
+
+    /// Syncs the save files and states of a game.
+    pub async fn sync_game(&self, game: &Path) -> Result<SyncReport> {
+        let Some(stem) = game.file_stem().and_then(OsStr::to_str) else {
+            return Ok(SyncReport::default());
+        };
+        let mut paths: Vec<String> = save_files(game)
+            .iter()
+            .filter_map(|file| self.relative(file))
+            .collect();
+        let manifest = self.manifest().await?;
+        paths.extend(
+            manifest
+                .keys()
+                .filter(|path| is_game_file(path, stem))
+                .cloned(),
+        );
+        paths.sort_unstable();
+        paths.dedup();
+        self.sync(&paths).await
+    }
+
+    /// Syncs the given files, relative to the profile directory.
+    pub async fn sync(&self, paths: &[String]) -> Result<SyncReport> {
+        let manifest = self.manifest().await?;
+        let mut uploaded = Manifest::new();
+        let mut state = self.state()?;
+        let mut report = SyncReport::default();
+
+        for path in paths {
+            let local = self.local_version(path)?;
+            let remote = manifest.get(path).copied();
+            let base = state.get(path).copied();
+            match (local, remote) {
+                (Some(local), Some(remote)) if local.same_contents(&remote) => {
+                    state.insert(path.clone(), remote);
+                }
+                (Some(local), Some(remote)) => match base {
+                    Some(base) if base.same_contents(&local) => {
+                        self.download(path, remote, &mut state).await?;
+                        report.downloaded += 1;
+                    }
+                    Some(base) if base.same_contents(&remote) => {
+                        self.upload(path, local, &mut uploaded, &mut state).await?;
+                        report.uploaded += 1;
+                    }
+                    _ => {
+                        let conflict = Conflict {
+                            path: path.clone(),
+                            local,
+                            remote,
+                        };
+                        warn!(
+                            "save sync conflict {}: device modified {}, remote modified {}",
+                            path, local.modified, remote.modified
+                        );
+                        report.conflicts.push(conflict);
+                    }
+                },
+                (Some(local), None) => {
+                    self.upload(path, local, &mut uploaded, &mut state).await?;
+                    report.uploaded += 1;
+                }
+                (None, Some(remote)) => {
+                    self.download(path, remote, &mut state).await?;
+                    report.downloaded += 1;
+                }
+                (None, None) => {}
+            }
+        }
+
+        self.merge_manifest(&uploaded).await?;
+        self.save_state(&state)?;
+
+        info!(
+            "synced saves: {} uploaded, {} downloaded, {} conflicts",
+            report.uploaded,
+            report.downloaded,
+            report.conflicts.len()
+        );
+        Ok(report)
+    }
+
+    /// Resolves conflicts by copying the kept side over the other.
+    pub async fn resolve(&self, conflicts: &[Conflict], resolution: Resolution) -> Result<()> {
+        let mut uploaded = Manifest::new();
+        let mut state = self.state()?;
+        for conflict in conflicts {
+            let keep_remote = match resolution {
+                Resolution::KeepLocal => false,
+                Resolution::KeepRemote => true,
+                Resolution::KeepNewer => conflict.is_remote_newer(),
+            };
+            info!(
+                "resolving conflict {}: keeping {}",
+                conflict.path,
+                if keep_remote { "remote" } else { "local" }
+            );
+            if keep_remote {
+                self.download(&conflict.path, conflict.remote, &mut state)
+                    .await?;
+            } else {
+                let Some(local) = self.local_version(&conflict.path)? else {
+                    bail!("{} no longer exists", conflict.path);
+                };
+                self.upload(&conflict.path, local, &mut uploaded, &mut state)
+                    .await?;
+            }
+        }
+        self.merge_manifest(&uploaded).await?;
+        self.save_state(&state)
+    }
+
+    async fn upload(
+        &self,
+        path: &str,
+        version: FileVersion,
+        uploaded: &mut Manifest,
+        state: &mut Manifest,
+    ) -> Result<()> {
+        debug!("uploading {}", path);
+        let data = fs::read(self.local_root.join(path))?;
+        self.remote.put(path, data).await?;
+        uploaded.insert(path.to_string(), version);
+        state.insert(path.to_string(), version);
+        Ok(())
+    }
+
+    async fn download(&self, path: &str, version: FileVersion, state: &mut Manifest) -> Result<()> {
+        debug!("downloading {}", path);
+        let Some(data) = self.remote.get(path).await? else {
+            bail!("{} is missing from the remote", path);
+        };
+        let dest = self.local_root.join(path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(dest, data)?;
+        state.insert(path.to_string(), version);
+        Ok(())
+    }
+
+    fn local_version(&self, path: &str) -> Result<Option<FileVersion>> {
+        let file = self.local_root.join(path);
+        if !file.is_file() {
+            return Ok(None);
+        }
+        let modified = fs::metadata(&file)?.modified()?;
+        Ok(Some(FileVersion::new(&fs::read(file)?, modified.into())))
+    }
+
+    fn relative(&self, file: &Path) -> Option<String> {
+        let relative = file.strip_prefix(&self.local_root).ok()?;
+        let parts: Option<Vec<&str>> = relative.iter().map(OsStr::to_str).collect();
+        Some(parts?.join("/"))
+    }
+
+    async fn manifest(&self) -> Result<Manifest> {
+        Ok(match self.remote.get(MANIFEST).await? {
+            Some(data) => serde_json::from_slice(&data)?,
+            None => Manifest::new(),
+        })
+    }
+
+    /// Records uploaded files in the remote manifest. It's read again first, so that files
+    /// another device synced in the meantime are kept.
+    async fn merge_manifest(&self, uploaded: &Manifest) -> Result<()> {
+        if uploaded.is_empty() {
+            return Ok(());
+        }
+        let mut manifest = self.manifest().await?;
+        manifest.extend(
+            uploaded
+                .iter()
+                .map(|(path, version)| (path.clone(), *version)),
+        );
+        self.remote
+            .put(MANIFEST, serde_json::to_vec(&manifest)?)
+            .await
+    }
+
+    /// Returns the versions of the files as of the last sync.
+    fn state(&self) -> Result<Manifest> {
+        let path = self.local_root.join(SYNC_STATE);
+        if !path.exists() {
+            return Ok(Manifest::new());
+        }
+        match serde_json::from_slice(&fs::read(&path)?) {
+            Ok(state) => Ok(state),
+            Err(e) => {
+                warn!("failed to read sync state, starting over: {}", e);
+                Ok(Manifest::new())
+            }
+        }
+    }
+
+    fn save_state(&self, state: &Manifest) -> Result<()> {
+        fs::write(self.local_root.join(SYNC_STATE), serde_json::to_vec(state)?)?;
+        Ok(())
+    }
+}
+
+/// Returns true if the synced path is a save file or state of the game.
+fn is_game_file(path: &str, stem: &str) -> bool {
+    (path.starts_with("saves/") || path.starts_with("states/"))
+        && path
+            .rsplit('/')
+            .next()
+            .is_some_and(|name| is_save_file(name, stem))
+}
+
+/// Syncs to a folder, e.g. a mounted network share or another SD card.
+#[derive(Debug)]
+pub struct FolderRemote {
+    root: PathBuf,
+}
+
+impl FolderRemote {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait]
+impl Remote for FolderRemote {
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.root.join(path)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put(&self, path: &str, data: Vec<u8>) -> Result<()> {
+        let dest = self.root.join(path);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(dest, data).await?;
+        Ok(())
+    }
+}
+
+/// Syncs to a WebDAV collection over HTTP.
+#[derive(Debug)]
+pub struct WebDavRemote {
+    url: String,
+    authorization: Option<String>,
+    client: Client<HttpConnector>,
+}
+
+impl WebDavRemote {
+    pub fn new(url: String, username: String, password: String) -> Self {
+        let authorization = (!username.is_empty()).then(|| {
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password))
+            )
+        });
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            authorization,
+            client: Client::new(),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Body,
+    ) -> Result<hyper::Response<Body>> {
+        let path = path
+            .split('/')
+            .map(|part| percent_encoding::utf8_percent_encode(part, PATH_SEGMENT).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}/{}", self.url, path));
+        if let Some(authorization) = &self.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+        let request = request.body(body)?;
+        tokio::time::timeout(SAVE_SYNC_TIMEOUT, self.client.request(request))
+            .await
+            .map_err(|_| anyhow!("WebDAV request timed out"))?
+            .map_err(Into::into)
+    }
+
+    /// Creates the parent collections of a path, ignoring ones that already exist.
+    async fn create_parents(&self, path: &str) -> Result<()> {
+        let parts: Vec<&str> = path.split('/').collect();
+        for i in 1..parts.len() {
+            let dir = format!("{}/", parts[..i].join("/"));
+            let status = self.request(mkcol(), &dir, Body::empty()).await?.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                bail!("failed to create {}: {}", dir, status);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Characters that are escaped in WebDAV path segments.
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+fn mkcol() -> Method {
+    Method::from_bytes(b"MKCOL").unwrap()
+}
+
+#[async_trait]
+impl Remote for WebDavRemote {
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.request(Method::GET, path, Body::empty()).await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(
+                hyper::body::to_bytes(response.into_body()).await?.to_vec(),
+            )),
+            status => bail!("failed to download {}: {}", path, status),
+        }
+    }
+
+    async fn put(&self, path: &str, data: Vec<u8>) -> Result<()> {
+        for attempt in 0..2 {
+            let response = self
+                .request(Method::PUT, path, Body::from(data.clone()))
+                .await?;
+            match response.status() {
+                // The parent collection doesn't exist yet
+                StatusCode::CONFLICT if attempt == 0 => self.create_parents(path).await?,
+                status if status.is_success() => return Ok(()),
+                status => bail!("failed to upload {}: {}", path, status),
+            }
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    /// Two devices syncing through the same remote.
+    async fn test_devices(name: &str, remote: impl Fn() -> Box<dyn Remote>) {
+        let a_dir = temp_dir(&format!("save-sync-{}-a", name));
+        let b_dir = temp_dir(&format!("save-sync-{}-b", name));
+        let a = SaveSync::new(a_dir.clone(), remote());
+        let b = SaveSync::new(b_dir.clone(), remote());
+        fs::create_dir_all(a_dir.join("saves")).unwrap();
+        fs::create_dir_all(b_dir.join("saves")).unwrap();
+        let paths = vec!["saves/Game.srm".to_string()];
+
+        // New file is uploaded, then downloaded on the other device
+        fs::write(a_dir.join("saves/Game.srm"), "1").unwrap();
+        assert_eq!(a.sync(&paths).await.unwrap().uploaded, 1);
+        assert_eq!(b.sync(&paths).await.unwrap().downloaded, 1);
+        assert_eq!(
+            fs::read_to_string(b_dir.join("saves/Game.srm")).unwrap(),
+            "1"
+        );
+
+        // Change on one side is copied to the other
+        fs::write(b_dir.join("saves/Game.srm"), "2").unwrap();
+        assert_eq!(b.sync(&paths).await.unwrap().uploaded, 1);
+        assert_eq!(a.sync(&paths).await.unwrap().downloaded, 1);
+        assert_eq!(
+            fs::read_to_string(a_dir.join("saves/Game.srm")).unwrap(),
+            "2"
+        );
+
+        // Changes on both sides conflict
+        fs::write(a_dir.join("saves/Game.srm"), "3a").unwrap();
+        fs::write(b_dir.join("saves/Game.srm"), "3b").unwrap();
+        assert_eq!(a.sync(&paths).await.unwrap().uploaded, 1);
+        let report = b.sync(&paths).await.unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(
+            fs::read_to_string(b_dir.join("saves/Game.srm")).unwrap(),
+            "3b"
+        );
+
+        // Resolving keeps the chosen side
+        b.resolve(&report.conflicts, Resolution::KeepRemote)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(b_dir.join("saves/Game.srm")).unwrap(),
+            "3a"
+        );
+        assert!(b.sync(&paths).await.unwrap().conflicts.is_empty());
+
+        fs::remove_dir_all(a_dir).ok();
+        fs::remove_dir_all(b_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_folder_sync() {
+        let remote_dir = temp_dir("save-sync-folder-remote");
+        let remote = remote_dir.clone();
+        test_devices("folder", move || {
+            Box::new(FolderRemote::new(remote.clone()))
+        })
+        .await;
+        fs::remove_dir_all(remote_dir).ok();
+    }
+
+    /// Minimal in-memory WebDAV server supporting GET, PUT and MKCOL.
+    async fn webdav_server() -> SocketAddr {
+        let files: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+        let make_service = make_service_fn(move |_| {
+            let files = Arc::clone(&files);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let files = Arc::clone(&files);
+                    async move {
+                        let path = req.uri().path().to_string();
+                        let mut response = Response::new(Body::empty());
+                        match req.method().as_str() {
+                            "GET" => match files.lock().unwrap().get(&path) {
+                                Some(data) => *response.body_mut() = Body::from(data.clone()),
+                                None => *response.status_mut() = StatusCode::NOT_FOUND,
+                            },
+                            "PUT" => {
+                                let parent = &path[..path.rfind('/').unwrap() + 1];
+                                if parent != "/dav/" && !files.lock().unwrap().contains_key(parent)
+                                {
+                                    *response.status_mut() = StatusCode::CONFLICT;
+                                } else {
+                                    let data = hyper::body::to_bytes(req.into_body()).await?;
+                                    files.lock().unwrap().insert(path, data.to_vec());
+                                    *response.status_mut() = StatusCode::CREATED;
+                                }
+                            }
+                            "MKCOL" => {
+                                files.lock().unwrap().insert(path, Vec::new());
+                                *response.status_mut() = StatusCode::CREATED;
+                            }
+                            _ => *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED,
+                        }
+                        Ok::<_, hyper::Error>(response)
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_webdav_sync() {
+        let addr = webdav_server().await;
+        test_devices("webdav", move || {
+            Box::new(WebDavRemote::new(
+                format!("http://{}/dav", addr),
+                "user".to_string(),
+                "pass".to_string(),
+            ))
+        })
+        .await;
+    }
+
+    fn write_aged(path: &Path, contents: &str, age: std::time::Duration) {
+        fs::write(path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - age)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_keep_newer() {
+        let remote_dir = temp_dir("save-sync-newer-remote");
+        let a_dir = temp_dir("save-sync-newer-a");
+        let b_dir = temp_dir("save-sync-newer-b");
+        let a = SaveSync::new(
+            a_dir.clone(),
+            Box::new(FolderRemote::new(remote_dir.clone())),
+        );
+        let b = SaveSync::new(
+            b_dir.clone(),
+            Box::new(FolderRemote::new(remote_dir.clone())),
+        );
+        fs::create_dir_all(a_dir.join("saves")).unwrap();
+        fs::create_dir_all(b_dir.join("saves")).unwrap();
+        let paths = vec!["saves/Game.srm".to_string(), "saves/Game.state".to_string()];
+        let hour = std::time::Duration::from_secs(3600);
+
+        // The save was last changed on a, the state on b
+        write_aged(
+            &a_dir.join("saves/Game.srm"),
+            "a",
+            std::time::Duration::ZERO,
+        );
+        write_aged(&a_dir.join("saves/Game.state"), "a", hour);
+        write_aged(&b_dir.join("saves/Game.srm"), "b", hour);
+        write_aged(
+            &b_dir.join("saves/Game.state"),
+            "b",
+            std::time::Duration::ZERO,
+        );
+        assert_eq!(a.sync(&paths).await.unwrap().uploaded, 2);
+
+        let report = b.sync(&paths).await.unwrap();
+        assert_eq!(report.conflicts.len(), 2);
+        assert!(report.conflicts[0].is_remote_newer());
+        assert!(!report.conflicts[1].is_remote_newer());
+
+        b.resolve(&report.conflicts, Resolution::KeepNewer)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(b_dir.join("saves/Game.srm")).unwrap(),
+            "a"
+        );
+        assert_eq!(
+            fs::read_to_string(b_dir.join("saves/Game.state")).unwrap(),
+            "b"
+        );
+        assert_eq!(a.sync(&paths).await.unwrap().downloaded, 1);
+        assert_eq!(
+            fs::read_to_string(a_dir.join("saves/Game.state")).unwrap(),
+            "b"
+        );
+
+        fs::remove_dir_all(remote_dir).ok();
+        fs::remove_dir_all(a_dir).ok();
+        fs::remove_dir_all(b_dir).ok();
+    }
+
+    /// Remote that another device syncs a file to while `saves/Game.srm` is being uploaded.
+    #[derive(Debug)]
+    struct RacingRemote {
+        inner: FolderRemote,
+        other: SaveSync,
+    }
+
+    #[async_trait]
+    impl Remote for RacingRemote {
+        async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+            self.inner.get(path).await
+        }
+
+        async fn put(&self, path: &str, data: Vec<u8>) -> Result<()> {
+            if path == "saves/Game.srm" {
+                self.other.sync(&["saves/Other.srm".to_string()]).await?;
+            }
+            self.inner.put(path, data).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manifest_merge() {
+        let remote_dir = temp_dir("save-sync-merge-remote");
+        let a_dir = temp_dir("save-sync-merge-a");
+        let b_dir = temp_dir("save-sync-merge-b");
+        fs::create_dir_all(a_dir.join("saves")).unwrap();
+        fs::create_dir_all(b_dir.join("saves")).unwrap();
+        fs::write(a_dir.join("saves/Game.srm"), "a").unwrap();
+        fs::write(b_dir.join("saves/Other.srm"), "b").unwrap();
+        let a = SaveSync::new(
+            a_dir.clone(),
+            Box::new(RacingRemote {
+                inner: FolderRemote::new(remote_dir.clone()),
+                other: SaveSync::new(
+                    b_dir.clone(),
+                    Box::new(FolderRemote::new(remote_dir.clone())),
+                ),
+            }),
+        );
+
+        assert_eq!(
+            a.sync(&["saves/Game.srm".to_string()])
+                .await
+                .unwrap()
+                .uploaded,
+            1
+        );
+        let manifest = a.manifest().await.unwrap();
+        assert!(manifest.contains_key("saves/Game.srm"));
+        assert!(manifest.contains_key("saves/Other.srm"));
+
+        fs::remove_dir_all(remote_dir).ok();
+        fs::remove_dir_all(a_dir).ok();
+        fs::remove_dir_all(b_dir).ok();
+    }
+
+    #[test]
+    fn test_is_game_file() {
+        assert!(is_game_file("saves/Game.srm", "Game"));
+        assert!(is_game_file("states/mGBA/Game.state1", "Game"));
+        assert!(!is_game_file("states/Game.state.png", "Game"));
+        assert!(!is_game_file("backups/Game.srm", "Game"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::{RETROARCH_FILTERS_DIR, RETROARCH_OVERLAYS_DIR};
+use crate::overrides::Overrides;
+
+const VIDEO_FILTER: &str = "video_filter";
+const INPUT_OVERLAY: &str = "input_overlay";
+const INPUT_OVERLAY_ENABLE: &str = "input_overlay_enable";
+
+/// Config keys set by [`set_video_filter`] and [`set_overlay`].
+pub const KEYS: [&str; 3] = [VIDEO_FILTER, INPUT_OVERLAY, INPUT_OVERLAY_ENABLE];
+
+/// A video filter or overlay on the SD card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    /// Path relative to the presets folder, without the extension.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Lists the video filters, which the device's RetroArch uses in place of shader presets.
+pub fn video_filters() -> Vec<Preset> {
+    list_in(&RETROARCH_FILTERS_DIR, "filt")
+}
+
+pub fn overlays() -> Vec<Preset> {
+    list_in(&RETROARCH_OVERLAYS_DIR, "cfg")
+}
+
+/// Recursively lists the files with the given extension, sorted by name.
+fn list_in(dir: &Path, extension: &str) -> Vec<Preset> {
+    let mut presets = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().and_then(|e| e.to_str()) == Some(extension) {
+                let Ok(relative) = path.strip_prefix(dir) else {
+                    continue;
+                };
+                presets.push(Preset {
+                    name: relative.with_extension("").to_string_lossy().into_owned(),
+                    path,
+                });
+            }
+        }
+    }
+    presets.sort_unstable_by_key(|p| p.name.to_lowercase());
+    presets
+}
+
+/// Returns the video filter set in the overrides. `Some(None)` means the filter is turned off.
+pub fn video_filter(overrides: &Overrides) -> Option<Option<PathBuf>> {
+    overrides
+        .get(VIDEO_FILTER)
+        .map(|path| (!path.is_empty()).then(|| PathBuf::from(path)))
+}
+
+pub fn set_video_filter(overrides: &mut Overrides, filter: Option<&Path>) {
+    match filter {
+        Some(path) => overrides.set(VIDEO_FILTER, path.to_string_lossy()),
+        None => overrides.set(VIDEO_FILTER, ""),
+    }
+}
+
+/// Returns the overlay set in the overrides. `Some(None)` means the overlay is turned off.
+pub fn overlay(overrides: &Overrides) -> Option<Option<PathBuf>> {
+    match overrides.get(INPUT_OVERLAY_ENABLE) {
+        Some("false") => Some(None),
+        _ => overrides
+            .get(INPUT_OVERLAY)
+            .map(|path| (!path.is_empty()).then(|| PathBuf::from(path))),
+    }
+}
+
+pub fn set_overlay(overrides: &mut Overrides, overlay: Option<&Path>) {
+    match overlay {
+        Some(path) => {
+            overrides.set(INPUT_OVERLAY, path.to_string_lossy());
+            overrides.set(INPUT_OVERLAY_ENABLE, "true");
+        }
+        None => {
+            overrides.remove(INPUT_OVERLAY);
+            overrides.set(INPUT_OVERLAY_ENABLE, "false");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        let dir = temp_dir("shaders-list");
+        fs::create_dir_all(dir.join("Upscale")).unwrap();
+        fs::write(dir.join("Scanline2x.filt"), "").unwrap();
+        fs::write(dir.join("Upscale/Upscale_256-240x4.filt"), "").unwrap();
+        fs::write(dir.join("2xBR.filt"), "").unwrap();
+        fs::write(dir.join("README.txt"), "").unwrap();
+
+        let presets = list_in(&dir, "filt");
+        assert_eq!(
+            presets.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["2xBR", "Scanline2x", "Upscale/Upscale_256-240x4"]
+        );
+        assert_eq!(presets[1].path, dir.join("Scanline2x.filt"));
+
+        assert!(list_in(&dir.join("missing"), "filt").is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
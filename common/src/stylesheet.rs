@@ -113,6 +113,9 @@ impl StylesheetFont {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Stylesheet {
     pub enable_box_art: bool,
+    /// Swaps A/B and X/Y in Allium's menus, for Xbox-style confirm and back buttons.
+    #[serde(default)]
+    pub swap_buttons: bool,
     pub foreground_color: Color,
     pub background_color: Color,
     pub highlight_color: Color,
@@ -218,6 +221,7 @@ impl Default for Stylesheet {
     fn default() -> Self {
         Self {
             enable_box_art: true,
+            swap_buttons: false,
             foreground_color: Color::new(255, 255, 255),
             background_color: Color::new(0, 0, 0),
             highlight_color: Color::new(151, 135, 187),
@@ -0,0 +1,14 @@
+//! Helpers for the tests of every crate in the workspace. Other crates get them by enabling the
+//! `testing` feature in their dev-dependencies.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns an empty directory for a test to work in. `name` must be unique across the workspace's
+/// tests, which run in parallel; the process ID keeps concurrent test runs apart.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("allium-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("failed to create test directory");
+    dir
+}
@@ -0,0 +1,356 @@
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use ed25519_compact::{PublicKey, Signature};
+use hyper::{Body, Client, Request, StatusCode};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::constants::{
+    ALLIUM_BASE_DIR, ALLIUM_UPDATE_DIR, ALLIUM_UPDATE_DROP_DIR, ALLIUM_UPDATE_SETTINGS,
+    ALLIUM_VERSION, UPDATE_PUBLIC_KEY, UPDATE_TIMEOUT,
+};
+
+/// Name of the release manifest, both in the SD card drop folder and on the update server.
+const MANIFEST_NAME: &str = "update.json";
+
+/// Marker left in `.allium/bin` by the updater script until the new version has started
+/// successfully. If it's still there when alliumd exits, the updater rolls back.
+const PENDING_MARKER: &str = "bin/.pending";
+
+/// Marker asking the updater script to switch back to the previous version on the next boot.
+const ROLLBACK_MARKER: &str = "rollback";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// URL of the release manifest. Only plain HTTP is supported, which is fine because the
+    /// release is verified against the embedded public key.
+    pub url: String,
+}
+
+impl UpdateSettings {
+    pub fn load() -> Result<Self> {
+        if ALLIUM_UPDATE_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            if let Ok(json) = fs::read_to_string(ALLIUM_UPDATE_SETTINGS.as_path()) {
+                if let Ok(json) = serde_json::from_str(&json) {
+                    return Ok(json);
+                }
+            }
+            warn!("failed to read update settings, removing");
+            fs::remove_file(ALLIUM_UPDATE_SETTINGS.as_path())?;
+        }
+        Ok(Self::default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string(&self).unwrap();
+        File::create(ALLIUM_UPDATE_SETTINGS.as_path())?.write_all(json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Release manifest, published next to the release archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the release, e.g. `v0.17.0`.
+    pub version: String,
+    #[serde(default)]
+    pub changelog: String,
+    /// Location of the `.tar.gz` archive containing `bin/`, relative to the manifest.
+    pub archive: String,
+    /// Base64 Ed25519 signature of the release, see [`signed_message`].
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Folder(PathBuf),
+    Url(String),
+}
+
+/// A release that is newer than the running version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub manifest: Manifest,
+    source: Source,
+}
+
+impl Release {
+    /// Looks for a newer release, first in the SD card drop folder for offline installs, then
+    /// at the configured URL.
+    pub async fn check() -> Result<Option<Release>> {
+        let manifest_path = ALLIUM_UPDATE_DROP_DIR.join(MANIFEST_NAME);
+        if manifest_path.exists() {
+            let manifest: Manifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+            if is_newer(&manifest.version, ALLIUM_VERSION) {
+                return Ok(Some(Release {
+                    manifest,
+                    source: Source::Folder(ALLIUM_UPDATE_DROP_DIR.clone()),
+                }));
+            }
+        }
+
+        let settings = UpdateSettings::load()?;
+        if settings.url.is_empty() {
+            return Ok(None);
+        }
+        let manifest: Manifest = serde_json::from_slice(&fetch(&settings.url).await?)?;
+        if is_newer(&manifest.version, ALLIUM_VERSION) {
+            return Ok(Some(Release {
+                manifest,
+                source: Source::Url(settings.url),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Downloads and verifies the archive, then extracts it to be applied on the next boot.
+    pub async fn stage(&self) -> Result<()> {
+        let archive = match &self.source {
+            Source::Folder(dir) => tokio::fs::read(dir.join(&self.manifest.archive)).await?,
+            Source::Url(url) => fetch(&resolve_url(url, &self.manifest.archive)).await?,
+        };
+        let public_key = UPDATE_PUBLIC_KEY
+            .filter(|key| !key.is_empty())
+            .context("this build has no update public key")?;
+        verify(
+            public_key,
+            &signed_message(&self.manifest, &archive),
+            &self.manifest.signature,
+        )?;
+        // The manifest was only trusted to decide whether to download, so check the now
+        // verified version again to refuse downgrades to older signed releases
+        if !is_newer(&self.manifest.version, ALLIUM_VERSION) {
+            bail!(
+                "update {} is not newer than v{}",
+                self.manifest.version,
+                ALLIUM_VERSION
+            );
+        }
+        info!("verified update {}", self.manifest.version);
+
+        // Extract next to the staging directory and move it into place once complete, so
+        // that the updater never sees a partial update.
+        let staging = ALLIUM_BASE_DIR.join("update.tmp");
+        if staging.exists() {
+            tokio::fs::remove_dir_all(&staging).await?;
+        }
+        tokio::fs::create_dir_all(&staging).await?;
+        let archive_path = staging.join("update.tar.gz");
+        tokio::fs::write(&archive_path, archive).await?;
+
+        let status = tokio::process::Command::new("tar")
+            .arg("-xzf")
+            .arg(&archive_path)
+            .arg("-C")
+            .arg(&staging)
+            .status()
+            .await?;
+        tokio::fs::remove_file(&archive_path).await?;
+        if !status.success() || !staging.join("bin").is_dir() {
+            tokio::fs::remove_dir_all(&staging).await.ok();
+            bail!("update archive is invalid");
+        }
+
+        if ALLIUM_UPDATE_DIR.exists() {
+            tokio::fs::remove_dir_all(ALLIUM_UPDATE_DIR.as_path()).await?;
+        }
+        tokio::fs::rename(&staging, ALLIUM_UPDATE_DIR.as_path()).await?;
+        info!("staged update {}", self.manifest.version);
+        Ok(())
+    }
+}
+
+/// Returns true if an update or rollback will be applied on the next boot.
+pub fn is_staged() -> bool {
+    ALLIUM_UPDATE_DIR.join("bin").is_dir() || ALLIUM_UPDATE_DIR.join(ROLLBACK_MARKER).exists()
+}
+
+/// Returns true if the previous version was kept and can be rolled back to.
+pub fn can_rollback() -> bool {
+    ALLIUM_BASE_DIR.join("bin.prev").is_dir()
+}
+
+/// Switches back to the previous version on the next boot, replacing any staged update.
+pub fn stage_rollback() -> Result<()> {
+    if !can_rollback() {
+        bail!("no previous version to roll back to");
+    }
+    if ALLIUM_UPDATE_DIR.exists() {
+        fs::remove_dir_all(ALLIUM_UPDATE_DIR.as_path())?;
+    }
+    fs::create_dir_all(ALLIUM_UPDATE_DIR.as_path())?;
+    File::create(ALLIUM_UPDATE_DIR.join(ROLLBACK_MARKER))?;
+    info!("staged rollback");
+    Ok(())
+}
+
+/// Marks the running version as working, so that the updater doesn't roll it back.
+pub fn confirm() -> Result<()> {
+    let marker = ALLIUM_BASE_DIR.join(PENDING_MARKER);
+    if marker.exists() {
+        info!("confirming update to v{}", ALLIUM_VERSION);
+        fs::remove_file(marker)?;
+    }
+    Ok(())
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>> {
+    let request = Request::get(url).body(Body::empty())?;
+    let response = tokio::time::timeout(UPDATE_TIMEOUT, async {
+        let response = Client::new().request(request).await?;
+        match response.status() {
+            StatusCode::OK => Ok(hyper::body::to_bytes(response.into_body()).await?),
+            status => Err(anyhow!("failed to download {}: {}", url, status)),
+        }
+    })
+    .await
+    .map_err(|_| anyhow!("update request timed out"))??;
+    Ok(response.to_vec())
+}
+
+/// Resolves the archive location against the manifest URL.
+fn resolve_url(manifest_url: &str, archive: &str) -> String {
+    if archive.contains("://") {
+        return archive.to_string();
+    }
+    match manifest_url.rfind('/') {
+        Some(i) => format!("{}/{}", &manifest_url[..i], archive),
+        None => archive.to_string(),
+    }
+}
+
+/// The message a release is signed over: its version, the SHA-256 of its archive and its
+/// changelog, so that none of them can be swapped without invalidating the signature. This
+/// must match what `make package-update` signs.
+fn signed_message(manifest: &Manifest, archive: &[u8]) -> Vec<u8> {
+    let hash: String = Sha256::digest(archive)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!(
+        "allium-update\n{}\n{}\n{}",
+        manifest.version, hash, manifest.changelog
+    )
+    .into_bytes()
+}
+
+/// Checks a base64 Ed25519 signature of `data` against a base64 public key.
+fn verify(public_key: &str, data: &[u8], signature: &str) -> Result<()> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let public_key = PublicKey::from_slice(&engine.decode(public_key)?)
+        .map_err(|e| anyhow!("invalid public key: {}", e))?;
+    let signature = Signature::from_slice(&engine.decode(signature.trim())?)
+        .map_err(|e| anyhow!("invalid signature: {}", e))?;
+    public_key
+        .verify(data, &signature)
+        .context("update signature does not match")
+}
+
+/// Compares dotted versions such as `v0.16.1`, ignoring the leading `v`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(a).cmp(&parse(b))
+}
+
+fn is_newer(version: &str, current: &str) -> bool {
+    compare_versions(version, current) == Ordering::Greater
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_compact::{KeyPair, Seed};
+
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let key_pair = KeyPair::from_seed(Seed::new([7; 32]));
+        let public_key = engine.encode(*key_pair.pk);
+        let manifest = Manifest {
+            version: "v0.17.0".to_string(),
+            changelog: "Fixes".to_string(),
+            archive: "allium.tar.gz".to_string(),
+            signature: String::new(),
+        };
+        let archive = b"archive contents";
+        let message = signed_message(&manifest, archive);
+        let signature = engine.encode(*key_pair.sk.sign(&message, None));
+
+        assert!(verify(&public_key, &message, &signature).is_ok());
+        let tampered = signed_message(&manifest, b"tampered contents");
+        assert!(verify(&public_key, &tampered, &signature).is_err());
+        // The raw archive isn't what releases are signed over
+        assert!(verify(&public_key, archive, &signature).is_err());
+        let other_key = engine.encode(*KeyPair::from_seed(Seed::new([8; 32])).pk);
+        assert!(verify(&other_key, &message, &signature).is_err());
+        assert!(verify(&public_key, &message, "not a signature").is_err());
+    }
+
+    #[test]
+    fn test_signed_message() {
+        let manifest = Manifest {
+            version: "v0.17.0".to_string(),
+            changelog: "Fixes".to_string(),
+            archive: "allium.tar.gz".to_string(),
+            signature: String::new(),
+        };
+        // Matches `printf 'allium-update\n%s\n%s\n%s' v0.17.0 "$(printf abc | sha256sum)" Fixes`
+        assert_eq!(
+            signed_message(&manifest, b"abc"),
+            b"allium-update\nv0.17.0\n\
+            ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\nFixes"
+        );
+
+        let older = Manifest {
+            version: "v0.16.0".to_string(),
+            ..manifest.clone()
+        };
+        assert_ne!(
+            signed_message(&manifest, b"abc"),
+            signed_message(&older, b"abc")
+        );
+        assert_ne!(
+            signed_message(&manifest, b"abc"),
+            signed_message(&manifest, b"abd")
+        );
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert!(is_newer("v0.17.0", "0.16.1"));
+        assert!(is_newer("v0.16.10", "v0.16.9"));
+        assert!(is_newer("1.0", "0.99.99"));
+        assert!(!is_newer("v0.16.1", "0.16.1"));
+        assert!(!is_newer("v0.16.0", "0.16.1"));
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        assert_eq!(
+            resolve_url("http://example.com/allium/update.json", "allium.tar.gz"),
+            "http://example.com/allium/allium.tar.gz"
+        );
+        assert_eq!(
+            resolve_url(
+                "http://example.com/update.json",
+                "http://mirror.example.com/allium.tar.gz"
+            ),
+            "http://mirror.example.com/allium.tar.gz"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::constants::RETROARCH_MAIN_CONFIG;
+use crate::overrides::Overrides;
+use crate::retroarch_config::RetroArchConfig;
+
+const ASPECT_RATIO_INDEX: &str = "aspect_ratio_index";
+const ASPECT_RATIO_AUTO: &str = "video_aspect_ratio_auto";
+const SCALE_INTEGER: &str = "video_scale_integer";
+const SMOOTH: &str = "video_smooth";
+
+/// Config keys set by [`VideoSettings::write`].
+pub const KEYS: [&str; 4] = [ASPECT_RATIO_INDEX, ASPECT_RATIO_AUTO, SCALE_INTEGER, SMOOTH];
+
+/// Aspect ratios offered by Allium, a subset of RetroArch's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectRatio {
+    Core,
+    Full,
+    FourThree,
+    SixteenNine,
+    ThreeTwo,
+    SquarePixel,
+}
+
+impl AspectRatio {
+    pub const ALL: [AspectRatio; 6] = [
+        AspectRatio::Core,
+        AspectRatio::Full,
+        AspectRatio::FourThree,
+        AspectRatio::SixteenNine,
+        AspectRatio::ThreeTwo,
+        AspectRatio::SquarePixel,
+    ];
+
+    /// RetroArch's `aspect_ratio_index`.
+    fn index(self) -> u32 {
+        match self {
+            AspectRatio::FourThree => 0,
+            AspectRatio::SixteenNine => 1,
+            AspectRatio::ThreeTwo => 7,
+            AspectRatio::SquarePixel => 21,
+            AspectRatio::Core => 22,
+            AspectRatio::Full => 24,
+        }
+    }
+
+    fn from_index(index: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.index() == index)
+    }
+
+    /// Locale key of the aspect ratio's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            AspectRatio::Core => "video-settings-aspect-ratio-core",
+            AspectRatio::Full => "video-settings-aspect-ratio-full",
+            AspectRatio::FourThree => "video-settings-aspect-ratio-4-3",
+            AspectRatio::SixteenNine => "video-settings-aspect-ratio-16-9",
+            AspectRatio::ThreeTwo => "video-settings-aspect-ratio-3-2",
+            AspectRatio::SquarePixel => "video-settings-aspect-ratio-square-pixel",
+        }
+    }
+}
+
+/// The common RetroArch video options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoSettings {
+    pub aspect_ratio: AspectRatio,
+    pub integer_scale: bool,
+    pub smooth: bool,
+}
+
+impl VideoSettings {
+    /// Loads the settings RetroArch would use with the given overrides, from most to least
+    /// specific, on top of the main config.
+    pub fn load(overrides: &[Overrides]) -> Result<Self> {
+        Self::load_in(&RETROARCH_MAIN_CONFIG, overrides)
+    }
+
+    fn load_in(main_config: &Path, overrides: &[Overrides]) -> Result<Self> {
+        let main = RetroArchConfig::load(main_config)?;
+        Ok(Self::resolve(|key| {
+            overrides
+                .iter()
+                .find_map(|o| o.get(key))
+                .or_else(|| main.get(key))
+        }))
+    }
+
+    fn resolve<'a>(get: impl Fn(&str) -> Option<&'a str>) -> Self {
+        let aspect_ratio = if get(ASPECT_RATIO_AUTO) == Some("true") {
+            AspectRatio::Core
+        } else {
+            get(ASPECT_RATIO_INDEX)
+                .and_then(|i| i.parse().ok())
+                .and_then(AspectRatio::from_index)
+                .unwrap_or(AspectRatio::Core)
+        };
+        Self {
+            aspect_ratio,
+            integer_scale: get(SCALE_INTEGER) == Some("true"),
+            smooth: get(SMOOTH) == Some("true"),
+        }
+    }
+
+    /// Writes all settings to the overrides.
+    pub fn write(&self, overrides: &mut Overrides) {
+        overrides.set(ASPECT_RATIO_INDEX, self.aspect_ratio.index().to_string());
+        overrides.set(ASPECT_RATIO_AUTO, "false");
+        overrides.set(SCALE_INTEGER, self.integer_scale.to_string());
+        overrides.set(SMOOTH, self.smooth.to_string());
+    }
+
+    /// Removes the settings from the overrides, so that less specific ones apply again.
+    pub fn clear(overrides: &mut Overrides) {
+        for key in KEYS {
+            overrides.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::overrides::OverrideScope;
+    use crate::retroarch::CoreInfo;
+    use crate::testing::temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let main = RetroArchConfig::parse(
+            r#"
+aspect_ratio_index = "22"
+video_aspect_ratio_auto = "false"
+video_scale_integer = "false"
+video_smooth = "true"
+"#,
+        );
+        let game = RetroArchConfig::parse(
+            r#"
+aspect_ratio_index = "0"
+video_scale_integer = "true"
+"#,
+        );
+
+        let settings = VideoSettings::resolve(|key| main.get(key));
+        assert_eq!(
+            settings,
+            VideoSettings {
+                aspect_ratio: AspectRatio::Core,
+                integer_scale: false,
+                smooth: true,
+            }
+        );
+
+        let settings = VideoSettings::resolve(|key| game.get(key).or_else(|| main.get(key)));
+        assert_eq!(
+            settings,
+            VideoSettings {
+                aspect_ratio: AspectRatio::FourThree,
+                integer_scale: true,
+                smooth: true,
+            }
+        );
+
+        // Automatic aspect ratio takes priority over the index
+        let auto = RetroArchConfig::parse("video_aspect_ratio_auto = \"true\"\n");
+        let settings = VideoSettings::resolve(|key| auto.get(key).or_else(|| game.get(key)));
+        assert_eq!(settings.aspect_ratio, AspectRatio::Core);
+
+        // Unknown aspect ratios fall back to the core's
+        let custom = RetroArchConfig::parse("aspect_ratio_index = \"23\"\n");
+        let settings = VideoSettings::resolve(|key| custom.get(key));
+        assert_eq!(settings.aspect_ratio, AspectRatio::Core);
+    }
+
+    #[test]
+    fn test_aspect_ratio_index() {
+        for aspect_ratio in AspectRatio::ALL {
+            assert_eq!(
+                AspectRatio::from_index(aspect_ratio.index()),
+                Some(aspect_ratio)
+            );
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = temp_dir("video-settings-write");
+        let info = CoreInfo {
+            corename: "mGBA".to_string(),
+            databases: Vec::new(),
+        };
+        let rom = Path::new("/mnt/SDCARD/Roms/GBA/Game.gba");
+        let main_config = dir.join("retroarch.cfg");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&main_config, "video_smooth = \"true\"\n").unwrap();
+
+        let load = |scope| Overrides::load_in(&dir, &info, rom, scope).unwrap();
+
+        let mut game = load(OverrideScope::Game);
+        let mut settings = VideoSettings::load_in(&main_config, &[game.clone()]).unwrap();
+        assert!(settings.smooth);
+        settings.aspect_ratio = AspectRatio::FourThree;
+        settings.smooth = false;
+        settings.write(&mut game);
+        game.save().unwrap();
+
+        let mut game = load(OverrideScope::Game);
+        assert_eq!(game.get("aspect_ratio_index"), Some("0"));
+        assert_eq!(game.get("video_aspect_ratio_auto"), Some("false"));
+        assert_eq!(
+            VideoSettings::load_in(&main_config, &[game.clone(), load(OverrideScope::Core)])
+                .unwrap(),
+            settings
+        );
+
+        VideoSettings::clear(&mut game);
+        game.save().unwrap();
+        assert!(!dir.join("mGBA/Game.cfg").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
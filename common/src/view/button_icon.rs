@@ -47,7 +47,14 @@ impl View for ButtonIcon {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
-        let (color, text) = match self.button {
+        // Show the physical button that views' A/B/X/Y are read from
+        let button = if styles.swap_buttons {
+            self.button.swap_buttons()
+        } else {
+            self.button
+        };
+
+        let (color, text) = match button {
             Key::A => (styles.button_a_color, "A"),
             Key::B => (styles.button_b_color, "B"),
             Key::X => (styles.button_x_color, "X"),
@@ -82,7 +89,7 @@ impl View for ButtonIcon {
             }
         };
 
-        match self.button {
+        match button {
             Key::A | Key::B | Key::X | Key::Y | Key::Menu => {
                 Circle::new(point, diameter)
                     .into_styled(PrimitiveStyle::with_fill(color))
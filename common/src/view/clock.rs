@@ -3,11 +3,12 @@ use std::time::Instant;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 
 use crate::constants::CLOCK_UPDATE_INTERVAL;
+use crate::datetime::DateTimeSettings;
 use crate::display::Display;
 use crate::geom::{Alignment, Point, Rect};
 use crate::platform::{DefaultPlatform, KeyEvent, Platform};
@@ -18,20 +19,28 @@ use crate::view::{Command, Label, View};
 pub struct Clock {
     label: Label<String>,
     point: Point,
+    settings: DateTimeSettings,
     #[serde(skip, default = "Instant::now")]
     last_updated: Instant,
 }
 
 impl Clock {
-    pub fn new(point: Point, alignment: Alignment) -> Self {
-        let label = Label::new(point, text(), alignment, None);
+    pub fn new(point: Point, alignment: Alignment, settings: DateTimeSettings) -> Self {
+        let label = Label::new(point, text(&settings), alignment, None);
 
         Self {
             label,
             point,
+            settings,
             last_updated: Instant::now(),
         }
     }
+
+    /// Changes the timezone and format the time is shown in.
+    pub fn set_settings(&mut self, settings: DateTimeSettings) {
+        self.label.set_text(text(&settings));
+        self.settings = settings;
+    }
 }
 
 #[async_trait(?Send)]
@@ -42,7 +51,7 @@ impl View for Clock {
         styles: &Stylesheet,
     ) -> Result<bool> {
         if self.last_updated.elapsed() >= CLOCK_UPDATE_INTERVAL {
-            self.label.set_text(text());
+            self.label.set_text(text(&self.settings));
         }
 
         display.load(self.bounding_box(styles))?;
@@ -84,6 +93,6 @@ impl View for Clock {
     }
 }
 
-fn text() -> String {
-    format!("{}", Local::now().format("%H:%M"))
+fn text(settings: &DateTimeSettings) -> String {
+    settings.format_time(&Utc::now())
 }
@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::{error, info};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::constants::SELECTION_MARGIN;
+use crate::core_options::{self, CoreOptionKind, CoreOptions, CoreOptionsScope};
+use crate::display::Display;
+use crate::game_info::GameInfo;
+use crate::geom::{Alignment, Point, Rect};
+use crate::locale::Locale;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::view::{
+    ButtonHint, ButtonIcon, Label, NullView, Row, Select, SettingsList, Toggle, View,
+};
+
+const SCOPE_ROW: usize = 0;
+
+/// Edits the core options of a game, either for the game alone or for every game run with the
+/// core. Used from both the launcher and the in-game menu.
+#[derive(Debug)]
+pub struct CoreOptionsEditor {
+    rect: Rect,
+    res: Resources,
+    core: String,
+    rom: PathBuf,
+    /// Whether the game is running, in which case changes apply once RetroArch exits.
+    is_running: bool,
+    scope: CoreOptionsScope,
+    options: CoreOptions,
+    /// Option key and kind of each row after the scope row.
+    rows: Vec<(String, CoreOptionKind)>,
+    title: Label<String>,
+    list: SettingsList,
+    note: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl CoreOptionsEditor {
+    pub fn new(
+        rect: Rect,
+        res: Resources,
+        core: String,
+        rom: PathBuf,
+        is_running: bool,
+    ) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let scope = if CoreOptions::has_game_options(&core, &rom) {
+            CoreOptionsScope::Game
+        } else {
+            CoreOptionsScope::Core
+        };
+        let options = CoreOptions::load(&core, &rom, scope)?;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("core-options-title"),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let Items { rows, left, right } = items(&locale, &options, scope);
+        let list = SettingsList::new(
+            Rect::new(
+                x + 24,
+                y + 8 + styles.ui_font.size as i32 + 8,
+                w - 48,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let note = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            if is_running {
+                locale.t("core-options-restart-note")
+            } else {
+                String::new()
+            },
+            Alignment::Left,
+            None,
+        );
+
+        let mut hints = vec![ButtonHint::new(
+            Point::zero(),
+            Key::A,
+            locale.t("button-edit"),
+            Alignment::Right,
+        )];
+        if is_running {
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::X,
+                locale.t("core-options-restart"),
+                Alignment::Right,
+            ));
+        }
+        hints.push(ButtonHint::new(
+            Point::zero(),
+            Key::B,
+            locale.t("button-back"),
+            Alignment::Right,
+        ));
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            hints,
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Ok(Self {
+            rect,
+            res,
+            core,
+            rom,
+            is_running,
+            scope,
+            options,
+            rows,
+            title,
+            list,
+            note,
+            button_hints,
+            dirty: true,
+        })
+    }
+
+    fn set_scope(&mut self, scope: CoreOptionsScope) -> Result<()> {
+        self.scope = scope;
+        self.options = CoreOptions::load(&self.core, &self.rom, scope)?;
+
+        let Items { rows, left, right } = items(&self.res.get::<Locale>(), &self.options, scope);
+        self.rows = rows;
+        self.list.select(0);
+        self.list.set_items(left, right);
+        Ok(())
+    }
+
+    fn set_option(&mut self, row: usize, value: Value) -> Result<()> {
+        let Some((key, kind)) = self.rows.get(row - 1) else {
+            return Ok(());
+        };
+        let value = match (kind, value) {
+            (CoreOptionKind::Toggle { on, off }, Value::Bool(enabled)) => {
+                if enabled {
+                    on.clone()
+                } else {
+                    off.clone()
+                }
+            }
+            (CoreOptionKind::Select(_, values), Value::Int(i)) => values[i as usize].clone(),
+            _ => return Ok(()),
+        };
+        info!("setting core option {} to {}", key, value);
+        self.options.set(key, value);
+        self.options.save(self.is_running)
+    }
+
+    /// Quits RetroArch and lets alliumd launch the game again with the new options.
+    async fn restart(&self, commands: Sender<Command>) -> Result<()> {
+        GameInfo::request_relaunch().await?;
+        commands.send(Command::Exit).await?;
+        Ok(())
+    }
+}
+
+/// The rows of the editor's list.
+struct Items {
+    /// Option key and kind of each row after the scope row.
+    rows: Vec<(String, CoreOptionKind)>,
+    left: Vec<String>,
+    right: Vec<Box<dyn View>>,
+}
+
+fn items(locale: &Locale, options: &CoreOptions, scope: CoreOptionsScope) -> Items {
+    let mut rows = Vec::new();
+    let mut left = vec![locale.t("core-options-scope")];
+    let mut right: Vec<Box<dyn View>> = vec![Box::new(Select::new(
+        Point::zero(),
+        match scope {
+            CoreOptionsScope::Game => 0,
+            CoreOptionsScope::Core => 1,
+        },
+        vec![
+            locale.t("core-options-scope-game"),
+            locale.t("core-options-scope-core"),
+        ],
+        Alignment::Right,
+    ))];
+
+    if options.is_empty() {
+        left.push(locale.t("core-options-empty"));
+        right.push(Box::new(NullView));
+    }
+
+    let prefix = core_options::common_prefix(options.iter().map(|(k, _)| k));
+    for (key, _) in options.iter() {
+        let Some(kind) = options.kind(key) else {
+            continue;
+        };
+        let view: Box<dyn View> = match &kind {
+            CoreOptionKind::Toggle { on, .. } => Box::new(Toggle::new(
+                Point::zero(),
+                options.get(key) == Some(on.as_str()),
+                Alignment::Right,
+            )),
+            CoreOptionKind::Select(i, values) => Box::new(Select::new(
+                Point::zero(),
+                *i,
+                values.clone(),
+                Alignment::Right,
+            )),
+            CoreOptionKind::ReadOnly => Box::new(Label::new(
+                Point::zero(),
+                options.get(key).unwrap_or_default().to_string(),
+                Alignment::Right,
+                None,
+            )),
+        };
+        left.push(core_options::option_name(key, &prefix));
+        right.push(view);
+        rows.push((key.to_string(), kind));
+    }
+
+    Items { rows, left, right }
+}
+
+#[async_trait(?Send)]
+impl View for CoreOptionsEditor {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.note.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.note.should_draw() && self.note.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.note.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    let result = if i == SCOPE_ROW {
+                        self.set_scope(match val.as_int() {
+                            Some(0) => CoreOptionsScope::Game,
+                            _ => CoreOptionsScope::Core,
+                        })
+                    } else {
+                        self.set_option(i, val)
+                    };
+                    if let Err(e) = result {
+                        error!("failed to save core options: {}", e);
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::X) if self.is_running => {
+                self.restart(commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.note, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.note,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::{
+    prelude::{OriginDimensions, Size},
+    primitives::{Primitive, PrimitiveStyleBuilder, Rectangle, RoundedRectangle},
+    text::Text,
+    Drawable,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::display::font::FontTextStyleBuilder;
+use crate::geom::{Alignment, Point, Rect};
+use crate::hotkeys;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::stylesheet::Stylesheet;
+use crate::view::View;
+
+/// Records a chord: every key pressed until all of them are released. Releasing B on its own
+/// cancels, since B alone can't be a hotkey without breaking every menu. The chord is shown and
+/// sent as physical buttons, as hotkeys are matched before A/B and X/Y are swapped.
+#[derive(Debug, Clone)]
+pub struct ChordInput {
+    title: String,
+    held: Vec<Key>,
+    chord: Vec<Key>,
+    /// Whether A/B and X/Y are swapped, to undo it for the recorded chord.
+    swap_buttons: bool,
+    dirty: bool,
+}
+
+impl ChordInput {
+    pub fn new(title: String, swap_buttons: bool) -> Self {
+        Self {
+            title,
+            held: Vec::new(),
+            chord: Vec::new(),
+            swap_buttons,
+            dirty: true,
+        }
+    }
+
+    /// The chord as physical buttons.
+    fn physical_chord(&self) -> Vec<Key> {
+        if self.swap_buttons {
+            self.chord.iter().map(|k| k.swap_buttons()).collect()
+        } else {
+            self.chord.clone()
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for ChordInput {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+
+        let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallback(styles.cjk_font.font())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .background_color(styles.background_color)
+            .build();
+
+        let fill_style = PrimitiveStyleBuilder::new()
+            .fill_color(styles.background_color)
+            .stroke_color(styles.highlight_color)
+            .stroke_width(2)
+            .build();
+
+        let Size { width, height } = display.size();
+        let h = styles.ui_font.size * 3 + 8 * 4;
+        let y0 = (height - h) as i32 / 2;
+
+        RoundedRectangle::with_equal_corners(
+            Rectangle::new(Point::new(24, y0).into(), Size::new(width - 48, h)),
+            Size::new_equal(12),
+        )
+        .into_styled(fill_style)
+        .draw(display)?;
+
+        Text::with_alignment(
+            &self.title,
+            Point::new(width as i32 / 2, y0 + 8).into(),
+            text_style.clone(),
+            Alignment::Center.into(),
+        )
+        .draw(display)?;
+
+        Text::with_alignment(
+            &hotkeys::chord_name(&self.physical_chord()),
+            Point::new(
+                width as i32 / 2,
+                y0 + 8 * 2 + styles.ui_font.size as i32 * 3 / 2,
+            )
+            .into(),
+            text_style,
+            Alignment::Center.into(),
+        )
+        .draw(display)?;
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(key) => {
+                if !self.held.contains(&key) {
+                    self.held.push(key);
+                }
+                if !self.chord.contains(&key) {
+                    self.chord.push(key);
+                    self.dirty = true;
+                }
+            }
+            KeyEvent::Released(key) => {
+                self.held.retain(|k| *k != key);
+                if self.held.is_empty() && !self.chord.is_empty() {
+                    if self.chord != [Key::B] {
+                        bubble.push_back(Command::ValueChanged(
+                            0,
+                            Value::Keys(self.physical_chord()),
+                        ));
+                    }
+                    self.chord.clear();
+                    bubble.push_back(Command::CloseView);
+                    commands.send(Command::Redraw).await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        Rect::new(0, 0, 640, 480)
+    }
+
+    fn set_position(&mut self, _point: crate::geom::Point) {}
+}
@@ -0,0 +1,444 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use embedded_graphics::prelude::Dimensions;
+use embedded_graphics::text::Text;
+use embedded_graphics::Drawable;
+use log::trace;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::Value;
+use crate::display::color::Color;
+use crate::display::font::{FontTextStyle, FontTextStyleBuilder};
+use crate::geom::{Alignment, Point, Rect};
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::view::{Command, View};
+
+/// Whether a `DateTimePicker` edits the date or the time of its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateTimePickerMode {
+    Date,
+    /// Time of day, shown with an AM/PM field if `use_24_hour` is false.
+    Time {
+        use_24_hour: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Meridiem,
+}
+
+/// Edits a date or a time field by field. Left and right select the field, up and down change
+/// it. Only the fields of the chosen mode are changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateTimePicker {
+    point: Point,
+    value: NaiveDateTime,
+    mode: DateTimePickerMode,
+    alignment: Alignment,
+    dirty: bool,
+    #[serde(skip)]
+    edit_state: Option<EditState>,
+    background_color: StylesheetColor,
+}
+
+#[derive(Debug, Clone)]
+struct EditState {
+    selected: usize,
+    value: NaiveDateTime,
+}
+
+impl DateTimePicker {
+    pub fn new(
+        point: Point,
+        value: NaiveDateTime,
+        mode: DateTimePickerMode,
+        alignment: Alignment,
+    ) -> Self {
+        Self {
+            point,
+            value,
+            mode,
+            alignment,
+            dirty: true,
+            edit_state: None,
+            background_color: StylesheetColor::Background,
+        }
+    }
+
+    pub fn value(&self) -> NaiveDateTime {
+        self.value
+    }
+
+    /// Updates the value, unless it is being edited.
+    pub fn set_value(&mut self, value: NaiveDateTime) {
+        if self.edit_state.is_none() && self.value != value {
+            self.value = value;
+            self.dirty = true;
+        }
+    }
+
+    fn fields(&self) -> &'static [Field] {
+        match self.mode {
+            DateTimePickerMode::Date => &[Field::Year, Field::Month, Field::Day],
+            DateTimePickerMode::Time { use_24_hour: true } => &[Field::Hour, Field::Minute],
+            DateTimePickerMode::Time { use_24_hour: false } => {
+                &[Field::Hour, Field::Minute, Field::Meridiem]
+            }
+        }
+    }
+
+    /// Splits the value into the text of each field, with the separator that precedes it.
+    fn segments(&self, value: NaiveDateTime) -> Vec<(&'static str, String)> {
+        self.fields()
+            .iter()
+            .map(|field| match field {
+                Field::Year => ("", format!("{:04}", value.year())),
+                Field::Month => ("-", format!("{:02}", value.month())),
+                Field::Day => ("-", format!("{:02}", value.day())),
+                Field::Hour => match self.mode {
+                    DateTimePickerMode::Time { use_24_hour: false } => {
+                        ("", format!("{}", value.hour12().1))
+                    }
+                    _ => ("", format!("{:02}", value.hour())),
+                },
+                Field::Minute => (":", format!("{:02}", value.minute())),
+                Field::Meridiem => (" ", value.format("%p").to_string()),
+            })
+            .collect()
+    }
+}
+
+/// Changes one field of the value by `delta`, wrapping around within the enclosing unit.
+fn adjust(value: NaiveDateTime, field: Field, delta: i32) -> NaiveDateTime {
+    let date = value.date();
+    let time = value.time();
+    match field {
+        Field::Year => {
+            let year = (date.year() + delta).clamp(2000, 2099);
+            with_date(value, year, date.month(), date.day())
+        }
+        Field::Month => {
+            let month = (date.month0() as i32 + delta).rem_euclid(12) as u32 + 1;
+            with_date(value, date.year(), month, date.day())
+        }
+        Field::Day => {
+            let days = days_in_month(date.year(), date.month());
+            let day = (date.day0() as i32 + delta).rem_euclid(days as i32) as u32 + 1;
+            with_date(value, date.year(), date.month(), day)
+        }
+        Field::Hour => {
+            let hour = (time.hour() as i32 + delta).rem_euclid(24) as u32;
+            value.with_hour(hour).unwrap_or(value)
+        }
+        Field::Minute => {
+            let minute = (time.minute() as i32 + delta).rem_euclid(60) as u32;
+            value.with_minute(minute).unwrap_or(value)
+        }
+        Field::Meridiem => {
+            let hour = (time.hour() + 12) % 24;
+            value.with_hour(hour).unwrap_or(value)
+        }
+    }
+}
+
+/// Replaces the date, clamping the day to the length of the month.
+fn with_date(value: NaiveDateTime, year: i32, month: u32, day: u32) -> NaiveDateTime {
+    let day = day.min(days_in_month(year, month));
+    NaiveDate::from_ymd_opt(year, month, day)
+        .map(|date| date.and_time(value.time()))
+        .unwrap_or(value)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    next.map_or(31, |next| (next - Duration::days(1)).day())
+}
+
+#[async_trait(?Send)]
+impl View for DateTimePicker {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+
+        let value = self
+            .edit_state
+            .as_ref()
+            .map(|s| s.value)
+            .unwrap_or(self.value);
+        let edit_index = self.edit_state.as_ref().map(|s| s.selected);
+
+        let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallback(styles.cjk_font.font())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .background_color(self.background_color.to_color(styles))
+            .build();
+
+        let focused_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .background_color(styles.highlight_color)
+            .draw_background()
+            .build();
+
+        let selected_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .background_color(styles.highlight_color)
+            .underline()
+            .draw_background()
+            .build();
+
+        let unselected_style = if self.edit_state.is_some() {
+            focused_style
+        } else {
+            text_style
+        };
+
+        match self.alignment {
+            Alignment::Right => {
+                let mut x = self.point.x;
+                for (i, (separator, text)) in self.segments(value).iter().enumerate().rev() {
+                    let field = Text::with_alignment(
+                        text,
+                        Point::new(x, self.point.y).into(),
+                        if edit_index == Some(i) {
+                            selected_style.clone()
+                        } else {
+                            unselected_style.clone()
+                        },
+                        Alignment::Right.into(),
+                    );
+                    field.draw(display)?;
+                    x = field.bounding_box().top_left.x;
+
+                    if !separator.is_empty() {
+                        let separator = Text::with_alignment(
+                            separator,
+                            Point::new(x, self.point.y).into(),
+                            unselected_style.clone(),
+                            Alignment::Right.into(),
+                        );
+                        separator.draw(display)?;
+                        x = separator.bounding_box().top_left.x;
+                    }
+                }
+            }
+            Alignment::Left | Alignment::Center => {
+                // Drawn left to right from the left edge of the whole text
+                let mut x = self.bounding_box(styles).x;
+                for (i, (separator, text)) in self.segments(value).iter().enumerate() {
+                    if !separator.is_empty() {
+                        let separator = Text::with_alignment(
+                            separator,
+                            Point::new(x, self.point.y).into(),
+                            unselected_style.clone(),
+                            Alignment::Left.into(),
+                        );
+                        separator.draw(display)?;
+                        let rect = separator.bounding_box();
+                        x = rect.top_left.x + rect.size.width as i32;
+                    }
+
+                    let field = Text::with_alignment(
+                        text,
+                        Point::new(x, self.point.y).into(),
+                        if edit_index == Some(i) {
+                            selected_style.clone()
+                        } else {
+                            unselected_style.clone()
+                        },
+                        Alignment::Left.into(),
+                    );
+                    field.draw(display)?;
+                    let rect = field.bounding_box();
+                    x = rect.top_left.x + rect.size.width as i32;
+                }
+            }
+        }
+
+        self.dirty = false;
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        _command: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        trace!(
+            "date time picker key event: {:?}, state: {:?}",
+            event,
+            self.edit_state
+        );
+        let fields = self.fields();
+        if let Some(state) = &mut self.edit_state {
+            match event {
+                KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
+                    state.value = adjust(state.value, fields[state.selected], 1);
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
+                    state.value = adjust(state.value, fields[state.selected], -1);
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
+                    state.selected = state.selected.saturating_sub(1);
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
+                    state.selected = (state.selected + 1).min(fields.len() - 1);
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::A) => {
+                    self.value = state.value;
+                    self.dirty = true;
+                    self.edit_state = None;
+                    bubble.push_back(Command::ValueChanged(0, Value::DateTime(self.value)));
+                    bubble.push_back(Command::Unfocus);
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::B) => {
+                    self.edit_state = None;
+                    self.dirty = true;
+                    bubble.push_back(Command::Unfocus);
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        } else {
+            self.edit_state = Some(EditState {
+                value: self.value,
+                selected: 0,
+            });
+            self.dirty = true;
+            bubble.push_back(Command::TrapFocus);
+            Ok(true)
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        let text_style: FontTextStyle<Color> = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallback(styles.cjk_font.font())
+            .font_size(styles.ui_font.size)
+            .draw_background()
+            .build();
+
+        let value = self
+            .edit_state
+            .as_ref()
+            .map(|s| s.value)
+            .unwrap_or(self.value);
+        let text: String = self
+            .segments(value)
+            .into_iter()
+            .map(|(separator, text)| format!("{separator}{text}"))
+            .collect();
+
+        let rect: Rect =
+            Text::with_alignment(&text, self.point.into(), text_style, self.alignment.into())
+                .bounding_box()
+                .into();
+
+        Rect::new(rect.x, self.point.y, rect.w, rect.h + 1)
+    }
+
+    fn set_position(&mut self, point: Point) {
+        self.point = point;
+        self.dirty = true;
+    }
+
+    fn set_background_color(&mut self, color: StylesheetColor) {
+        self.background_color = color;
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_adjust() {
+        let value = date_time(2024, 1, 31, 23, 59);
+        assert_eq!(
+            adjust(value, Field::Year, 1),
+            date_time(2025, 1, 31, 23, 59)
+        );
+        assert_eq!(
+            adjust(value, Field::Month, 1),
+            date_time(2024, 2, 29, 23, 59)
+        );
+        assert_eq!(
+            adjust(value, Field::Month, -1),
+            date_time(2024, 12, 31, 23, 59)
+        );
+        assert_eq!(adjust(value, Field::Day, 1), date_time(2024, 1, 1, 23, 59));
+        assert_eq!(adjust(value, Field::Hour, 1), date_time(2024, 1, 31, 0, 59));
+        assert_eq!(
+            adjust(value, Field::Minute, 1),
+            date_time(2024, 1, 31, 23, 0)
+        );
+        assert_eq!(
+            adjust(value, Field::Meridiem, 1),
+            date_time(2024, 1, 31, 11, 59)
+        );
+
+        let value = date_time(2023, 2, 1, 0, 0);
+        assert_eq!(adjust(value, Field::Day, -1), date_time(2023, 2, 28, 0, 0));
+        assert_eq!(
+            adjust(value, Field::Minute, -1),
+            date_time(2023, 2, 1, 0, 59)
+        );
+        assert_eq!(adjust(value, Field::Year, -30), date_time(2000, 2, 1, 0, 0));
+    }
+}
@@ -1,8 +1,11 @@
 pub mod button;
+pub mod chord_input;
 pub mod color_picker;
+pub mod date_time_picker;
 pub mod keyboard;
 pub mod number;
 pub mod percentage;
+pub mod pin_input;
 pub mod select;
 pub mod text_box;
 pub mod toggle;
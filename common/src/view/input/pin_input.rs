@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::{
+    prelude::{OriginDimensions, Size},
+    primitives::{Primitive, PrimitiveStyleBuilder, Rectangle, RoundedRectangle},
+    text::Text,
+    Drawable,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::display::font::FontTextStyleBuilder;
+use crate::geom::{self, Alignment, Point, Rect};
+use crate::kid_mode::PIN_KEYS;
+use crate::locale::Locale;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::Stylesheet;
+use crate::view::{ButtonHint, ButtonIcon, Row, View};
+
+/// Maximum number of buttons in a PIN.
+const MAX_PIN_LENGTH: usize = 8;
+
+/// Records a sequence of button presses, shown masked, to be used as a PIN. The physical buttons
+/// are recorded, so that swapping A/B and X/Y doesn't change which buttons enter a PIN.
+#[derive(Debug, Clone)]
+pub struct PinInput {
+    title: String,
+    value: Vec<Key>,
+    /// Whether A/B and X/Y are swapped, to undo it when recording.
+    swap_buttons: bool,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl PinInput {
+    pub fn new(res: Resources, title: String) -> Self {
+        let geom::Size { w, h } = res.get::<geom::Size>().to_owned();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let button_hints = Row::new(
+            Point::new(
+                w as i32 - 12,
+                h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Start,
+                    locale.t("button-confirm"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    Point::zero(),
+                    Key::Select,
+                    locale.t("button-cancel"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        Self {
+            title,
+            value: Vec::new(),
+            swap_buttons: styles.swap_buttons,
+            button_hints,
+            dirty: true,
+        }
+    }
+
+    pub fn value(&self) -> &[Key] {
+        &self.value
+    }
+}
+
+#[async_trait(?Send)]
+impl View for PinInput {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+        if self.dirty {
+            let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+                .font_fallback(styles.cjk_font.font())
+                .font_size(styles.ui_font.size)
+                .text_color(styles.foreground_color)
+                .background_color(styles.background_color)
+                .build();
+
+            let fill_style = PrimitiveStyleBuilder::new()
+                .fill_color(styles.background_color)
+                .stroke_color(styles.highlight_color)
+                .stroke_width(2)
+                .build();
+
+            let Size { width, height } = display.size();
+            let h = styles.ui_font.size * 3 + 8 * 4;
+            let y0 = (height - h) as i32 / 2;
+
+            RoundedRectangle::with_equal_corners(
+                Rectangle::new(Point::new(24, y0).into(), Size::new(width - 48, h)),
+                Size::new_equal(12),
+            )
+            .into_styled(fill_style)
+            .draw(display)?;
+
+            Text::with_alignment(
+                &self.title,
+                Point::new(width as i32 / 2, y0 + 8).into(),
+                text_style.clone(),
+                Alignment::Center.into(),
+            )
+            .draw(display)?;
+
+            let mut masked: String = "●".repeat(self.value.len());
+            masked.push_str(&"○".repeat(MAX_PIN_LENGTH - self.value.len()));
+            Text::with_alignment(
+                &masked,
+                Point::new(
+                    width as i32 / 2,
+                    y0 + 8 * 2 + styles.ui_font.size as i32 * 3 / 2,
+                )
+                .into(),
+                text_style,
+                Alignment::Center.into(),
+            )
+            .draw(display)?;
+
+            self.dirty = false;
+            drawn = true;
+        }
+
+        if self.button_hints.should_draw() {
+            drawn |= self.button_hints.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(key) if PIN_KEYS.contains(&key) => {
+                if self.value.len() < MAX_PIN_LENGTH {
+                    self.value.push(if self.swap_buttons {
+                        key.swap_buttons()
+                    } else {
+                        key
+                    });
+                    self.dirty = true;
+                }
+            }
+            KeyEvent::Pressed(Key::Select) => {
+                bubble.push_back(Command::CloseView);
+                commands.send(Command::Redraw).await?;
+            }
+            KeyEvent::Pressed(Key::Start) => {
+                bubble.push_back(Command::ValueChanged(0, Value::Keys(self.value.clone())));
+                bubble.push_back(Command::CloseView);
+                commands.send(Command::Redraw).await?;
+            }
+            KeyEvent::Pressed(_) | KeyEvent::Autorepeat(_) => {}
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        Rect::new(0, 0, 640, 480)
+    }
+
+    fn set_position(&mut self, _point: crate::geom::Point) {}
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle, RoundedRectangle};
+use embedded_graphics::text::{Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+
+use crate::display::font::FontTextStyleBuilder;
+use crate::geom::{Alignment, Rect};
+use crate::platform::{DefaultPlatform, Platform};
+use crate::stylesheet::Stylesheet;
+
+/// Returns the letter an entry is indexed under: its first alphabetic character, uppercased,
+/// or `#` for entries starting with anything else.
+pub fn initial(text: &str) -> char {
+    match text.trim_start().chars().next() {
+        Some(c) if c.is_alphabetic() => c.to_uppercase().next().unwrap_or(c),
+        _ => '#',
+    }
+}
+
+/// Returns the index of the first entry starting with the smallest letter after the selected
+/// entry's letter.
+pub fn next(items: &[String], selected: usize) -> Option<usize> {
+    let current = initial(items.get(selected)?);
+    let letter = items
+        .iter()
+        .map(|s| initial(s))
+        .filter(|&c| c > current)
+        .min()?;
+    items.iter().position(|s| initial(s) == letter)
+}
+
+/// Returns the index of the first entry starting with the largest letter before the selected
+/// entry's letter.
+pub fn prev(items: &[String], selected: usize) -> Option<usize> {
+    let current = initial(items.get(selected)?);
+    let letter = items
+        .iter()
+        .map(|s| initial(s))
+        .filter(|&c| c < current)
+        .max()?;
+    items.iter().position(|s| initial(s) == letter)
+}
+
+/// Draws a large letter centered in `rect`, shown while jumping between letters.
+pub fn draw_overlay(
+    display: &mut <DefaultPlatform as Platform>::Display,
+    styles: &Stylesheet,
+    rect: Rect,
+    letter: char,
+) -> Result<()> {
+    let font_size = styles.ui_font.size * 2;
+    let size = font_size + 24;
+    let x = rect.x + (rect.w as i32 - size as i32) / 2;
+    let y = rect.y + (rect.h as i32 - size as i32) / 2;
+
+    RoundedRectangle::with_equal_corners(
+        Rectangle::new(
+            embedded_graphics::prelude::Point::new(x, y),
+            Size::new_equal(size),
+        ),
+        Size::new_equal(24),
+    )
+    .into_styled(PrimitiveStyle::with_fill(styles.highlight_color))
+    .draw(display)?;
+
+    let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+        .font_fallback(styles.cjk_font.font())
+        .font_size(font_size)
+        .text_color(styles.foreground_color)
+        .background_color(styles.highlight_color)
+        .build();
+    Text::with_text_style(
+        &letter.to_string(),
+        embedded_graphics::prelude::Point::new(x + size as i32 / 2, y + 12),
+        text_style,
+        TextStyleBuilder::new()
+            .alignment(Alignment::Center.into())
+            .build(),
+    )
+    .draw(display)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_initial() {
+        assert_eq!(initial("Zelda"), 'Z');
+        assert_eq!(initial("  mario"), 'M');
+        assert_eq!(initial("1942"), '#');
+        assert_eq!(initial("[BIOS]"), '#');
+        assert_eq!(initial(""), '#');
+    }
+
+    #[test]
+    fn test_next() {
+        let items = items(&["1942", "Asteroids", "Arkanoid", "Bomberman", "Contra"]);
+        assert_eq!(next(&items, 0), Some(1));
+        assert_eq!(next(&items, 2), Some(3));
+        assert_eq!(next(&items, 3), Some(4));
+        assert_eq!(next(&items, 4), None);
+        assert_eq!(next(&items, 5), None);
+    }
+
+    #[test]
+    fn test_prev() {
+        let items = items(&["1942", "Asteroids", "Arkanoid", "Bomberman", "Contra"]);
+        assert_eq!(prev(&items, 4), Some(3));
+        assert_eq!(prev(&items, 2), Some(0));
+        assert_eq!(prev(&items, 0), None);
+    }
+
+    #[test]
+    fn test_unsorted() {
+        let items = items(&["Contra", "Asteroids", "Bomberman", "Castlevania"]);
+        assert_eq!(next(&items, 1), Some(2));
+        assert_eq!(next(&items, 2), Some(0));
+        assert_eq!(prev(&items, 0), Some(2));
+    }
+}
@@ -2,14 +2,18 @@ mod battery_indicator;
 mod button_hint;
 mod button_icon;
 mod clock;
+mod core_options;
 mod image;
 mod input;
 mod label;
+mod letter_jump;
 mod list;
 mod null;
 mod row;
 mod scroll_list;
 mod settings_list;
+mod toast;
+mod video_settings;
 
 use std::collections::VecDeque;
 use std::fmt;
@@ -18,12 +22,16 @@ pub use self::battery_indicator::BatteryIndicator;
 pub use self::button_hint::ButtonHint;
 pub use self::button_icon::ButtonIcon;
 pub use self::clock::Clock;
+pub use self::core_options::CoreOptionsEditor;
 pub use self::image::{Image, ImageMode};
 pub use self::input::button::Button;
+pub use self::input::chord_input::ChordInput;
 pub use self::input::color_picker::ColorPicker;
+pub use self::input::date_time_picker::{DateTimePicker, DateTimePickerMode};
 pub use self::input::keyboard::Keyboard;
 pub use self::input::number::Number;
 pub use self::input::percentage::Percentage;
+pub use self::input::pin_input::PinInput;
 pub use self::input::select::Select;
 pub use self::input::text_box::TextBox;
 pub use self::input::toggle::Toggle;
@@ -33,6 +41,8 @@ pub use self::null::NullView;
 pub use self::row::Row;
 pub use self::scroll_list::ScrollList;
 pub use self::settings_list::SettingsList;
+pub use self::toast::Toast;
+pub use self::video_settings::VideoSettingsEditor;
 
 use anyhow::Result;
 use async_trait::async_trait;
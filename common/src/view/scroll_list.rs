@@ -10,10 +10,12 @@ use embedded_graphics::Drawable;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 
+use crate::constants::LISTING_JUMP_SIZE;
 use crate::display::Display;
 use crate::geom::{Alignment, Point, Rect};
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::view::letter_jump;
 use crate::view::{Command, Label, View};
 
 /// A listing of selectable entries. Assumes that all entries have the same size.
@@ -29,6 +31,10 @@ pub struct ScrollList {
     top: usize,
     selected: usize,
     background_color: Option<StylesheetColor>,
+    /// Whether L2/R2 jump to the previous/next starting letter.
+    letter_jump: bool,
+    /// Letter shown in the overlay while jumping.
+    jump_letter: Option<char>,
     dirty: bool,
 }
 
@@ -57,6 +63,8 @@ impl ScrollList {
             top: 0,
             selected: 0,
             background_color: None,
+            letter_jump: false,
+            jump_letter: None,
             dirty: true,
         };
 
@@ -130,6 +138,20 @@ impl ScrollList {
         self.selected
     }
 
+    /// Enables jumping to the previous/next starting letter with L2/R2.
+    pub fn set_letter_jump(&mut self, enabled: bool) -> &mut Self {
+        self.letter_jump = enabled;
+        self
+    }
+
+    fn jump_to(&mut self, index: Option<usize>) {
+        if let Some(index) = index {
+            self.select(index);
+        }
+        self.jump_letter = Some(letter_jump::initial(&self.items[self.selected]));
+        self.dirty = true;
+    }
+
     pub fn visible_count(&self) -> usize {
         (self.rect.h as usize / self.entry_height as usize).min(self.items.len())
     }
@@ -189,6 +211,11 @@ impl View for ScrollList {
                 child.draw(display, styles)?;
             }
 
+            if let Some(letter) = self.jump_letter {
+                let rect = self.bounding_box(styles);
+                letter_jump::draw_overlay(display, styles, rect, letter)?;
+            }
+
             self.dirty = false;
 
             return Ok(true);
@@ -237,14 +264,30 @@ impl View for ScrollList {
                 }
                 KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
                     self.select(
-                        (self.selected as isize - 5).clamp(0, self.items.len() as isize - 1)
+                        (self.selected as isize - LISTING_JUMP_SIZE as isize)
+                            .clamp(0, self.items.len() as isize - 1)
                             as usize,
                     );
                     self.dirty = true;
                     Ok(true)
                 }
                 KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
-                    self.select((self.selected + 5).clamp(0, self.items.len() - 1));
+                    self.select(
+                        (self.selected + LISTING_JUMP_SIZE as usize).clamp(0, self.items.len() - 1),
+                    );
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::L2) | KeyEvent::Autorepeat(Key::L2) if self.letter_jump => {
+                    self.jump_to(letter_jump::prev(&self.items, self.selected));
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::R2) | KeyEvent::Autorepeat(Key::R2) if self.letter_jump => {
+                    self.jump_to(letter_jump::next(&self.items, self.selected));
+                    Ok(true)
+                }
+                KeyEvent::Released(Key::L2 | Key::R2) if self.jump_letter.is_some() => {
+                    self.jump_letter = None;
                     self.dirty = true;
                     Ok(true)
                 }
@@ -7,10 +7,12 @@ use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle, Rounde
 use embedded_graphics::Drawable;
 use tokio::sync::mpsc::Sender;
 
+use crate::constants::LISTING_JUMP_SIZE;
 use crate::display::Display;
 use crate::geom::{Alignment, Point, Rect};
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::view::letter_jump;
 use crate::view::{Command, Label, View};
 
 /// A listing of selectable entries. Assumes that all entries have the same size.
@@ -24,6 +26,10 @@ pub struct SettingsList {
     top: usize,
     selected: usize,
     focused: bool,
+    /// Whether L2/R2 jump to the previous/next starting letter.
+    letter_jump: bool,
+    /// Letter shown in the overlay while jumping.
+    jump_letter: Option<char>,
     dirty: bool,
     has_layout: bool,
 }
@@ -44,6 +50,8 @@ impl SettingsList {
             top: 0,
             selected: 0,
             focused: false,
+            letter_jump: false,
+            jump_letter: None,
             dirty: true,
             has_layout: false,
         };
@@ -121,6 +129,20 @@ impl SettingsList {
         self.selected
     }
 
+    /// Enables jumping to the previous/next starting letter with L2/R2.
+    pub fn set_letter_jump(&mut self, enabled: bool) -> &mut Self {
+        self.letter_jump = enabled;
+        self
+    }
+
+    fn jump_to(&mut self, index: Option<usize>) {
+        if let Some(index) = index {
+            self.select(index);
+        }
+        self.jump_letter = Some(letter_jump::initial(&self.labels[self.selected]));
+        self.dirty = true;
+    }
+
     pub fn visible_count(&self) -> usize {
         ((self.rect.h as usize - 16) / self.entry_height as usize)
             .min(self.labels.len())
@@ -141,7 +163,8 @@ impl View for SettingsList {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
-        if self.dirty {
+        let dirty = self.dirty;
+        if dirty {
             if !self.has_layout {
                 for i in 0..self.visible_count() {
                     let child = &mut self.right[self.top + i];
@@ -221,6 +244,13 @@ impl View for SettingsList {
             drawn = true
         }
 
+        if dirty {
+            if let Some(letter) = self.jump_letter {
+                letter_jump::draw_overlay(display, styles, self.rect, letter)?;
+                drawn = true;
+            }
+        }
+
         Ok(drawn)
     }
 
@@ -277,14 +307,30 @@ impl View for SettingsList {
                 }
                 KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
                     self.select(
-                        (self.selected as isize - 5).clamp(0, self.right.len() as isize - 1)
+                        (self.selected as isize - LISTING_JUMP_SIZE as isize)
+                            .clamp(0, self.right.len() as isize - 1)
                             as usize,
                     );
                     self.dirty = true;
                     Ok(true)
                 }
                 KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
-                    self.select((self.selected + 5).clamp(0, self.right.len() - 1));
+                    self.select(
+                        (self.selected + LISTING_JUMP_SIZE as usize).clamp(0, self.right.len() - 1),
+                    );
+                    self.dirty = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::L2) | KeyEvent::Autorepeat(Key::L2) if self.letter_jump => {
+                    self.jump_to(letter_jump::prev(&self.labels, self.selected));
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::R2) | KeyEvent::Autorepeat(Key::R2) if self.letter_jump => {
+                    self.jump_to(letter_jump::next(&self.labels, self.selected));
+                    Ok(true)
+                }
+                KeyEvent::Released(Key::L2 | Key::R2) if self.jump_letter.is_some() => {
+                    self.jump_letter = None;
                     self.dirty = true;
                     Ok(true)
                 }
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::prelude::{Dimensions, Size};
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, RoundedRectangle};
+use embedded_graphics::text::{Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use tokio::sync::mpsc::Sender;
+
+use crate::command::Command;
+use crate::display::color::Color;
+use crate::display::font::FontTextStyleBuilder;
+use crate::geom::{Alignment, Point, Rect};
+use crate::platform::{DefaultPlatform, KeyEvent, Platform};
+use crate::stylesheet::Stylesheet;
+use crate::view::View;
+
+const PADDING: u32 = 12;
+
+/// A short message shown near the bottom of the screen, e.g. to explain why an action was refused.
+/// It doesn't take input; its owner decides how long it stays up.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    /// Bottom center of the toast.
+    point: Point,
+    text: String,
+    dirty: bool,
+}
+
+impl Toast {
+    pub fn new(point: Point, text: String) -> Self {
+        Self {
+            point,
+            text,
+            dirty: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Toast {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let rect = self.bounding_box(styles);
+
+        RoundedRectangle::with_equal_corners(rect.into(), Size::new_equal(PADDING))
+            .into_styled(PrimitiveStyle::with_fill(styles.highlight_color))
+            .draw(display)?;
+
+        let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallback(styles.cjk_font.font())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .background_color(styles.highlight_color)
+            .build();
+        Text::with_text_style(
+            &self.text,
+            embedded_graphics::prelude::Point::new(self.point.x, rect.y + PADDING as i32),
+            text_style,
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center.into())
+                .build(),
+        )
+        .draw(display)?;
+
+        self.dirty = false;
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        let text_style = FontTextStyleBuilder::<Color>::new(styles.ui_font.font())
+            .font_fallback(styles.cjk_font.font())
+            .font_size(styles.ui_font.size)
+            .build();
+        let width = Text::with_alignment(
+            &self.text,
+            embedded_graphics::prelude::Point::zero(),
+            text_style,
+            Alignment::Center.into(),
+        )
+        .bounding_box()
+        .size
+        .width;
+
+        let w = width + PADDING * 2;
+        let h = styles.ui_font.size + PADDING * 2;
+        Rect::new(self.point.x - w as i32 / 2, self.point.y - h as i32, w, h)
+    }
+
+    fn set_position(&mut self, point: Point) {
+        self.point = point;
+        self.dirty = true;
+    }
+}
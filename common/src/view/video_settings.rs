@@ -0,0 +1,361 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::constants::SELECTION_MARGIN;
+use crate::display::Display;
+use crate::game_info::GameInfo;
+use crate::geom::{Alignment, Point, Rect};
+use crate::locale::Locale;
+use crate::overrides::{OverrideScope, Overrides};
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::video_settings::{self, AspectRatio, VideoSettings};
+use crate::view::{ButtonHint, ButtonIcon, Label, Row, Select, SettingsList, Toggle, View};
+
+const SCOPE_ROW: usize = 0;
+const ASPECT_RATIO_ROW: usize = 1;
+const INTEGER_SCALE_ROW: usize = 2;
+const SMOOTH_ROW: usize = 3;
+
+/// Edits the common RetroArch video options of a game, saved as overrides for the game, its
+/// console or every game run with the core. Used from both the launcher and the in-game menu.
+#[derive(Debug)]
+pub struct VideoSettingsEditor {
+    rect: Rect,
+    res: Resources,
+    /// Whether the game is running, in which case changes apply after a restart.
+    is_running: bool,
+    /// Overrides of each scope, from most to least specific.
+    overrides: Vec<Overrides>,
+    /// Index of the scope that changes are saved to.
+    scope: usize,
+    settings: VideoSettings,
+    title: Label<String>,
+    list: SettingsList,
+    note: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl VideoSettingsEditor {
+    pub fn new(
+        rect: Rect,
+        res: Resources,
+        core: &str,
+        rom: &Path,
+        is_running: bool,
+    ) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let overrides = OverrideScope::ALL
+            .iter()
+            .map(|scope| Overrides::load(core, rom, *scope))
+            .collect::<Result<Vec<_>>>()?;
+        // Edit the most specific overrides that already set a video option
+        let scope = overrides
+            .iter()
+            .position(|o| video_settings::KEYS.iter().any(|k| o.get(k).is_some()))
+            .unwrap_or_default();
+        let settings = VideoSettings::load(&overrides[scope..])?;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("video-settings-title"),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 24,
+                y + 8 + styles.ui_font.size as i32 + 8,
+                w - 48,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            vec![
+                locale.t("video-settings-scope"),
+                locale.t("video-settings-aspect-ratio"),
+                locale.t("video-settings-integer-scale"),
+                locale.t("video-settings-smooth"),
+            ],
+            vec![
+                Box::new(Select::new(
+                    Point::zero(),
+                    scope,
+                    vec![
+                        locale.t("video-settings-scope-game"),
+                        locale.t("video-settings-scope-console"),
+                        locale.t("video-settings-scope-core"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(aspect_ratio_select(&locale, settings.aspect_ratio)),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.integer_scale,
+                    Alignment::Right,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.smooth,
+                    Alignment::Right,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let note = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            if is_running {
+                locale.t("video-settings-restart-note")
+            } else {
+                String::new()
+            },
+            Alignment::Left,
+            None,
+        );
+
+        let mut hints = vec![
+            ButtonHint::new(
+                Point::zero(),
+                Key::A,
+                locale.t("button-edit"),
+                Alignment::Right,
+            ),
+            ButtonHint::new(
+                Point::zero(),
+                Key::Y,
+                locale.t("video-settings-reset"),
+                Alignment::Right,
+            ),
+        ];
+        if is_running {
+            hints.push(ButtonHint::new(
+                Point::zero(),
+                Key::X,
+                locale.t("video-settings-restart"),
+                Alignment::Right,
+            ));
+        }
+        hints.push(ButtonHint::new(
+            Point::zero(),
+            Key::B,
+            locale.t("button-back"),
+            Alignment::Right,
+        ));
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            hints,
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Ok(Self {
+            rect,
+            res,
+            is_running,
+            overrides,
+            scope,
+            settings,
+            title,
+            list,
+            note,
+            button_hints,
+            dirty: true,
+        })
+    }
+
+    /// Shows the settings RetroArch would use with the selected scope's overrides.
+    fn reload(&mut self) -> Result<()> {
+        self.settings = VideoSettings::load(&self.overrides[self.scope..])?;
+
+        let locale = self.res.get::<Locale>();
+        self.list.set_right(
+            ASPECT_RATIO_ROW,
+            Box::new(aspect_ratio_select(&locale, self.settings.aspect_ratio)),
+        );
+        self.list.set_right(
+            INTEGER_SCALE_ROW,
+            Box::new(Toggle::new(
+                Point::zero(),
+                self.settings.integer_scale,
+                Alignment::Right,
+            )),
+        );
+        self.list.set_right(
+            SMOOTH_ROW,
+            Box::new(Toggle::new(
+                Point::zero(),
+                self.settings.smooth,
+                Alignment::Right,
+            )),
+        );
+        Ok(())
+    }
+
+    fn set_value(&mut self, row: usize, value: Value) -> Result<()> {
+        match row {
+            SCOPE_ROW => {
+                self.scope = value.as_int().unwrap() as usize;
+                return self.reload();
+            }
+            ASPECT_RATIO_ROW => {
+                self.settings.aspect_ratio = AspectRatio::ALL[value.as_int().unwrap() as usize];
+            }
+            INTEGER_SCALE_ROW => self.settings.integer_scale = value.as_bool().unwrap(),
+            SMOOTH_ROW => self.settings.smooth = value.as_bool().unwrap(),
+            _ => unreachable!("Invalid index"),
+        }
+        let overrides = &mut self.overrides[self.scope];
+        self.settings.write(overrides);
+        overrides.save()
+    }
+
+    /// Removes the selected scope's overrides, so that less specific ones apply again.
+    fn reset(&mut self) -> Result<()> {
+        let overrides = &mut self.overrides[self.scope];
+        VideoSettings::clear(overrides);
+        overrides.save()?;
+        self.reload()
+    }
+
+    /// Quits RetroArch and lets alliumd launch the game again with the new overrides.
+    async fn restart(&self, commands: Sender<Command>) -> Result<()> {
+        GameInfo::request_relaunch().await?;
+        commands.send(Command::Exit).await?;
+        Ok(())
+    }
+}
+
+fn aspect_ratio_select(locale: &Locale, aspect_ratio: AspectRatio) -> Select {
+    Select::new(
+        Point::zero(),
+        AspectRatio::ALL
+            .iter()
+            .position(|a| *a == aspect_ratio)
+            .unwrap_or_default(),
+        AspectRatio::ALL.iter().map(|a| locale.t(a.key())).collect(),
+        Alignment::Right,
+    )
+}
+
+#[async_trait(?Send)]
+impl View for VideoSettingsEditor {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.note.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.note.should_draw() && self.note.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.note.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    if let Err(e) = self.set_value(i, val) {
+                        error!("failed to save video settings: {}", e);
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::Y) => {
+                if let Err(e) = self.reset() {
+                    error!("failed to reset video settings: {}", e);
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) if self.is_running => {
+                self.restart(commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.note, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.note,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -3,31 +3,89 @@ use std::io::Write;
 #[cfg(feature = "miyoo")]
 use tokio::process::Command;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use log::{debug, warn};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::constants::ALLIUM_WIFI_SETTINGS;
+use crate::alliumd::AlliumDRequest;
+use crate::constants::{ALLIUM_WIFI_SETTINGS, ALLIUM_WPA_CLI, WIFI_SCAN_DURATION};
+
+/// Wi-Fi interface passed to `wpa_cli`.
+const WIFI_INTERFACE: &str = "wlan0";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WiFiSettings {
     pub wifi: bool,
-    pub ssid: String,
-    pub password: String,
+    /// Saved networks. Networks with a higher priority are preferred when several are visible.
+    #[serde(default)]
+    pub networks: Vec<SavedNetwork>,
     pub ntp: bool,
     pub telnet: bool,
     pub ftp: bool,
+    /// Whether the HTTP file server is running. It is started and stopped by alliumd.
+    #[serde(default)]
+    pub file_server: bool,
+    /// Token required to use the file server, regenerated each time it is enabled.
+    #[serde(default)]
+    pub file_server_token: String,
+    /// Single network from before multiple networks could be saved. Migrated on load.
+    #[serde(default, skip_serializing)]
+    ssid: String,
+    #[serde(default, skip_serializing)]
+    password: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedNetwork {
+    pub ssid: String,
+    pub password: String,
+    pub priority: i32,
+}
+
+/// A network found by scanning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedNetwork {
+    pub ssid: String,
+    /// Signal level in dBm.
+    pub signal: i32,
+    pub secured: bool,
+}
+
+impl ScannedNetwork {
+    /// Signal strength as a percentage, from -100 dBm (0%) to -50 dBm (100%).
+    pub fn strength(&self) -> u8 {
+        ((self.signal + 100) * 2).clamp(0, 100) as u8
+    }
+}
+
+/// Connection status as reported by `wpa_cli status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WiFiStatus {
+    /// e.g. `COMPLETED`, `SCANNING`, `DISCONNECTED`.
+    pub state: String,
+    pub ssid: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl WiFiStatus {
+    pub fn is_connected(&self) -> bool {
+        self.state == "COMPLETED"
+    }
 }
 
 impl WiFiSettings {
     pub fn new() -> Self {
         Self {
             wifi: false,
-            ssid: String::new(),
-            password: String::new(),
+            networks: Vec::new(),
             ntp: false,
             telnet: false,
             ftp: false,
+            file_server: false,
+            file_server_token: String::new(),
+            ssid: String::new(),
+            password: String::new(),
         }
     }
 
@@ -35,8 +93,9 @@ impl WiFiSettings {
         if ALLIUM_WIFI_SETTINGS.exists() {
             debug!("found state, loading from file");
             if let Ok(json) = fs::read_to_string(ALLIUM_WIFI_SETTINGS.as_path()) {
-                if let Ok(json) = serde_json::from_str(&json) {
-                    return Ok(json);
+                if let Ok(mut settings) = serde_json::from_str::<Self>(&json) {
+                    settings.migrate();
+                    return Ok(settings);
                 }
             }
             warn!("failed to read state file, removing");
@@ -45,6 +104,18 @@ impl WiFiSettings {
         Ok(Self::load_wpa_supplicant_conf().unwrap_or_default())
     }
 
+    /// Moves the network from the old single network fields into the saved networks.
+    fn migrate(&mut self) {
+        if !self.ssid.is_empty() && self.networks.is_empty() {
+            debug!("migrating saved network");
+            self.networks.push(SavedNetwork {
+                ssid: std::mem::take(&mut self.ssid),
+                password: std::mem::take(&mut self.password),
+                priority: 0,
+            });
+        }
+    }
+
     pub fn init(&self) -> Result<()> {
         if self.wifi {
             wifi_on()?;
@@ -71,24 +142,24 @@ impl WiFiSettings {
         Ok(())
     }
 
+    /// Tells alliumd that the settings were saved, so that it starts or stops the file server to
+    /// match. Failures are only logged, as alliumd isn't running in the simulator.
+    pub async fn notify_alliumd() {
+        let result = AlliumDRequest::ReloadWiFiSettings
+            .send()
+            .await
+            .and_then(|response| response.into_result());
+        if let Err(e) = result {
+            warn!("failed to notify alliumd of Wi-Fi settings: {}", e);
+        }
+    }
+
     fn load_wpa_supplicant_conf() -> Option<Self> {
         #[cfg(feature = "miyoo")]
         {
             let data = fs::read_to_string("/appconfigs/wpa_supplicant.conf").ok()?;
-
-            let ssid_index = data.find("ssid=\"")?;
-            let ssid = &data[ssid_index + 6..];
-            let ssid_end = ssid.find('"')?;
-            let ssid = &ssid[..ssid_end];
-
-            let psk_index = data.find("psk=\"")?;
-            let psk = &data[psk_index + 5..];
-            let psk_end = psk.find('"')?;
-            let psk = &psk[..psk_end];
-
             Some(Self {
-                ssid: ssid.to_string(),
-                password: psk.to_string(),
+                networks: parse_wpa_supplicant_conf(&data),
                 ..Default::default()
             })
         }
@@ -101,18 +172,7 @@ impl WiFiSettings {
         #[cfg(feature = "miyoo")]
         {
             let mut file = File::create("/appconfigs/wpa_supplicant.conf")?;
-            write!(
-                file,
-                r#"ctrl_interface=/var/run/wpa_supplicant
-update_config=1
-
-network={{
-	ssid="{ssid}"
-	psk="{password}"
-}}"#,
-                ssid = self.ssid.replace('"', "\\\""),
-                password = self.password.replace('"', "\\\""),
-            )?;
+            file.write_all(wpa_supplicant_conf(&self.networks).as_bytes())?;
         }
         Ok(())
     }
@@ -145,18 +205,53 @@ network={{
         Ok(())
     }
 
-    pub fn set_ssid(&mut self, ssid: String) -> Result<()> {
-        self.ssid = ssid;
-        if self.wifi {
-            self.set_wifi(self.wifi)?;
+    /// Saves a network, or updates its password if it is already saved. New networks are given
+    /// the highest priority so that they are connected to first.
+    pub fn add_network(&mut self, ssid: String, password: String) -> Result<()> {
+        if !is_valid_password(&password) {
+            bail!("invalid Wi-Fi password");
         }
-        Ok(())
+        if let Some(network) = self.networks.iter_mut().find(|n| n.ssid == ssid) {
+            network.password = password;
+        } else {
+            let priority = self
+                .networks
+                .iter()
+                .map(|n| n.priority + 1)
+                .max()
+                .unwrap_or_default();
+            self.networks.push(SavedNetwork {
+                ssid,
+                password,
+                priority,
+            });
+        }
+        self.save()?;
+        self.reconfigure()
     }
 
-    pub fn set_password(&mut self, password: String) -> Result<()> {
-        self.password = password;
+    pub fn remove_network(&mut self, ssid: &str) -> Result<()> {
+        self.networks.retain(|n| n.ssid != ssid);
+        self.save()?;
+        self.reconfigure()
+    }
+
+    pub fn set_priority(&mut self, ssid: &str, priority: i32) -> Result<()> {
+        if let Some(network) = self.networks.iter_mut().find(|n| n.ssid == ssid) {
+            network.priority = priority;
+        }
+        self.save()?;
+        self.reconfigure()
+    }
+
+    /// Reloads the saved networks into `wpa_supplicant` if Wi-Fi is on.
+    fn reconfigure(&self) -> Result<()> {
         if self.wifi {
-            self.set_wifi(self.wifi)?;
+            tokio::spawn(async {
+                if let Err(e) = wpa_cli(&["reconfigure"]).await {
+                    warn!("failed to reconfigure wpa_supplicant: {}", e);
+                }
+            });
         }
         Ok(())
     }
@@ -188,6 +283,14 @@ network={{
         }
         Ok(())
     }
+
+    pub fn toggle_file_server(&mut self, enabled: bool) -> Result<()> {
+        self.file_server = enabled;
+        if enabled {
+            self.file_server_token = generate_token();
+        }
+        Ok(())
+    }
 }
 
 impl Default for WiFiSettings {
@@ -378,6 +481,201 @@ pub fn ip_address() -> Option<String> {
     return None;
 }
 
+/// Runs `wpa_cli` with the given arguments and returns its output.
+async fn wpa_cli(args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new(ALLIUM_WPA_CLI.as_path())
+        .args(["-i", WIFI_INTERFACE])
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!("wpa_cli {:?} exited with {}", args, output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Scans for visible networks, strongest first.
+pub async fn scan() -> Result<Vec<ScannedNetwork>> {
+    wpa_cli(&["scan"]).await?;
+    tokio::time::sleep(WIFI_SCAN_DURATION).await;
+    Ok(parse_scan_results(&wpa_cli(&["scan_results"]).await?))
+}
+
+/// Returns the current connection status.
+pub async fn status() -> Result<WiFiStatus> {
+    Ok(parse_status(&wpa_cli(&["status"]).await?))
+}
+
+/// Parses the output of `wpa_cli scan_results`. Hidden networks are skipped, and networks seen
+/// on several access points are only listed once with the strongest signal.
+fn parse_scan_results(output: &str) -> Vec<ScannedNetwork> {
+    let mut networks: Vec<ScannedNetwork> = Vec::new();
+    // bssid / frequency / signal level / flags / ssid
+    for line in output.lines().filter(|l| !l.starts_with("bssid")) {
+        let fields: Vec<&str> = line.splitn(5, '\t').collect();
+        let [_, _, signal, flags, ssid] = fields[..] else {
+            continue;
+        };
+        let Ok(signal) = signal.trim().parse() else {
+            continue;
+        };
+        if ssid.is_empty() {
+            continue;
+        }
+        let network = ScannedNetwork {
+            ssid: ssid.to_string(),
+            signal,
+            secured: flags.contains("WPA") || flags.contains("WEP"),
+        };
+        match networks.iter_mut().find(|n| n.ssid == network.ssid) {
+            Some(existing) if existing.signal < network.signal => *existing = network,
+            Some(_) => {}
+            None => networks.push(network),
+        }
+    }
+    networks.sort_by_key(|n| std::cmp::Reverse(n.signal));
+    networks
+}
+
+/// Parses the output of `wpa_cli status`.
+fn parse_status(output: &str) -> WiFiStatus {
+    let mut status = WiFiStatus::default();
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some(("wpa_state", value)) => status.state = value.to_string(),
+            Some(("ssid", value)) => status.ssid = Some(value.to_string()),
+            Some(("ip_address", value)) => status.ip_address = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+/// Returns true if the password can be written to `wpa_supplicant.conf`: empty for an open
+/// network, a passphrase of 8 to 63 printable ASCII characters other than `"`, or a 64 digit hex
+/// PSK.
+pub fn is_valid_password(password: &str) -> bool {
+    password.is_empty()
+        || (8..=63).contains(&password.len())
+            && password
+                .bytes()
+                .all(|b| (b' '..=b'~').contains(&b) && b != b'"')
+        || password.len() == 64 && password.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parses the saved networks from `wpa_supplicant.conf`.
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+fn parse_wpa_supplicant_conf(data: &str) -> Vec<SavedNetwork> {
+    let mut networks = Vec::new();
+    let mut network: Option<SavedNetwork> = None;
+    for line in data.lines().map(str::trim) {
+        let Some(current) = network.as_mut() else {
+            if line == "network={" {
+                network = Some(SavedNetwork {
+                    ssid: String::new(),
+                    password: String::new(),
+                    priority: 0,
+                });
+            }
+            continue;
+        };
+        match line.split_once('=') {
+            Some(("ssid", value)) => current.ssid = parse_ssid(value),
+            Some(("psk", value)) => current.password = unquote(value),
+            Some(("priority", value)) => current.priority = value.parse().unwrap_or_default(),
+            _ if line == "}" => {
+                if let Some(network) = network.take().filter(|n| !n.ssid.is_empty()) {
+                    networks.push(network);
+                }
+            }
+            _ => {}
+        }
+    }
+    networks
+}
+
+/// Formats the saved networks as a `wpa_supplicant.conf`. wpa_supplicant has no escapes in
+/// quoted strings, so SSIDs that aren't plain printable ASCII are written as hex. Networks with
+/// passwords that can't be written are skipped.
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+fn wpa_supplicant_conf(networks: &[SavedNetwork]) -> String {
+    let mut conf = "ctrl_interface=/var/run/wpa_supplicant\nupdate_config=1\n".to_string();
+    for network in networks {
+        if !is_valid_password(&network.password) {
+            warn!(
+                "skipping network {:?} with an invalid password",
+                network.ssid
+            );
+            continue;
+        }
+        conf.push_str(&format!(
+            "\nnetwork={{\n\tssid={ssid}\n",
+            ssid = format_ssid(&network.ssid),
+        ));
+        if network.password.is_empty() {
+            conf.push_str("\tkey_mgmt=NONE\n");
+        } else if network.password.len() == 64 {
+            conf.push_str(&format!("\tpsk={}\n", network.password));
+        } else {
+            conf.push_str(&format!("\tpsk=\"{}\"\n", network.password));
+        }
+        conf.push_str(&format!("\tpriority={}\n}}\n", network.priority));
+    }
+    conf
+}
+
+/// Generates a short token that is easy to type in from the screen.
+fn generate_token() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    let mut rng = rand::thread_rng();
+    (0..8)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// Formats an SSID as a quoted string if it's plain printable ASCII, or as hex otherwise.
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+fn format_ssid(ssid: &str) -> String {
+    if ssid
+        .bytes()
+        .all(|b| (b' '..=b'~').contains(&b) && b != b'"' && b != b'\\')
+    {
+        format!("\"{}\"", ssid)
+    } else {
+        format!("{:x}", ByteBuf(ssid.as_bytes()))
+    }
+}
+
+/// Parses an SSID written as a quoted string or as hex.
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+fn parse_ssid(value: &str) -> String {
+    let value = value.trim();
+    if value.starts_with('"') {
+        return unquote(value);
+    }
+    let bytes: Option<Vec<u8>> = (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect();
+    bytes
+        .map(|b| String::from_utf8_lossy(&b).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg_attr(not(feature = "miyoo"), allow(dead_code))]
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
 struct ByteBuf<'a>(&'a [u8]);
 
 impl<'a> std::fmt::LowerHex for ByteBuf<'a> {
@@ -388,3 +686,88 @@ impl<'a> std::fmt::LowerHex for ByteBuf<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scan_results() {
+        let output = "bssid / frequency / signal level / flags / ssid\n\
+            aa:bb:cc:dd:ee:01\t2412\t-70\t[WPA2-PSK-CCMP][ESS]\tHome\n\
+            aa:bb:cc:dd:ee:02\t2437\t-55\t[WPA2-PSK-CCMP][ESS]\tHome\n\
+            aa:bb:cc:dd:ee:03\t2462\t-60\t[ESS]\tCafe Wi-Fi\n\
+            aa:bb:cc:dd:ee:04\t2462\t-40\t[WPA2-PSK-CCMP][ESS]\t\n";
+        assert_eq!(
+            parse_scan_results(output),
+            vec![
+                ScannedNetwork {
+                    ssid: "Home".to_string(),
+                    signal: -55,
+                    secured: true,
+                },
+                ScannedNetwork {
+                    ssid: "Cafe Wi-Fi".to_string(),
+                    signal: -60,
+                    secured: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let output =
+            "bssid=aa:bb:cc:dd:ee:02\nssid=Home\nwpa_state=COMPLETED\nip_address=192.168.1.20\n";
+        let status = parse_status(output);
+        assert!(status.is_connected());
+        assert_eq!(status.ssid.as_deref(), Some("Home"));
+        assert_eq!(status.ip_address.as_deref(), Some("192.168.1.20"));
+        assert!(!parse_status("wpa_state=SCANNING\n").is_connected());
+    }
+
+    #[test]
+    fn test_wpa_supplicant_conf() {
+        let networks = vec![
+            SavedNetwork {
+                ssid: "Home".to_string(),
+                password: "pass#word}".to_string(),
+                priority: 2,
+            },
+            SavedNetwork {
+                ssid: "Cafe".to_string(),
+                password: String::new(),
+                priority: 0,
+            },
+            SavedNetwork {
+                ssid: "Bob's \"Wi-Fi\"\n}".to_string(),
+                password: "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+                    .to_string(),
+                priority: 1,
+            },
+        ];
+        let conf = wpa_supplicant_conf(&networks);
+        assert!(conf.contains("priority=2"));
+        assert!(conf.contains("key_mgmt=NONE"));
+        assert!(conf.contains("\tssid=426f622773202257692d4669220a7d\n"));
+        assert_eq!(parse_wpa_supplicant_conf(&conf), networks);
+
+        let invalid = vec![SavedNetwork {
+            ssid: "Home".to_string(),
+            password: "pass\"word\n}".to_string(),
+            priority: 0,
+        }];
+        assert!(!wpa_supplicant_conf(&invalid).contains("network={"));
+    }
+
+    #[test]
+    fn test_is_valid_password() {
+        assert!(is_valid_password(""));
+        assert!(is_valid_password("password"));
+        assert!(!is_valid_password("short"));
+        assert!(!is_valid_password("pass\"word"));
+        assert!(!is_valid_password("pass\nword"));
+        assert!(!is_valid_password(&"g".repeat(64)));
+        assert!(is_valid_password(&"a0".repeat(32)));
+    }
+}
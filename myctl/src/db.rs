@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use common::database::{self, Database};
+use serde::Serialize;
+
+use crate::output::format_duration;
+
+/// A game in the database. Durations are in seconds.
+#[derive(Debug, Serialize)]
+pub struct Game {
+    pub name: String,
+    pub path: PathBuf,
+    pub image: Option<PathBuf>,
+    pub play_count: i64,
+    pub play_time: i64,
+    pub last_played: i64,
+}
+
+impl From<database::Game> for Game {
+    fn from(game: database::Game) -> Self {
+        Self {
+            name: game.name,
+            path: game.path,
+            image: game.image,
+            play_count: game.play_count,
+            play_time: game.play_time.num_seconds(),
+            last_played: game.last_played,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub games: i64,
+    pub played: i64,
+    pub play_count: i64,
+    pub play_time: i64,
+}
+
+pub fn stats() -> Result<Stats> {
+    let stats = Database::new()?.select_stats()?;
+    Ok(Stats {
+        games: stats.games,
+        played: stats.played,
+        play_count: stats.play_count,
+        play_time: stats.play_time.num_seconds(),
+    })
+}
+
+pub fn search(query: &str, limit: i64) -> Result<Vec<Game>> {
+    Ok(Database::new()?
+        .search(query, limit)?
+        .into_iter()
+        .map(Game::from)
+        .collect())
+}
+
+pub fn export() -> Result<Vec<Game>> {
+    Ok(Database::new()?
+        .select_all_games()?
+        .into_iter()
+        .map(Game::from)
+        .collect())
+}
+
+pub fn format_stats(stats: &Stats) -> String {
+    format!(
+        "Games: {}\nPlayed: {}\nPlay count: {}\nPlay time: {}",
+        stats.games,
+        stats.played,
+        stats.play_count,
+        format_duration(stats.play_time)
+    )
+}
+
+/// Formats games as tab-separated name, path, play count and play time.
+pub fn format_games(games: &[Game]) -> String {
+    games
+        .iter()
+        .map(|game| {
+            format!(
+                "{}\t{}\t{}\t{}",
+                game.name,
+                game.path.display(),
+                game.play_count,
+                format_duration(game.play_time)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use common::alliumd::{AlliumDRequest, AlliumDResponse, GameStatus};
+
+use crate::output::format_duration;
+
+/// Launches a game through alliumd, as if it was selected in the launcher.
+pub async fn launch(path: PathBuf) -> Result<()> {
+    AlliumDRequest::Launch { path }
+        .send()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Returns the running game, if any.
+pub async fn current() -> Result<Option<GameStatus>> {
+    let AlliumDResponse::Status(status) = AlliumDRequest::Status.send().await?.into_result()?
+    else {
+        bail!("Unexpected response from alliumd.");
+    };
+    Ok(status.game)
+}
+
+pub fn format(game: &Option<GameStatus>) -> String {
+    let Some(game) = game else {
+        return "No game is running.".to_string();
+    };
+    let mut lines = vec![game.name.clone(), game.path.display().to_string()];
+    if let Some(core) = &game.core {
+        lines.push(format!("Core: {}", core));
+    }
+    lines.push(format!("Play time: {}", format_duration(game.play_time)));
+    lines.join("\n")
+}
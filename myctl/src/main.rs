@@ -1,9 +1,21 @@
-use anyhow::Result;
-use clap::{arg, value_parser, Command};
+use std::path::PathBuf;
+use std::process;
+
+use anyhow::{bail, Result};
+use clap::{arg, value_parser, ArgMatches, Command};
+use common::alliumd::{AlliumDRequest, AlliumDResponse};
+use common::retroarch::RetroArchCommand;
+use log::LevelFilter;
 use simple_logger::SimpleLogger;
 
+use crate::output::Output;
+
+mod db;
 mod display;
+mod game;
+mod output;
 mod volume;
+mod wifi;
 
 fn cli() -> Command {
     Command::new(env!("CARGO_CRATE_NAME"))
@@ -11,6 +23,7 @@ fn cli() -> Command {
         .subcommand_required(true)
         .arg_required_else_help(true)
         .allow_external_subcommands(true)
+        .arg(arg!(--json "Print output as JSON").global(true))
         .subcommand(
             Command::new("volume").arg(
                 arg!([VOLUME] "Volume to set")
@@ -57,19 +70,79 @@ fn cli() -> Command {
                     ),
                 ),
         )
+        .subcommand(
+            Command::new("game")
+                .about("Launches games and shows the running game")
+                .subcommand_required(true)
+                .subcommand(Command::new("launch").arg(
+                    arg!(<PATH> "Path of the game to launch").value_parser(value_parser!(PathBuf)),
+                ))
+                .subcommand(Command::new("current")),
+        )
+        .subcommand(
+            Command::new("retroarch")
+                .about("Sends a command to RetroArch, e.g. SAVE_STATE or SET_STATE_SLOT 2")
+                .arg(arg!(<COMMAND> ... "Command and its argument")),
+        )
+        .subcommand(
+            Command::new("db")
+                .about("Queries the game database")
+                .subcommand_required(true)
+                .subcommand(Command::new("stats"))
+                .subcommand(
+                    Command::new("search")
+                        .arg(arg!(<QUERY> "Start of a word in the game's name"))
+                        .arg(
+                            arg!(--limit <LIMIT> "Maximum number of games")
+                                .value_parser(value_parser!(i64))
+                                .default_value("50"),
+                        ),
+                )
+                .subcommand(Command::new("export")),
+        )
+        .subcommand(Command::new("battery"))
+        .subcommand(
+            Command::new("wifi")
+                .subcommand_required(true)
+                .subcommand(Command::new("status"))
+                .subcommand(
+                    Command::new("connect")
+                        .arg(arg!(<SSID> "Network to connect to"))
+                        .arg(arg!([PASSWORD] "Password of the network").default_value("")),
+                ),
+        )
+        .subcommand(Command::new("screenshot"))
+        .subcommand(Command::new("suspend").about("Puts the device to sleep"))
+        .subcommand(Command::new("resume").about("Wakes the device up"))
+        .subcommand(Command::new("shutdown").about("Quits the running game and powers off"))
 }
 
-fn main() -> Result<()> {
-    SimpleLogger::new().init().unwrap();
+#[tokio::main]
+async fn main() {
+    // Logs would get mixed into the output, so they're only shown when asked for with RUST_LOG
+    SimpleLogger::new()
+        .with_level(LevelFilter::Off)
+        .env()
+        .init()
+        .unwrap();
 
     let matches = cli().get_matches();
+    let output = Output::new(matches.get_flag("json"));
+
+    if let Err(e) = run(&matches, &output).await {
+        output.error(&e);
+        process::exit(1);
+    }
+}
 
+async fn run(matches: &ArgMatches, output: &Output) -> Result<()> {
     match matches.subcommand() {
         Some(("volume", sub_matches)) => {
             if let Some(vol) = sub_matches.get_one::<i32>("VOLUME") {
                 volume::set(*vol)?;
+                output.ok()?;
             } else {
-                println!("{}", volume::get()?);
+                output.print(&volume::get()?, i32::to_string)?;
             }
         }
         Some(("display", sub_matches)) => {
@@ -78,36 +151,41 @@ fn main() -> Result<()> {
                     ("brightness", sub_matches) => {
                         if let Some(brightness) = sub_matches.get_one::<i32>("BRIGHTNESS") {
                             display::set_brightness(*brightness)?;
+                            output.ok()?;
                         } else {
-                            println!("{}", display::get_brightness()?);
+                            output.print(&display::get_brightness()?, i32::to_string)?;
                         }
                     }
                     ("lumination", sub_matches) => {
                         if let Some(lumination) = sub_matches.get_one::<i32>("LUMINATION") {
                             display::set_lumination(*lumination)?;
+                            output.ok()?;
                         } else {
-                            println!("{}", display::get_lumination()?);
+                            output.print(&display::get_lumination()?, i32::to_string)?;
                         }
                     }
                     ("hue", sub_matches) => {
                         if let Some(hue) = sub_matches.get_one::<i32>("HUE") {
                             display::set_hue(*hue)?;
+                            output.ok()?;
                         } else {
-                            println!("{}", display::get_hue()?);
+                            output.print(&display::get_hue()?, i32::to_string)?;
                         }
                     }
                     ("saturation", sub_matches) => {
                         if let Some(saturation) = sub_matches.get_one::<i32>("SATURATION") {
                             display::set_saturation(*saturation)?;
+                            output.ok()?;
                         } else {
-                            println!("{}", display::get_saturation()?);
+                            output.print(&display::get_saturation()?, i32::to_string)?;
                         }
                     }
                     ("contrast", sub_matches) => {
                         if let Some(contrast) = sub_matches.get_one::<i32>("CONTRAST") {
                             display::set_contrast(*contrast)?;
+                            output.ok()?;
                         } else {
-                            println!("{}", display::get_contrast()?);
+                            output.print(&display::get_contrast()?, i32::to_string)?;
                         }
                     }
                     _ => unreachable!(),
@@ -116,6 +194,86 @@ fn main() -> Result<()> {
                 unreachable!()
             }
         }
+        Some(("game", sub_matches)) => match sub_matches.subcommand() {
+            Some(("launch", sub_matches)) => {
+                let path = sub_matches.get_one::<PathBuf>("PATH").unwrap();
+                game::launch(path.clone()).await?;
+                output.ok()?;
+            }
+            Some(("current", _)) => output.print(&game::current().await?, game::format)?,
+            _ => unreachable!(),
+        },
+        Some(("retroarch", sub_matches)) => {
+            let command = sub_matches
+                .get_many::<String>("COMMAND")
+                .unwrap()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" ")
+                .parse::<RetroArchCommand>()?;
+            if command.has_reply() {
+                let reply = command
+                    .send_recv()
+                    .await?
+                    .map(|reply| reply.trim_end().to_string());
+                output.print(&reply, |reply| reply.clone().unwrap_or_default())?;
+            } else {
+                command.send().await?;
+                output.ok()?;
+            }
+        }
+        Some(("db", sub_matches)) => match sub_matches.subcommand() {
+            Some(("stats", _)) => output.print(&db::stats()?, db::format_stats)?,
+            Some(("search", sub_matches)) => {
+                let query = sub_matches.get_one::<String>("QUERY").unwrap();
+                let limit = *sub_matches.get_one::<i64>("limit").unwrap();
+                output.print(&db::search(query, limit)?, |games| db::format_games(games))?;
+            }
+            Some(("export", _)) => {
+                output.print(&db::export()?, |games| db::format_games(games))?;
+            }
+            _ => unreachable!(),
+        },
+        Some(("battery", _)) => {
+            let AlliumDResponse::Status(status) =
+                AlliumDRequest::Status.send().await?.into_result()?
+            else {
+                bail!("Unexpected response from alliumd.");
+            };
+            output.print(&status.battery, |battery| {
+                if battery.charging {
+                    format!("{}% (charging)", battery.percentage)
+                } else {
+                    format!("{}%", battery.percentage)
+                }
+            })?;
+        }
+        Some(("wifi", sub_matches)) => match sub_matches.subcommand() {
+            Some(("status", _)) => output.print(&wifi::status().await?, wifi::format_status)?,
+            Some(("connect", sub_matches)) => {
+                let ssid = sub_matches.get_one::<String>("SSID").unwrap();
+                let password = sub_matches.get_one::<String>("PASSWORD").unwrap();
+                let status = wifi::connect(ssid.clone(), password.clone()).await?;
+                output.print(&status, wifi::format_status)?;
+            }
+            _ => unreachable!(),
+        },
+        Some(("screenshot", _)) => {
+            AlliumDRequest::Screenshot.send().await?.into_result()?;
+            output.ok()?;
+        }
+        Some(("suspend", _)) => {
+            AlliumDRequest::Suspend.send().await?.into_result()?;
+            output.ok()?;
+        }
+        Some(("resume", _)) => {
+            AlliumDRequest::Resume.send().await?.into_result()?;
+            output.ok()?;
+        }
+        Some(("shutdown", _)) => {
+            AlliumDRequest::Shutdown.send().await?.into_result()?;
+            output.ok()?;
+        }
         _ => unreachable!(),
     }
 
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use common::alliumd::AlliumDResponse;
+use serde::Serialize;
+
+/// Prints the results of commands, as JSON with `--json` and as text otherwise.
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Self { json }
+    }
+
+    /// Prints a value, formatted by `text` unless printing JSON.
+    pub fn print<T: Serialize>(&self, value: &T, text: impl FnOnce(&T) -> String) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string(value)?);
+        } else {
+            let text = text(value);
+            if !text.is_empty() {
+                println!("{}", text);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports that a command without output succeeded. Only JSON has something to print.
+    pub fn ok(&self) -> Result<()> {
+        self.print(&AlliumDResponse::Ok, |_| String::new())
+    }
+
+    /// Reports that a command failed, in the same format as alliumd's errors for JSON.
+    pub fn error(&self, error: &anyhow::Error) {
+        let message = format!("{:#}", error);
+        if self.json {
+            let response = AlliumDResponse::Error { message };
+            if let Ok(json) = serde_json::to_string(&response) {
+                println!("{}", json);
+            }
+        } else {
+            eprintln!("Error: {}", message);
+        }
+    }
+}
+
+/// Formats seconds as e.g. `1h 05m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
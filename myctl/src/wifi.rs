@@ -0,0 +1,29 @@
+use anyhow::Result;
+use common::wifi::{self, WiFiSettings, WiFiStatus};
+
+pub async fn status() -> Result<WiFiStatus> {
+    wifi::status().await
+}
+
+/// Saves the network, turning Wi-Fi on if needed, and waits until connected.
+pub async fn connect(ssid: String, password: String) -> Result<WiFiStatus> {
+    let mut settings = WiFiSettings::load()?;
+    if !settings.wifi {
+        settings.set_wifi(true)?;
+    }
+    settings.add_network(ssid, password)?;
+    WiFiSettings::notify_alliumd().await;
+    wifi::wait_for_wifi().await?;
+    wifi::status().await
+}
+
+pub fn format_status(status: &WiFiStatus) -> String {
+    let mut lines = vec![status.state.clone()];
+    if let Some(ssid) = &status.ssid {
+        lines.push(format!("SSID: {}", ssid));
+    }
+    if let Some(ip_address) = &status.ip_address {
+        lines.push(format!("IP address: {}", ip_address));
+    }
+    lines.join("\n")
+}